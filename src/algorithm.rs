@@ -0,0 +1,151 @@
+//! Sunrise and sunset by more than one method, and how far they differ over
+//! a range of days, to judge whether the fast sunrise equation is good
+//! enough at a latitude.
+
+use std::io;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+use crate::format::{Field, OutputFormatter, Table, Value};
+use crate::parallel;
+use crate::solar::{datetime2julian, degrees::crossings_tz, jdn, julian2utc, sun_position};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algorithm {
+    /// The sunrise equation, which the rest of so2lar uses.
+    Simple,
+    /// Crossings of the horizon by the apparent position of the sun, with
+    /// nutation, ΔT and parallax, found by iterating from the simple ones.
+    Position,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 2] = [Algorithm::Simple, Algorithm::Position];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Simple => "simple",
+            Algorithm::Position => "position",
+        }
+    }
+
+    /// Sunrise and sunset on the day `today` with the sun crossing
+    /// `altitude`, `None` when the sunrise equation finds none.
+    pub fn sunrise_sunset(&self, lat: f64, long: f64, today: f64, altitude: f64) -> Option<RiseSet> {
+        let (rise, set) = crossings_tz(lat, long, today, altitude, &Utc)?;
+        match self {
+            Algorithm::Simple => Some((rise, set)),
+            Algorithm::Position => Some((refine(lat, long, rise, altitude)?, refine(lat, long, set, altitude)?)),
+        }
+    }
+}
+
+/// The crossing of `altitude` near `t` by Newton's method on the elevation.
+fn refine(lat: f64, long: f64, t: DateTime<Utc>, altitude: f64) -> Option<DateTime<Utc>> {
+    // A minute, in days, for the rate of change of the elevation
+    const STEP: f64 = 1.0 / 1440.0;
+    let elevation = |j: f64| sun_position(lat, long, j).0 - altitude;
+    let mut j = datetime2julian(&t);
+    for _ in 0..8 {
+        let rate = (elevation(j + STEP) - elevation(j - STEP)) / (2.0 * STEP);
+        let step = elevation(j) / rate;
+        // Keep to the neighbourhood of the estimate, where the crossing is
+        if !step.is_finite() || step.abs() > 0.1 {
+            return None;
+        }
+        j -= step;
+        if step.abs() < 1e-9 {
+            break;
+        }
+    }
+    Some(julian2utc(j))
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Algorithm, String> {
+        Algorithm::ALL.into_iter().find(|a| a.name() == s.to_lowercase()).ok_or_else(|| {
+            let names: Vec<&str> = Algorithm::ALL.iter().map(Algorithm::name).collect();
+            format!("unknown algorithm `{}` (expected {})", s, names.join(" or "))
+        })
+    }
+}
+
+/// Sunrise and sunset of a day.
+pub type RiseSet = (DateTime<Utc>, DateTime<Utc>);
+
+/// Sunrise and sunset of each day by two algorithms.
+pub struct AlgorithmDiff {
+    pub algorithms: [Algorithm; 2],
+    pub lat: f64,
+    pub long: f64,
+    pub days: Vec<(NaiveDate, Option<[RiseSet; 2]>)>,
+}
+
+impl AlgorithmDiff {
+    /// The days from `from` through `to`.
+    pub fn new(algorithms: [Algorithm; 2], lat: f64, long: f64, from: NaiveDate, to: NaiveDate, altitude: f64) -> AlgorithmDiff {
+        let dates: Vec<NaiveDate> = from.iter_days().take_while(|d| *d <= to).collect();
+        let days = parallel::map(&dates, |date| {
+            let [a, b] = algorithms.map(|algorithm| algorithm.sunrise_sunset(lat, long, jdn(*date), altitude));
+            (*date, a.zip(b).map(<[_; 2]>::from))
+        });
+        AlgorithmDiff { algorithms, lat, long, days }
+    }
+
+    /// Second minus first algorithm's sunrise and sunset of the days both have.
+    pub fn differences(&self) -> Vec<(TimeDelta, TimeDelta)> {
+        self.days.iter().filter_map(|(_, times)| times.map(|[a, b]| (b.0 - a.0, b.1 - a.1))).collect()
+    }
+
+    fn table(&self) -> Table {
+        let mut table = Table::new(vec!["date", "sunrise_a", "sunrise_b", "sunrise_diff_seconds", "sunset_a", "sunset_b", "sunset_diff_seconds"]);
+        let time = |t: DateTime<Utc>| t.format("%H:%M:%S%.3f").to_string();
+        let seconds = |d: TimeDelta| d.num_milliseconds() as f64 / 1000.0;
+        for (date, times) in &self.days {
+            let row = match times {
+                Some([a, b]) => vec![
+                    date.to_string().into(),
+                    time(a.0).into(),
+                    time(b.0).into(),
+                    seconds(b.0 - a.0).into(),
+                    time(a.1).into(),
+                    time(b.1).into(),
+                    seconds(b.1 - a.1).into(),
+                ],
+                None => [date.to_string().into()].into_iter().chain([(); 6].map(|_| Value::Null)).collect(),
+            };
+            table.push(row);
+        }
+        table
+    }
+
+    fn summary(&self) -> Vec<Field> {
+        let differences = self.differences();
+        let seconds = |label, key, d: Option<f64>| Field {
+            label,
+            key,
+            value: d.map(|d| (d * 1000.0).round() / 1000.0).into(),
+            text: d.map_or("n/a".to_string(), |d| format!("{:.3}s", d)),
+        };
+        let largest = |f: fn(&(TimeDelta, TimeDelta)) -> TimeDelta| differences.iter().map(f).map(|d| d.num_milliseconds().abs() as f64 / 1000.0).reduce(f64::max);
+        let mean = |f: fn(&(TimeDelta, TimeDelta)) -> TimeDelta| {
+            (!differences.is_empty()).then(|| differences.iter().map(f).map(|d| d.num_milliseconds().abs() as f64 / 1000.0).sum::<f64>() / differences.len() as f64)
+        };
+        vec![
+            seconds("Largest sunrise diff", "max_sunrise_diff_seconds", largest(|d| d.0)),
+            seconds("Mean sunrise diff", "mean_sunrise_diff_seconds", mean(|d| d.0)),
+            seconds("Largest sunset diff", "max_sunset_diff_seconds", largest(|d| d.1)),
+            seconds("Mean sunset diff", "mean_sunset_diff_seconds", mean(|d| d.1)),
+        ]
+    }
+
+    pub fn write(&self, w: &mut dyn io::Write, formatter: &dyn OutputFormatter) -> io::Result<()> {
+        let [a, b] = self.algorithms.map(|a| a.name());
+        let title = format!("Sunrise and sunset (UTC) at {:.4}, {:.4}: a = {}, b = {}", self.lat, self.long, a, b);
+        let meta = [("algorithm_a", a.into()), ("algorithm_b", b.into())];
+        formatter.report(w, &title, &meta, &self.table(), &self.summary())
+    }
+}
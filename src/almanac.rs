@@ -0,0 +1,101 @@
+//! Daily page of the Sun's data as printed in nautical almanacs.
+
+use std::io;
+
+use chrono::{NaiveDate, TimeDelta};
+
+use crate::format::{Field, OutputFormatter, Table};
+use crate::solar::{datetime2julian, subsolar_point, sun_distance};
+
+/// Greenwich hour angle and declination of the Sun in degrees at the UTC
+/// Julian date `j`.
+pub fn gha_dec(j: f64) -> (f64, f64) {
+    let (dec, long) = subsolar_point(j);
+    ((-long).rem_euclid(360.0), dec)
+}
+
+/// Apparent minus mean solar time at the UTC Julian date `j`.
+pub fn equation_of_time(j: f64) -> TimeDelta {
+    let (gha, _) = gha_dec(j);
+    let mean = ((j - 0.5).fract() * 360.0 + 180.0).rem_euclid(360.0);
+    let degrees = (gha - mean + 540.0).rem_euclid(360.0) - 180.0;
+    TimeDelta::milliseconds((degrees * 240_000.0).round() as i64)
+}
+
+/// Angle as degrees and decimal minutes, e.g. `183° 35.6'`.
+fn format_angle(degrees: f64) -> String {
+    let tenths = (degrees.abs() * 600.0).round() as i64;
+    format!("{}° {:04.1}'", tenths / 600, (tenths % 600) as f64 / 10.0)
+}
+
+/// Declination with its hemisphere, e.g. `S 8° 10.2'`.
+fn format_dec(degrees: f64) -> String {
+    format!("{} {}", if degrees < 0.0 { 'S' } else { 'N' }, format_angle(degrees))
+}
+
+/// `mm ss` of an equation of time with its sign, e.g. `-13 58`.
+fn format_eot(d: TimeDelta) -> String {
+    let seconds = (d.num_milliseconds().abs() + 500) / 1000;
+    format!("{}{:02} {:02}", if d < TimeDelta::zero() { "-" } else { "+" }, seconds / 60, seconds % 60)
+}
+
+pub struct AlmanacPage {
+    pub date: NaiveDate,
+    /// GHA and declination at 00h to 23h UT.
+    pub hours: Vec<(f64, f64)>,
+    pub eot_midnight: TimeDelta,
+    pub eot_noon: TimeDelta,
+}
+
+impl AlmanacPage {
+    pub fn new(date: NaiveDate) -> AlmanacPage {
+        let midnight = datetime2julian(&date.and_hms_opt(0, 0, 0).unwrap().and_utc());
+        let hours = (0..24).map(|h| gha_dec(midnight + h as f64 / 24.0)).collect();
+        AlmanacPage { date, hours, eot_midnight: equation_of_time(midnight), eot_noon: equation_of_time(midnight + 0.5) }
+    }
+
+    /// Time of the Sun's upper transit of the Greenwich meridian.
+    pub fn meridian_passage(&self) -> TimeDelta {
+        TimeDelta::hours(12) - self.eot_noon
+    }
+
+    /// Semi-diameter of the Sun in arc minutes at noon.
+    pub fn semi_diameter(&self) -> f64 {
+        let noon = datetime2julian(&self.date.and_hms_opt(12, 0, 0).unwrap().and_utc());
+        15.99 / sun_distance(noon)
+    }
+
+    /// Mean hourly change of the declination in arc minutes, `d` in almanacs.
+    pub fn d(&self) -> f64 {
+        (self.hours[23].1 - self.hours[0].1) * 60.0 / 23.0
+    }
+
+    fn table(&self) -> Table {
+        let mut table = Table::new(vec!["ut", "gha", "dec", "gha_degrees", "dec_degrees"]);
+        let round = |x: f64| (x * 1e4).round() / 1e4;
+        for (h, &(gha, dec)) in self.hours.iter().enumerate() {
+            table.push(vec![format!("{:02}", h).into(), format_angle(gha).into(), format_dec(dec).into(), round(gha).into(), round(dec).into()]);
+        }
+        table
+    }
+
+    fn summary(&self) -> Vec<Field> {
+        let passage = (self.meridian_passage().num_seconds() + 30) / 60;
+        let passage = format!("{:02}:{:02}", passage / 60, passage % 60);
+        let eot = |label, key, d: TimeDelta| Field { label, key, value: (d.num_milliseconds() as f64 / 1000.0).into(), text: format_eot(d) };
+        let minutes = |label, key, x: f64, text| Field { label, key, value: ((x * 10.0).round() / 10.0).into(), text };
+        vec![
+            eot("Equation of time 00h", "eot_00h_seconds", self.eot_midnight),
+            eot("Equation of time 12h", "eot_12h_seconds", self.eot_noon),
+            Field { label: "Meridian passage", key: "meridian_passage", value: passage.clone().into(), text: passage },
+            minutes("Semi-diameter", "semi_diameter_arcmin", self.semi_diameter(), format!("{:.1}'", self.semi_diameter())),
+            minutes("d", "d_arcmin", self.d(), format!("{:+.1}'", self.d())),
+        ]
+    }
+
+    pub fn write(&self, w: &mut dyn io::Write, formatter: &dyn OutputFormatter) -> io::Result<()> {
+        let title = format!("Sun {} (UT)", self.date.format("%Y-%m-%d, %A"));
+        let meta = [("date", self.date.to_string().into())];
+        formatter.report(w, &title, &meta, &self.table(), &self.summary())
+    }
+}
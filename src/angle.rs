@@ -0,0 +1,151 @@
+//! Angles and coordinates as distinct types, so that a latitude cannot be
+//! passed as a longitude and degrees cannot be passed as radians. The
+//! sunrise and crossing functions in [`crate::solar`] take [`Coordinates`];
+//! the sun's position and the day lengths still take plain degrees.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, TimeDelta, Utc};
+
+#[cfg(feature = "chrono")]
+use crate::solar::{day_length, sunrise_sunset_tz};
+use crate::solar::{sun_position, DaySolution};
+use crate::validate::{self, InvalidInput};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Degrees(pub f64);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Radians(pub f64);
+
+impl From<Degrees> for Radians {
+    fn from(d: Degrees) -> Radians {
+        Radians(d.0.to_radians())
+    }
+}
+
+impl From<Radians> for Degrees {
+    fn from(r: Radians) -> Degrees {
+        Degrees(r.0.to_degrees())
+    }
+}
+
+impl fmt::Display for Degrees {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(p) => write!(f, "{:.*}°", p, self.0),
+            None => write!(f, "{}°", self.0),
+        }
+    }
+}
+
+/// Degrees north of the equator, from -90 to 90.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Latitude(f64);
+
+impl Latitude {
+    pub fn new(degrees: f64) -> Result<Latitude, InvalidInput> {
+        validate::latitude(degrees).map(Latitude)
+    }
+
+    pub fn degrees(self) -> Degrees {
+        Degrees(self.0)
+    }
+
+    pub fn radians(self) -> Radians {
+        self.degrees().into()
+    }
+}
+
+/// Degrees east of Greenwich, from -180 to 180.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Longitude(f64);
+
+impl Longitude {
+    pub fn new(degrees: f64) -> Result<Longitude, InvalidInput> {
+        validate::longitude(degrees).map(Longitude)
+    }
+
+    pub fn degrees(self) -> Degrees {
+        Degrees(self.0)
+    }
+
+    pub fn radians(self) -> Radians {
+        self.degrees().into()
+    }
+}
+
+impl fmt::Display for Latitude {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.4}°{}", self.0.abs(), if self.0 >= 0.0 { 'N' } else { 'S' })
+    }
+}
+
+impl fmt::Display for Longitude {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.4}°{}", self.0.abs(), if self.0 >= 0.0 { 'E' } else { 'W' })
+    }
+}
+
+fn parse_degrees(s: &str, what: &str) -> Result<f64, InvalidInput> {
+    s.trim().parse().map_err(|_| InvalidInput(format!("invalid {} `{}` (expected degrees)", what, s)))
+}
+
+impl FromStr for Latitude {
+    type Err = InvalidInput;
+
+    fn from_str(s: &str) -> Result<Latitude, InvalidInput> {
+        Latitude::new(parse_degrees(s, "latitude")?)
+    }
+}
+
+impl FromStr for Longitude {
+    type Err = InvalidInput;
+
+    fn from_str(s: &str) -> Result<Longitude, InvalidInput> {
+        Longitude::new(parse_degrees(s, "longitude")?)
+    }
+}
+
+/// A checked location on the Earth.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Coordinates {
+    pub lat: Latitude,
+    pub long: Longitude,
+}
+
+impl Coordinates {
+    pub fn new(lat: f64, long: f64) -> Result<Coordinates, InvalidInput> {
+        Ok(Coordinates { lat: Latitude::new(lat)?, long: Longitude::new(long)? })
+    }
+
+    /// Sunrise, sunset and noon on the day with Julian day number `today`.
+    pub fn day(&self, today: f64) -> DaySolution {
+        DaySolution::new(self, today)
+    }
+
+    /// Sunrise and sunset on the day `today`, `None` on polar days and nights.
+    #[cfg(feature = "chrono")]
+    pub fn sunrise_sunset(&self, today: f64) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        sunrise_sunset_tz(self, today, &Utc)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn day_length(&self, today: f64) -> TimeDelta {
+        day_length(self.lat.0, self.long.0, today)
+    }
+
+    /// Elevation and azimuth of the sun at the Julian date `j`.
+    pub fn sun_position(&self, j: f64) -> (Degrees, Degrees) {
+        let (elevation, azimuth) = sun_position(self.lat.0, self.long.0, j);
+        (Degrees(elevation), Degrees(azimuth))
+    }
+}
+
+impl fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}", self.lat, self.long)
+    }
+}
@@ -0,0 +1,91 @@
+//! Night as defined by aviation regulations, offset from sunrise and sunset
+//! or bounded by civil twilight.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::duration::parse_signed;
+use crate::solar::{degrees::crossings_tz, sunrise_altitude};
+
+/// Sun altitude at the end of evening and start of morning civil twilight.
+const CIVIL_TWILIGHT: f64 = -6.0;
+
+/// The evening and morning events a night is measured from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reference {
+    Sun,
+    CivilTwilight,
+}
+
+/// A night that starts `offset` after the evening event and ends `offset`
+/// before the following morning event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NightRule {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub reference: Reference,
+    pub offset: TimeDelta,
+}
+
+impl NightRule {
+    /// Start and end of the night beginning on the evening of the day
+    /// `today`, `None` if the sun does not cross the reference altitude.
+    pub fn night(&self, lat: f64, long: f64, today: f64) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let altitude = match self.reference {
+            Reference::Sun => sunrise_altitude(0.0),
+            Reference::CivilTwilight => CIVIL_TWILIGHT,
+        };
+        let (_, evening) = crossings_tz(lat, long, today, altitude, &Utc)?;
+        let (morning, _) = crossings_tz(lat, long, today + 1.0, altitude, &Utc)?;
+        Some((evening + self.offset, morning - self.offset))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rules {
+    /// US 14 CFR: night (§ 1.1), position lights (§ 91.209) and night
+    /// passenger-carrying currency (§ 61.57(b)).
+    Faa,
+    /// SERA.101 as applied by EASA and ICAO Annex 2: civil twilight only.
+    Easa,
+    /// Night from `offset` after sunset to `offset` before sunrise, as in
+    /// national rules based on a fixed interval such as 30 minutes.
+    Offset(TimeDelta),
+}
+
+impl Rules {
+    pub fn nights(&self) -> Vec<NightRule> {
+        match *self {
+            Rules::Faa => vec![
+                NightRule { name: "night", description: "end to start of civil twilight", reference: Reference::CivilTwilight, offset: TimeDelta::zero() },
+                NightRule { name: "position_lights", description: "sunset to sunrise", reference: Reference::Sun, offset: TimeDelta::zero() },
+                NightRule {
+                    name: "night_currency",
+                    description: "1h after sunset to 1h before sunrise",
+                    reference: Reference::Sun,
+                    offset: TimeDelta::hours(1),
+                },
+            ],
+            Rules::Easa => vec![NightRule {
+                name: "night",
+                description: "end to start of civil twilight",
+                reference: Reference::CivilTwilight,
+                offset: TimeDelta::zero(),
+            }],
+            Rules::Offset(offset) => vec![NightRule { name: "night", description: "offset from sunset and sunrise", reference: Reference::Sun, offset }],
+        }
+    }
+}
+
+impl FromStr for Rules {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Rules, String> {
+        match s.to_lowercase().as_str() {
+            "faa" => Ok(Rules::Faa),
+            "easa" | "icao" => Ok(Rules::Easa),
+            _ => parse_signed(s).map(Rules::Offset).map_err(|_| format!("unknown rules `{}` (expected faa, easa or an offset like 30m)", s)),
+        }
+    }
+}
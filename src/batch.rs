@@ -0,0 +1,92 @@
+//! Sunrise, sunset and day length for every date of a range at every one of
+//! a list of places, as one long table. The sunrise equation's elements
+//! depend on the date and the longitude only, so they are solved once per
+//! date and meridian and shared by all places on it, as in a grid.
+
+use std::collections::HashMap;
+use std::error::Error;
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+use crate::parallel;
+use crate::solar::{day_length_at, jdn, julian2datetime_tz, DaySolution};
+use crate::validate;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Place {
+    pub name: String,
+    pub lat: f64,
+    pub long: f64,
+}
+
+/// Places from `name,lat,long` or `lat,long` lines; unnamed places are named
+/// by their coordinates. Blank lines, `#` comments and a header are skipped.
+pub fn parse_places(s: &str) -> Result<Vec<Place>, Box<dyn Error>> {
+    let mut places = Vec::new();
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split([',', ';']).map(|f| f.trim().trim_matches('"')).collect();
+        let (name, lat, long) = match fields.as_slice() {
+            [lat, long] => (None, *lat, *long),
+            [name, lat, long, ..] => (Some(*name), *lat, *long),
+            _ => return Err(format!("line {}: expected `name,lat,long` or `lat,long`", i + 1).into()),
+        };
+        let (Ok(lat), Ok(long)) = (lat.parse::<f64>(), long.parse::<f64>()) else {
+            // A non-numeric first row is a CSV header
+            if places.is_empty() && lat.parse::<f64>().is_err() {
+                continue;
+            }
+            return Err(format!("line {}: invalid coordinates `{}`", i + 1, line).into());
+        };
+        let (lat, long) = validate::location(lat, long).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        let name = name.map_or_else(|| format!("{},{}", lat, long), str::to_string);
+        places.push(Place { name, lat, long });
+    }
+    if places.is_empty() {
+        return Err("the list contains no places".into());
+    }
+    Ok(places)
+}
+
+/// One place on one date; `place` indexes the list of places.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Row {
+    pub place: usize,
+    pub date: NaiveDate,
+    pub sunrise: Option<DateTime<Utc>>,
+    pub sunset: Option<DateTime<Utc>>,
+    pub solar_noon: DateTime<Utc>,
+    pub day_length: TimeDelta,
+}
+
+/// Every place on every date, by date and then in the order of `places`,
+/// with the sun crossing `altitude` at sunrise and sunset.
+pub fn cross_product(places: &[Place], dates: &[NaiveDate], altitude: f64) -> Vec<Row> {
+    parallel::map(dates, |date| {
+        let today = jdn(*date);
+        let mut meridians: HashMap<u64, DaySolution> = HashMap::new();
+        places
+            .iter()
+            .enumerate()
+            .map(|(i, place)| {
+                let mut solution = *meridians.entry(place.long.to_bits()).or_insert_with(|| DaySolution::from_degrees(place.lat, place.long, today));
+                solution.lat = place.lat;
+                let crossings = solution.crossings_tz(altitude, &Utc);
+                Row {
+                    place: i,
+                    date: *date,
+                    sunrise: crossings.map(|(rise, _)| rise),
+                    sunset: crossings.map(|(_, set)| set),
+                    solar_noon: julian2datetime_tz(solution.j_transit, &Utc),
+                    day_length: crossings.map_or_else(|| day_length_at(place.lat, place.long, today, altitude), |(rise, set)| set - rise),
+                }
+            })
+            .collect::<Vec<_>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
@@ -0,0 +1,138 @@
+use std::str::FromStr;
+
+/// North reference of displayed bearings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BearingReference {
+    #[default]
+    True,
+    Magnetic,
+    /// North of the UTM grid, as on topographic maps.
+    Grid,
+}
+
+impl FromStr for BearingReference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<BearingReference, String> {
+        match s.to_lowercase().as_str() {
+            "true" => Ok(BearingReference::True),
+            "magnetic" => Ok(BearingReference::Magnetic),
+            "grid" => Ok(BearingReference::Grid),
+            _ => Err(format!("unknown bearing reference `{}` (expected true, magnetic or grid)", s)),
+        }
+    }
+}
+
+/// Converts a true azimuth to a magnetic one given the declination (east positive).
+pub fn to_magnetic(azimuth: f64, declination: f64) -> f64 {
+    (azimuth - declination).rem_euclid(360.0)
+}
+
+/// Angle in degrees from true north to grid north of the UTM zone containing
+/// the location, positive where grid north lies east of true north.
+pub fn grid_convergence(lat: f64, long: f64) -> f64 {
+    let central = (((long + 180.0) / 6.0).floor() * 6.0 - 177.0).clamp(-177.0, 177.0);
+    ((long - central).to_radians().tan() * lat.to_radians().sin()).atan().to_degrees()
+}
+
+const POINTS_8: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+const POINTS_16: [&str; 16] = ["N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW"];
+
+/// The mariner's points, `NbE` read as "north by east".
+const POINTS_32: [&str; 32] = [
+    "N", "NbE", "NNE", "NEbN", "NE", "NEbE", "ENE", "EbN", "E", "EbS", "ESE", "SEbE", "SE", "SEbS", "SSE", "SbE", "S", "SbW", "SSW", "SWbS", "SW", "SWbW",
+    "WSW", "WbS", "W", "WbN", "WNW", "NWbW", "NW", "NWbN", "NNW", "NbW",
+];
+
+/// Number of named points around the compass.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompassRose {
+    Points8,
+    #[default]
+    Points16,
+    Points32,
+}
+
+impl CompassRose {
+    /// Name of the point nearest to an azimuth in degrees clockwise from north.
+    pub fn point(&self, azimuth: f64) -> &'static str {
+        let points: &[&'static str] = match self {
+            CompassRose::Points8 => &POINTS_8,
+            CompassRose::Points16 => &POINTS_16,
+            CompassRose::Points32 => &POINTS_32,
+        };
+        let sector = (azimuth.rem_euclid(360.0) / (360.0 / points.len() as f64) + 0.5).floor() as usize % points.len();
+        points[sector]
+    }
+}
+
+impl FromStr for CompassRose {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<CompassRose, String> {
+        match s {
+            "8" => Ok(CompassRose::Points8),
+            "16" => Ok(CompassRose::Points16),
+            "32" => Ok(CompassRose::Points32),
+            _ => Err(format!("unknown compass rose `{}` (expected 8, 16 or 32 points)", s)),
+        }
+    }
+}
+
+/// 16-point compass name of an azimuth in degrees clockwise from north.
+pub fn compass_point(azimuth: f64) -> &'static str {
+    CompassRose::Points16.point(azimuth)
+}
+
+/// How bearings are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BearingFormat {
+    /// `52° ENE`
+    #[default]
+    Both,
+    /// `52°`
+    Degrees,
+    /// `ENE`
+    Compass,
+    /// `924 mil`, in NATO mils of 6400 to the circle
+    Mils,
+}
+
+impl BearingFormat {
+    /// Formats an azimuth in degrees clockwise from north, passing compass
+    /// points through `point` for translation.
+    pub fn format(&self, azimuth: f64, point: impl Fn(&'static str) -> String) -> String {
+        self.format_in(CompassRose::default(), azimuth, point)
+    }
+
+    /// Like [`BearingFormat::format`], naming the points of `rose`.
+    pub fn format_in(&self, rose: CompassRose, azimuth: f64, point: impl Fn(&'static str) -> String) -> String {
+        let azimuth = azimuth.rem_euclid(360.0);
+        match self {
+            BearingFormat::Both => format!("{:.0}° {}", azimuth, point(rose.point(azimuth))),
+            BearingFormat::Degrees => format!("{:.0}°", azimuth),
+            BearingFormat::Compass => point(rose.point(azimuth)),
+            BearingFormat::Mils => format!("{:.0} mil", (azimuth * 6400.0 / 360.0).round() % 6400.0),
+        }
+    }
+}
+
+impl FromStr for BearingFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<BearingFormat, String> {
+        match s.to_lowercase().as_str() {
+            "both" => Ok(BearingFormat::Both),
+            "degrees" | "deg" => Ok(BearingFormat::Degrees),
+            "compass" => Ok(BearingFormat::Compass),
+            "mils" | "mil" => Ok(BearingFormat::Mils),
+            _ => Err(format!("unknown bearing format `{}` (expected both, degrees, compass or mils)", s)),
+        }
+    }
+}
+
+/// Formats an azimuth as e.g. `52° ENE`.
+pub fn format_bearing(azimuth: f64) -> String {
+    BearingFormat::Both.format(azimuth, str::to_string)
+}
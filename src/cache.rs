@@ -0,0 +1,69 @@
+//! Answers kept per rounded location and day, since a server mostly hears
+//! the same few cities asking about today.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+
+/// Steps locations are rounded to, in degrees: about 10 m, which moves
+/// sunrise and sunset by well under a second.
+pub const LOCATION_STEP: f64 = 1e-4;
+
+/// Method, rounded latitude and longitude in steps, and day.
+type Key = (&'static str, i64, i64, NaiveDate);
+
+#[derive(Default)]
+struct Entries {
+    answers: HashMap<Key, (Instant, String)>,
+    /// Keys in the order they were first stored, the oldest evicted first.
+    order: VecDeque<Key>,
+}
+
+/// Answers that expire after `ttl`, at most `capacity` of them.
+pub struct ResponseCache {
+    pub ttl: Duration,
+    pub capacity: usize,
+    entries: Mutex<Entries>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration, capacity: usize) -> ResponseCache {
+        ResponseCache { ttl, capacity, entries: Mutex::default() }
+    }
+
+    /// `lat` and `long` rounded to [`LOCATION_STEP`].
+    pub fn round(lat: f64, long: f64) -> (f64, f64) {
+        let round = |x: f64| (x / LOCATION_STEP).round() * LOCATION_STEP;
+        (round(lat), round(long))
+    }
+
+    /// The stored answer of `method` for the location and day, or the one
+    /// `answer` computes for the rounded location, which is then stored.
+    pub fn get_or_insert(&self, method: &'static str, lat: f64, long: f64, date: NaiveDate, answer: impl FnOnce(f64, f64) -> String) -> String {
+        let steps = |x: f64| (x / LOCATION_STEP).round() as i64;
+        let key = (method, steps(lat), steps(long), date);
+        if let Some((stored, answer)) = self.entries.lock().unwrap().answers.get(&key) {
+            if stored.elapsed() < self.ttl {
+                info!("Cache hit for {} at {}, {} on {}", method, lat, long, date);
+                return answer.clone();
+            }
+        }
+        let (lat, long) = ResponseCache::round(lat, long);
+        let answer = answer(lat, long);
+        if self.capacity > 0 {
+            let mut entries = self.entries.lock().unwrap();
+            let Entries { answers, order } = &mut *entries;
+            if answers.insert(key, (Instant::now(), answer.clone())).is_none() {
+                order.push_back(key);
+                while order.len() > self.capacity {
+                    if let Some(oldest) = order.pop_front() {
+                        answers.remove(&oldest);
+                    }
+                }
+            }
+        }
+        answer
+    }
+}
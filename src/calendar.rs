@@ -0,0 +1,186 @@
+//! Dates of the Gregorian calendar in the Julian, Hebrew and tabular Islamic
+//! calendars, after the arithmetic of Reingold and Dershowitz, Calendrical
+//! Calculations. Days are counted as fixed day numbers, 1 on 0001-01-01.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Calendar {
+    Julian,
+    Hebrew,
+    /// The tabular Islamic calendar with the civil epoch, a day after the
+    /// astronomical one; observed dates follow sightings of the crescent
+    /// and may differ by a day or two.
+    Islamic,
+}
+
+/// A day, month and year of a [`Calendar`], months counted from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalendarDate {
+    pub calendar: Calendar,
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+const JULIAN_MONTHS: [&str; 12] = ["January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November", "December"];
+/// From Nisan, with Adar II last; the year begins with Tishri.
+const HEBREW_MONTHS: [&str; 13] = ["Nisan", "Iyyar", "Sivan", "Tammuz", "Av", "Elul", "Tishri", "Marheshvan", "Kislev", "Tevet", "Shevat", "Adar", "Adar II"];
+const ISLAMIC_MONTHS: [&str; 12] = [
+    "Muharram",
+    "Safar",
+    "Rabi' al-awwal",
+    "Rabi' al-thani",
+    "Jumada al-ula",
+    "Jumada al-akhira",
+    "Rajab",
+    "Sha'ban",
+    "Ramadan",
+    "Shawwal",
+    "Dhu al-Qa'da",
+    "Dhu al-Hijja",
+];
+
+/// Fixed day of 1 Tishri AM 1, 7 October 3761 BC in the Julian calendar.
+const HEBREW_EPOCH: i64 = -1373427;
+/// Fixed day of 1 Muharram AH 1, 16 July 622 in the Julian calendar.
+const ISLAMIC_EPOCH: i64 = 227015;
+
+impl Calendar {
+    pub const ALL: [Calendar; 3] = [Calendar::Julian, Calendar::Hebrew, Calendar::Islamic];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Calendar::Julian => "julian",
+            Calendar::Hebrew => "hebrew",
+            Calendar::Islamic => "islamic",
+        }
+    }
+
+    /// The Gregorian `date` in this calendar.
+    pub fn date(&self, date: NaiveDate) -> CalendarDate {
+        let fixed = date.num_days_from_ce() as i64;
+        let (year, month, day) = match self {
+            Calendar::Julian => julian_from_fixed(fixed),
+            Calendar::Hebrew => hebrew_from_fixed(fixed),
+            Calendar::Islamic => islamic_from_fixed(fixed),
+        };
+        CalendarDate { calendar: *self, year, month, day }
+    }
+}
+
+impl FromStr for Calendar {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Calendar, String> {
+        Calendar::ALL.into_iter().find(|c| c.name() == s.to_lowercase()).ok_or_else(|| {
+            let names: Vec<&str> = Calendar::ALL.iter().map(Calendar::name).collect();
+            format!("unknown calendar `{}` (expected {})", s, names.join(", "))
+        })
+    }
+}
+
+impl CalendarDate {
+    pub fn month_name(&self) -> &'static str {
+        let i = self.month as usize - 1;
+        match self.calendar {
+            Calendar::Julian => JULIAN_MONTHS[i],
+            // Adar is Adar I in leap years
+            Calendar::Hebrew if self.month == 12 && hebrew_leap_year(self.year) => "Adar I",
+            Calendar::Hebrew => HEBREW_MONTHS[i],
+            Calendar::Islamic => ISLAMIC_MONTHS[i],
+        }
+    }
+}
+
+/// The date as e.g. `8 Tishri 5787` or `10 Ramadan 1447`.
+impl fmt::Display for CalendarDate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.day, self.month_name(), self.year)
+    }
+}
+
+fn julian_from_fixed(fixed: i64) -> (i64, u32, u32) {
+    // From the Julian day number by Richards' algorithm
+    let c = fixed + 1721425 + 32082;
+    let d = (4 * c + 3).div_euclid(1461);
+    let e = c - (1461 * d).div_euclid(4);
+    let m = (5 * e + 2).div_euclid(153);
+    let day = e - (153 * m + 2).div_euclid(5) + 1;
+    let month = m + 3 - 12 * (m / 10);
+    (d - 4800 + m / 10, month as u32, day as u32)
+}
+
+fn hebrew_leap_year(year: i64) -> bool {
+    (7 * year + 1).rem_euclid(19) < 7
+}
+
+/// Days from the epoch to the molad of Tishri of `year`, delayed a day
+/// where the molad would start the year on a Sunday, Wednesday or Friday.
+fn hebrew_elapsed_days(year: i64) -> i64 {
+    let months = (235 * year - 234).div_euclid(19);
+    let parts = 12084 + 13753 * months;
+    let day = 29 * months + parts.div_euclid(25920);
+    if (3 * (day + 1)).rem_euclid(7) < 3 {
+        day + 1
+    } else {
+        day
+    }
+}
+
+fn hebrew_new_year(year: i64) -> i64 {
+    let (before, this, after) = (hebrew_elapsed_days(year - 1), hebrew_elapsed_days(year), hebrew_elapsed_days(year + 1));
+    // Keep years to the allowed lengths
+    let delay = if after - this == 356 {
+        2
+    } else if this - before == 382 {
+        1
+    } else {
+        0
+    };
+    HEBREW_EPOCH + this + delay
+}
+
+fn hebrew_month_length(year: i64, month: u32) -> i64 {
+    let days = hebrew_new_year(year + 1) - hebrew_new_year(year);
+    let short = match month {
+        2 | 4 | 6 | 10 | 13 => true,
+        12 => !hebrew_leap_year(year),
+        8 => days % 10 != 5,
+        9 => days % 10 == 3,
+        _ => false,
+    };
+    if short {
+        29
+    } else {
+        30
+    }
+}
+
+fn fixed_from_hebrew(year: i64, month: u32, day: u32) -> i64 {
+    let last = if hebrew_leap_year(year) { 13 } else { 12 };
+    let months: Vec<u32> = if month < 7 { (7..=last).chain(1..month).collect() } else { (7..month).collect() };
+    hebrew_new_year(year) + months.iter().map(|m| hebrew_month_length(year, *m)).sum::<i64>() + day as i64 - 1
+}
+
+fn hebrew_from_fixed(fixed: i64) -> (i64, u32, u32) {
+    let approx = ((fixed - HEBREW_EPOCH) as f64 * 98496.0 / 35975351.0).floor() as i64 + 1;
+    let year = (approx - 1..=approx + 1).rev().find(|y| hebrew_new_year(*y) <= fixed).unwrap_or(approx - 1);
+    let first = if fixed < fixed_from_hebrew(year, 1, 1) { 7 } else { 1 };
+    let month = (first..=13).find(|m| fixed < fixed_from_hebrew(year, *m, 1) + hebrew_month_length(year, *m)).unwrap_or(first);
+    (year, month, (fixed - fixed_from_hebrew(year, month, 1) + 1) as u32)
+}
+
+fn fixed_from_islamic(year: i64, month: u32, day: u32) -> i64 {
+    let month = month as i64;
+    ISLAMIC_EPOCH - 1 + (year - 1) * 354 + (3 + 11 * year).div_euclid(30) + 29 * (month - 1) + (6 * month - 1).div_euclid(11) + day as i64
+}
+
+fn islamic_from_fixed(fixed: i64) -> (i64, u32, u32) {
+    let year = (30 * (fixed - ISLAMIC_EPOCH) + 10646).div_euclid(10631);
+    let month = ((11 * (fixed - fixed_from_islamic(year, 1, 1)) + 330).div_euclid(325)) as u32;
+    (year, month, (fixed - fixed_from_islamic(year, month, 1) + 1) as u32)
+}
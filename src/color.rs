@@ -0,0 +1,147 @@
+//! ANSI colours for the human-readable output, tinting table rows and the
+//! `now` line by the light they describe. Colours are set up once at startup
+//! and stay off unless stdout is a terminal and `NO_COLOR` is unset, or
+//! `--color always` asks for them.
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::phase::Phase;
+use crate::photo::Light;
+
+/// The kind of light a row or line is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tone {
+    Night,
+    Twilight,
+    BlueHour,
+    GoldenHour,
+    Day,
+}
+
+impl From<Light> for Tone {
+    fn from(light: Light) -> Tone {
+        match light {
+            Light::Night => Tone::Night,
+            Light::BlueHour => Tone::BlueHour,
+            Light::GoldenHour => Tone::GoldenHour,
+            Light::Harsh => Tone::Day,
+        }
+    }
+}
+
+impl From<Phase> for Tone {
+    fn from(phase: Phase) -> Tone {
+        match phase {
+            Phase::Day => Tone::Day,
+            Phase::Twilight { .. } => Tone::Twilight,
+            Phase::Night => Tone::Night,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ColorChoice, String> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("unknown color choice `{}` (expected auto, always or never)", s)),
+        }
+    }
+}
+
+/// SGR parameters for each tone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub name: &'static str,
+    night: &'static str,
+    twilight: &'static str,
+    blue_hour: &'static str,
+    golden_hour: &'static str,
+    day: &'static str,
+    header: &'static str,
+}
+
+/// Built-in themes: 256 colours, the 16 basic colours for older terminals,
+/// and bold and dim only.
+pub const THEMES: [Theme; 3] = [
+    Theme { name: "default", night: "38;5;69", twilight: "38;5;141", blue_hour: "38;5;39", golden_hour: "38;5;214", day: "38;5;229", header: "1" },
+    Theme { name: "basic", night: "34", twilight: "35", blue_hour: "36", golden_hour: "33", day: "", header: "1" },
+    Theme { name: "mono", night: "2", twilight: "2", blue_hour: "", golden_hour: "1", day: "", header: "1;4" },
+];
+
+impl Theme {
+    fn code(&self, tone: Tone) -> &'static str {
+        match tone {
+            Tone::Night => self.night,
+            Tone::Twilight => self.twilight,
+            Tone::BlueHour => self.blue_hour,
+            Tone::GoldenHour => self.golden_hour,
+            Tone::Day => self.day,
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Theme, String> {
+        THEMES.into_iter().find(|t| t.name.eq_ignore_ascii_case(s)).ok_or_else(|| {
+            let names: Vec<&str> = THEMES.iter().map(|t| t.name).collect();
+            format!("unknown theme `{}` (expected {})", s, names.join(", "))
+        })
+    }
+}
+
+static THEME: OnceLock<Option<Theme>> = OnceLock::new();
+
+/// Turns colours on with `theme` as `choice` and the environment decide;
+/// `to_stdout` is false when the output goes to a file.
+pub fn init(choice: ColorChoice, theme: Theme, to_stdout: bool) {
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            to_stdout
+                && io::stdout().is_terminal()
+                && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+                && env::var("TERM").as_deref() != Ok("dumb")
+        }
+    };
+    let _ = THEME.set(enabled.then_some(theme));
+}
+
+fn theme() -> Option<Theme> {
+    THEME.get().copied().flatten()
+}
+
+fn sgr(code: &str, s: &str) -> String {
+    if code.is_empty() {
+        s.to_string()
+    } else {
+        format!("\x1b[{}m{}\x1b[0m", code, s)
+    }
+}
+
+/// `s` in the colour of `tone`, unchanged when colours are off.
+pub fn paint(tone: Tone, s: &str) -> String {
+    theme().map_or_else(|| s.to_string(), |t| sgr(t.code(tone), s))
+}
+
+/// `s` styled as a table header.
+pub fn header(s: &str) -> String {
+    theme().map_or_else(|| s.to_string(), |t| sgr(t.header, s))
+}
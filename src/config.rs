@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use chrono::TimeDelta;
+
+use crate::duration::parse_signed;
+use crate::platform::Files;
+use crate::solar::SolarEvent;
+use crate::window::Window;
+
+/// A named location from the configuration file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LocationProfile {
+    pub lat: f64,
+    pub long: f64,
+    /// Observer height above the surrounding terrain in metres.
+    pub elevation: Option<f64>,
+    /// `UTC` or a zone name such as `Europe/Berlin`.
+    pub timezone: Option<String>,
+}
+
+/// A chat service to notify from the daemon, a `[notify.NAME]` table.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NotifierProfile {
+    /// String settings of the service, such as `bot_token` and `chat_id`.
+    pub settings: BTreeMap<String, String>,
+    /// Events to announce, sunrise and sunset unless `events` lists some.
+    pub events: Vec<SolarEvent>,
+    /// How long before the events, 15 minutes unless `lead` lists times.
+    pub leads: Vec<TimeDelta>,
+}
+
+/// Services a `[notify.NAME]` table can configure.
+pub const NOTIFIERS: [&str; 2] = ["matrix", "telegram"];
+
+/// Contents of `config.toml`:
+///
+/// ```toml
+/// default = "home"
+///
+/// [locations.home]
+/// lat = 48.3553
+/// long = 9.9061
+///
+/// [locations.cottage]
+/// lat = 47.42
+/// long = 10.98
+/// elevation = 900
+/// timezone = "Europe/Vienna"
+///
+/// [hours]
+/// hunting = "sunrise-30m to sunset+30m"
+///
+/// [notify.telegram]
+/// bot_token = "123456:ABC-DEF"
+/// chat_id = "-1001234567"
+/// events = "sunset"
+/// lead = "10m, 0m"
+///
+/// [notify.matrix]
+/// homeserver = "https://matrix.org"
+/// room = "!abcdef:matrix.org"
+/// access_token = "syt_..."
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub default: Option<String>,
+    pub locations: BTreeMap<String, LocationProfile>,
+    /// Named legal hours such as hunting or fishing times.
+    pub hours: BTreeMap<String, Window>,
+    /// Chat services the daemon posts to.
+    pub notify: BTreeMap<String, NotifierProfile>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum TomlValue {
+    String(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl Config {
+    /// Reads the file at `path`; a missing file is an empty configuration.
+    pub fn load(files: &dyn Files, path: &Path) -> Result<Config, String> {
+        match files.read_to_string(path) {
+            Ok(s) => Config::parse(&s).map_err(|e| format!("{}: {}", path.display(), e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    /// Parses the subset of TOML the configuration uses: tables, and keys
+    /// with string, number or boolean values.
+    pub fn parse(s: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+        let mut section: Vec<String> = Vec::new();
+        let mut partial: BTreeMap<String, BTreeMap<String, TomlValue>> = BTreeMap::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |msg: &str| format!("line {}: {}", i + 1, msg);
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header.strip_suffix(']').ok_or_else(|| err("unterminated table header"))?;
+                section = header.split('.').map(|p| p.trim().trim_matches('"').to_string()).collect();
+                match section.as_slice() {
+                    [locations, name] if locations == "locations" => {
+                        partial.entry(name.clone()).or_default();
+                    }
+                    [hours] if hours == "hours" => {}
+                    [notify, name] if notify == "notify" => {
+                        if !NOTIFIERS.contains(&name.as_str()) {
+                            return Err(err(&format!("unknown notifier `{}` (expected {})", name, NOTIFIERS.join(" or "))));
+                        }
+                        config.notify.entry(name.clone()).or_default();
+                    }
+                    _ => return Err(err(&format!("unknown table `{}`", header))),
+                }
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| err("expected `key = value`"))?;
+            let key = key.trim().trim_matches('"').to_string();
+            let value = parse_value(value.trim()).map_err(|e| err(&e))?;
+            match section.as_slice() {
+                [] if key == "default" => match value {
+                    TomlValue::String(s) => config.default = Some(s),
+                    _ => return Err(err("default must be a string")),
+                },
+                [] => return Err(err(&format!("unknown key `{}`", key))),
+                [_] => match value {
+                    TomlValue::String(s) => {
+                        config.hours.insert(key, s.parse().map_err(|e: String| err(&e))?);
+                    }
+                    _ => return Err(err(&format!("hours.{} must be a string like \"sunrise-30m to sunset+30m\"", key))),
+                },
+                [notify, name] if notify == "notify" => {
+                    let profile = config.notify.get_mut(name).unwrap();
+                    let TomlValue::String(value) = value else {
+                        return Err(err(&format!("notify.{}.{} must be a string", name, key)));
+                    };
+                    let list = || value.split(',').map(str::trim).filter(|v| !v.is_empty());
+                    match key.as_str() {
+                        "events" => profile.events = list().map(str::parse).collect::<Result<_, String>>().map_err(|e| err(&e))?,
+                        "lead" => profile.leads = list().map(parse_signed).collect::<Result<_, String>>().map_err(|e| err(&e))?,
+                        _ => {
+                            profile.settings.insert(key, value);
+                        }
+                    }
+                }
+                [_, name] => {
+                    partial.get_mut(name).unwrap().insert(key, value);
+                }
+                _ => unreachable!(),
+            }
+        }
+        for (name, fields) in partial {
+            let number = |key: &str| match fields.get(key) {
+                None => Ok(None),
+                Some(TomlValue::Number(n)) => Ok(Some(*n)),
+                Some(_) => Err(format!("locations.{}.{} must be a number", name, key)),
+            };
+            let (Some(lat), Some(long)) = (number("lat")?, number("long")?.or(number("lon")?)) else {
+                return Err(format!("locations.{} needs lat and long", name));
+            };
+            let timezone = match fields.get("timezone") {
+                None => None,
+                Some(TomlValue::String(tz)) => Some(tz.clone()),
+                Some(_) => return Err(format!("locations.{}.timezone must be a string", name)),
+            };
+            if let Some(key) = fields.keys().find(|k| !["lat", "long", "lon", "elevation", "timezone"].contains(&k.as_str())) {
+                return Err(format!("unknown key `{}` in locations.{}", key, name));
+            }
+            let profile = LocationProfile { lat, long, elevation: number("elevation")?, timezone };
+            config.locations.insert(name, profile);
+        }
+        for profile in config.notify.values_mut() {
+            if profile.events.is_empty() {
+                profile.events = vec![SolarEvent::Sunrise, SolarEvent::Sunset];
+            }
+            if profile.leads.is_empty() {
+                profile.leads = vec![TimeDelta::minutes(15)];
+            }
+        }
+        if let Some(default) = &config.default {
+            if !config.locations.contains_key(default) {
+                return Err(format!("default location `{}` is not defined", default));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn location(&self, name: &str) -> Result<&LocationProfile, String> {
+        self.locations.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.locations.keys().map(String::as_str).collect();
+            format!("unknown location `{}` (configured: {})", name, if known.is_empty() { "none".to_string() } else { known.join(", ") })
+        })
+    }
+
+    pub fn window(&self, name: &str) -> Result<&Window, String> {
+        self.hours.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.hours.keys().map(String::as_str).collect();
+            format!("unknown hours `{}` (configured: {})", name, if known.is_empty() { "none".to_string() } else { known.join(", ") })
+        })
+    }
+
+    pub fn default_location(&self) -> Option<&LocationProfile> {
+        self.locations.get(self.default.as_ref()?)
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_value(s: &str) -> Result<TomlValue, String> {
+    if let Some(inner) = s.strip_prefix('"') {
+        let inner = inner.strip_suffix('"').ok_or("unterminated string")?;
+        return Ok(TomlValue::String(inner.replace("\\\"", "\"").replace("\\\\", "\\")));
+    }
+    match s {
+        "true" => Ok(TomlValue::Bool(true)),
+        "false" => Ok(TomlValue::Bool(false)),
+        _ => s.replace('_', "").parse().map(TomlValue::Number).map_err(|_| format!("invalid value `{}`", s)),
+    }
+}
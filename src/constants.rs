@@ -0,0 +1,124 @@
+//! The numbers of the sunrise equation, with their sources, and a [`Model`]
+//! holding those that can be tweaked for one [`DaySolution`].
+//!
+//! The Wikipedia form of the equation adds 0.0008 days to the Julian day
+//! for leap seconds and ΔT; so2lar counts whole days from J2000 instead, see
+//! `normalized_date`, and has no such term.
+//!
+//! [`DaySolution`]: crate::solar::DaySolution
+
+/// Julian date of the epoch J2000.0, 2000-01-01 12:00 TT.
+pub const J2000: f64 = 2451545.0;
+
+/// Days in a Julian century, the unit of the polynomials in time.
+pub const JULIAN_CENTURY: f64 = 36525.0;
+
+/// Mean anomaly of the sun at J2000 in degrees and its rate in degrees per day.
+pub const MEAN_ANOMALY: [f64; 2] = [357.5291, 0.98560028];
+
+/// Coefficients of the equation of the center in degrees: of sin M with its
+/// linear and quadratic terms in centuries, of sin 2M with its linear term,
+/// and of sin 3M.
+pub const EQUATION_OF_THE_CENTER: [f64; 6] = [1.914602, -0.004817, -0.000014, 0.019993, -0.000101, 0.000289];
+
+/// Ecliptic longitude of the perihelion in degrees, the argument of the
+/// perihelion of the Earth's orbit.
+pub const PERIHELION: f64 = 102.9372;
+
+/// Mean obliquity of the ecliptic in degrees at J2000 and its linear,
+/// quadratic and cubic change per century (Laskar's cubic as given by Meeus).
+pub const OBLIQUITY: [f64; 4] = [23.439291, -0.0130042, -0.000000164, 0.000000504];
+
+/// Equation of time terms of the solar transit in days, of sin M and of sin 2λ.
+pub const TRANSIT: [f64; 2] = [0.0053, -0.0069];
+
+/// Greenwich mean sidereal time at J2000 in degrees and its rate in degrees per day.
+pub const SIDEREAL_TIME: [f64; 2] = [280.46061837, 360.98564736629];
+
+/// Refraction at the horizon in degrees, the classic 34'.
+pub const HORIZON_REFRACTION: f64 = 34.0 / 60.0;
+
+/// Apparent semi-diameter of the sun in degrees.
+pub const SEMI_DIAMETER: f64 = 0.2666;
+
+/// Altitude of the sun's centre at sunrise and sunset on a sea-level
+/// horizon: the refraction and the semi-diameter, rounded as usual.
+pub const STANDARD_ALTITUDE: f64 = -0.833;
+
+/// Error in degrees of the sun's altitude from the sunrise equation against
+/// a full position with nutation, aberration and ΔT, with some margin.
+pub const MODEL_UNCERTAINTY: f64 = 0.05;
+
+/// Spread in degrees of the refraction at the horizon with temperature and
+/// pressure, which dominates the error of sunrise and sunset.
+pub const REFRACTION_UNCERTAINTY: f64 = 0.25;
+
+/// Mean radius of the Earth in metres.
+pub const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Scale height of the atmosphere's pressure in metres, over which the
+/// refraction at the horizon falls by a factor of e.
+pub const SCALE_HEIGHT: f64 = 8434.0;
+
+/// Factor carrying the terrestrial refraction of the 2.076'·√h dip over to
+/// the exact geometric dip of the horizon.
+pub const DIP_FACTOR: f64 = 1.0779;
+
+/// The parameters of the sunrise equation in [`crate::solar::DaySolution::with_model`],
+/// the constants above by default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Model {
+    pub mean_anomaly: [f64; 2],
+    pub equation_of_the_center: [f64; 6],
+    pub perihelion: f64,
+    pub obliquity: [f64; 4],
+    pub transit: [f64; 2],
+}
+
+impl Model {
+    pub const DEFAULT: Model = Model {
+        mean_anomaly: MEAN_ANOMALY,
+        equation_of_the_center: EQUATION_OF_THE_CENTER,
+        perihelion: PERIHELION,
+        obliquity: OBLIQUITY,
+        transit: TRANSIT,
+    };
+
+    /// Mean anomaly of the sun in degrees, `j_star` days after J2000.
+    pub const fn mean_anomaly(&self, j_star: f64) -> f64 {
+        (self.mean_anomaly[0] + self.mean_anomaly[1] * j_star) % 360.0
+    }
+
+    /// Equation of the center for the mean anomaly `m` at `d` days after J2000.
+    pub fn equation_of_the_center(&self, m: f64, d: f64) -> f64 {
+        let [a0, a1, a2, b0, b1, c0] = self.equation_of_the_center;
+        let t = d / JULIAN_CENTURY;
+        let m = m.to_radians();
+        (a0 + a1 * t + a2 * t * t) * m.sin() + (b0 + b1 * t) * (2.0 * m).sin() + c0 * (3.0 * m).sin()
+    }
+
+    /// Ecliptic longitude of the sun in degrees from the mean anomaly `m` and
+    /// the equation of the center `c`.
+    pub const fn ecliptic_longitude(&self, m: f64, c: f64) -> f64 {
+        (m + c + 180.0 + self.perihelion) % 360.0
+    }
+
+    /// Mean obliquity of the ecliptic in degrees at the TT Julian date `j`.
+    pub const fn obliquity(&self, j: f64) -> f64 {
+        let [e0, e1, e2, e3] = self.obliquity;
+        let t = (j - J2000) / JULIAN_CENTURY;
+        e0 + e1 * t + e2 * t * t + e3 * t * t * t
+    }
+
+    /// Julian date of the solar transit of the mean solar time `j_star` with
+    /// the mean anomaly `m` and ecliptic longitude `lambda`.
+    pub fn transit(&self, j_star: f64, m: f64, lambda: f64) -> f64 {
+        J2000 + j_star + self.transit[0] * m.to_radians().sin() + self.transit[1] * (2.0 * lambda).to_radians().sin()
+    }
+}
+
+impl Default for Model {
+    fn default() -> Model {
+        Model::DEFAULT
+    }
+}
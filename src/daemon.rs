@@ -0,0 +1,190 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, TimeDelta};
+
+use crate::config::Config;
+use crate::notify::{Event, Notifier, Occurrence, Subscription};
+use crate::platform::{Clock, HostFiles};
+use crate::signal;
+use crate::solar::{jdn, Direction, SolarEvent, SolutionCache};
+
+pub struct Daemon {
+    pub lat: f64,
+    pub long: f64,
+    pub subscriptions: Vec<Subscription>,
+    pub clock: Box<dyn Clock>,
+    /// Where to reread the coordinates when they may have changed.
+    pub watch: Option<LocationWatch>,
+}
+
+/// A location of the configuration file the daemon follows: it is reread on
+/// SIGHUP and whenever the file changes, for machines that travel.
+pub struct LocationWatch {
+    pub path: PathBuf,
+    /// The named location, the default one without a name.
+    pub name: Option<String>,
+}
+
+/// How often a watched configuration file is checked for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+impl LocationWatch {
+    fn modified(&self) -> Option<SystemTime> {
+        fs::metadata(&self.path).ok()?.modified().ok()
+    }
+
+    fn load(&self) -> Result<(f64, f64), String> {
+        let config = Config::load(&HostFiles, &self.path)?;
+        let profile = match &self.name {
+            Some(name) => config.location(name)?,
+            None => config.default_location().ok_or("no default location is configured")?,
+        };
+        Ok((profile.lat, profile.long))
+    }
+}
+
+/// How late an event may fire after a suspend or a clock change before it
+/// counts as skipped.
+pub const MAX_LATENESS: TimeDelta = TimeDelta::minutes(5);
+
+/// Sleeps in short steps so suspend and clock changes delay an event by at most a minute.
+pub fn sleep_until(clock: &dyn Clock, t: DateTime<Local>) {
+    while let Ok(remaining) = (t.to_utc() - clock.now()).to_std() {
+        thread::sleep(remaining.min(Duration::from_secs(60)));
+    }
+}
+
+struct Trigger {
+    at: DateTime<Local>,
+    subscription: usize,
+    event: Event,
+    time: DateTime<Local>,
+    lead: TimeDelta,
+}
+
+impl Daemon {
+    /// Notifies `notifier` whenever the sun passes `angle` degrees in
+    /// `direction`, beyond the named events of the subscriptions.
+    pub fn on_elevation_crossing(&mut self, angle: f64, direction: Direction, notifier: Box<dyn Notifier>) {
+        let subscription = Subscription { notifier, events: Vec::new(), crossings: Vec::new(), leads: vec![TimeDelta::zero()] };
+        self.subscriptions.push(subscription.on_elevation_crossing(angle, direction));
+    }
+
+    fn now(&self) -> DateTime<Local> {
+        self.clock.now().with_timezone(&Local)
+    }
+
+    /// All triggers sharing the earliest firing time after `after`.
+    fn next(&self, cache: &mut SolutionCache, after: DateTime<Local>) -> Result<Vec<Trigger>, Box<dyn Error>> {
+        let today = jdn(self.clock.now().date_naive());
+        cache.forget_before(today - 1.0);
+        let mut triggers = Vec::new();
+        for (i, sub) in self.subscriptions.iter().enumerate() {
+            for lead in &sub.leads {
+                let (lat, long, now) = (self.lat, self.long, after + *lead);
+                let solar = sub.events.iter().filter_map(|e| Some((Event::Solar(*e), e.next_after_in(cache, lat, long, today - 1.0, now)?)));
+                let mut times: Vec<(Event, DateTime<Local>)> = solar.collect();
+                times.extend(sub.crossings.iter().filter_map(|c| Some((Event::Crossing(*c), c.next_after_in(cache, lat, long, today - 1.0, now)?))));
+                for (event, time) in times {
+                    triggers.push(Trigger { at: time - *lead, subscription: i, event, time, lead: *lead });
+                }
+            }
+        }
+        let Some(first) = triggers.iter().map(|t| t.at).min() else {
+            return Ok(triggers);
+        };
+        triggers.retain(|t| t.at == first);
+        Ok(triggers)
+    }
+
+    /// Logs the subscribed events the sun skips on the day `today`, in
+    /// polar day or night.
+    fn report_missing(&self, cache: &mut SolutionCache, today: f64) {
+        let solution = cache.get_degrees(self.lat, self.long, today);
+        let (rise, set) = solution.sunrise_sunset();
+        for event in [SolarEvent::Sunrise, SolarEvent::Sunset] {
+            let subscribed = self.subscriptions.iter().any(|s| s.events.contains(&event));
+            if subscribed && (if event == SolarEvent::Sunrise { rise } else { set }).is_nan() {
+                info!("No {} today: the sun does not cross the horizon", event.name());
+            }
+        }
+    }
+
+    /// Sleeps until `t` like [`sleep_until`], waking early when the watched
+    /// location moves. Returns whether it did, with the new coordinates set.
+    fn sleep_until_moved(&mut self, t: DateTime<Local>, modified: &mut Option<SystemTime>) -> bool {
+        let Some(watch) = &self.watch else {
+            sleep_until(self.clock.as_ref(), t);
+            return false;
+        };
+        while let Ok(remaining) = (t.to_utc() - self.clock.now()).to_std() {
+            thread::sleep(remaining.min(WATCH_INTERVAL));
+            let changed = watch.modified();
+            if !signal::hung_up() && changed == *modified {
+                continue;
+            }
+            *modified = changed;
+            match watch.load() {
+                Ok((lat, long)) if (lat, long) != (self.lat, self.long) => {
+                    info!("Moved from {:.4}, {:.4} to {:.4}, {:.4}", self.lat, self.long, lat, long);
+                    (self.lat, self.long) = (lat, long);
+                    return true;
+                }
+                Ok(_) => info!("Reread {}: the location is unchanged", watch.path.display()),
+                Err(e) => warn!("Cannot reread {}, staying at {:.4}, {:.4}: {}", watch.path.display(), self.lat, self.long, e),
+            }
+        }
+        false
+    }
+
+    /// Fires each trigger once: `last` only moves forward, so a clock set
+    /// back does not repeat a notification, and one that comes more than
+    /// [`MAX_LATENESS`] late after a suspend or a jump ahead is logged as
+    /// skipped. DST changes need nothing, as triggers are instants. When
+    /// the watched location moves the upcoming events are found anew.
+    pub fn run(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut cache = SolutionCache::default();
+        let mut last = self.now();
+        let mut reported = f64::NAN;
+        let mut modified = self.watch.as_ref().and_then(LocationWatch::modified);
+        if self.watch.is_some() {
+            signal::catch_hangup();
+        }
+        loop {
+            let today = jdn(self.clock.now().date_naive());
+            if today != reported {
+                self.report_missing(&mut cache, today);
+                reported = today;
+            }
+            let triggers = self.next(&mut cache, last.max(self.now()))?;
+            let Some(at) = triggers.first().map(|t| t.at) else {
+                warn!("No events within the next year, checking again tomorrow");
+                thread::sleep(Duration::from_secs(86400));
+                continue;
+            };
+            info!("Next notification at {}", at);
+            if self.sleep_until_moved(at, &mut modified) {
+                cache = SolutionCache::default();
+                reported = f64::NAN;
+                continue;
+            }
+            last = at;
+            if self.now() - at > MAX_LATENESS {
+                for trigger in &triggers {
+                    warn!("Skipped the notification for {} at {}: the machine slept or the clock jumped past it", trigger.event.name(), trigger.time);
+                }
+                continue;
+            }
+            for trigger in triggers {
+                let occurrence = Occurrence { event: trigger.event, time: trigger.time, lead: trigger.lead, lat: self.lat, long: self.long };
+                if let Err(e) = self.subscriptions[trigger.subscription].notifier.notify(&occurrence) {
+                    error!("Notification for {} failed: {}", occurrence.event.name(), e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! A 24-hour dial of the day's light for the terminal: midnight at the
+//! bottom, noon at the top and the hours running clockwise, with the ring
+//! shaded by night, twilight and day and a marker at the current time.
+
+use chrono::{DateTime, FixedOffset, TimeDelta, Utc};
+
+use crate::color::{self, Tone};
+use crate::phase::Phase;
+use crate::solar::datetime2julian;
+
+/// Rows from the centre to the ring.
+const RADIUS: f64 = 8.0;
+
+pub struct Dial {
+    pub lat: f64,
+    pub long: f64,
+    /// Start of the day on the clock of `now`.
+    pub midnight: DateTime<Utc>,
+    pub now: DateTime<FixedOffset>,
+}
+
+impl Dial {
+    /// Hour of the clock at `(x, y)` from the centre, in rows; columns are
+    /// half as high as rows, so `x` counts half columns.
+    fn hour(x: f64, y: f64) -> f64 {
+        ((-x).atan2(y).to_degrees().rem_euclid(360.0)) / 15.0
+    }
+
+    fn glyph(phase: Phase) -> char {
+        match phase {
+            Phase::Day => '█',
+            Phase::Twilight { .. } => '▓',
+            Phase::Night => '░',
+        }
+    }
+
+    /// The dial with the time and phase in its middle.
+    pub fn render(&self) -> String {
+        let size = 2 * RADIUS as usize + 1;
+        let j = |hour: f64| datetime2julian(&self.midnight) + hour / 24.0;
+        let now_hour = (self.now.to_utc() - self.midnight).num_seconds() as f64 / 3600.0;
+        let phase = Phase::at(self.lat, self.long, datetime2julian(&self.now));
+        let mut grid = vec![vec![" ".to_string(); 2 * size]; size];
+        let mut marker = None;
+        for (row, line) in grid.iter_mut().enumerate() {
+            for (col, cell) in line.iter_mut().enumerate() {
+                let (x, y) = (col as f64 / 2.0 - RADIUS, row as f64 - RADIUS);
+                if ((x * x + y * y).sqrt() - RADIUS).abs() >= 0.5 {
+                    continue;
+                }
+                let hour = Dial::hour(x, y);
+                let cell_phase = Phase::at(self.lat, self.long, j(hour));
+                *cell = color::paint(Tone::from(cell_phase), &Dial::glyph(cell_phase).to_string());
+                let distance = (hour - now_hour).abs().min(24.0 - (hour - now_hour).abs());
+                if marker.is_none_or(|(_, _, d)| distance < d) {
+                    marker = Some((row, col, distance));
+                }
+            }
+        }
+        if let Some((row, col, _)) = marker {
+            grid[row][col] = color::header("●");
+        }
+        let middle = RADIUS as usize;
+        let mut put = |row: usize, col: usize, s: &str| {
+            for (i, c) in s.chars().enumerate() {
+                grid[row][col + i] = c.to_string();
+            }
+        };
+        let centred = |s: &str| size - s.chars().count() / 2;
+        put(2, centred("12"), "12");
+        put(middle, 4, "6");
+        put(middle, 2 * size - 6, "18");
+        let time = self.now.format("%H:%M").to_string();
+        put(middle - 1, centred(&time), &time);
+        let name = phase.to_string();
+        put(middle + 1, centred(&name), &name);
+        put(size - 3, centred("0"), "0");
+        grid.iter().map(|line| line.concat().trim_end().to_string()).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Time until the next whole minute of `now`, when the dial changes.
+    pub fn refresh_in(&self) -> TimeDelta {
+        TimeDelta::seconds(60 - self.now.timestamp().rem_euclid(60)).max(TimeDelta::seconds(1))
+    }
+}
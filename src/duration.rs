@@ -0,0 +1,133 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::TimeDelta;
+
+use crate::format::{FormatOptions, Precision};
+
+/// A length of time such as a day length, displayed as e.g. `10h 54m 16s`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span(pub TimeDelta);
+
+impl Span {
+    /// The magnitude with the smallest unit of `options.precision`, as
+    /// `10h 54m`, `10h 54m 16s` or `10h 54m 16.250s`.
+    pub fn format_with(&self, options: &FormatOptions) -> String {
+        if options.durations != DurationStyle::Default {
+            return options.durations.format(self.0);
+        }
+        let millis = self.0.num_milliseconds().abs();
+        let hm = format_hm(self.0);
+        match options.precision {
+            Precision::Minutes => hm,
+            Precision::Seconds => format!("{} {}s", hm, millis / 1000 % 60),
+            Precision::Millis => format!("{} {}.{:03}s", hm, millis / 1000 % 60, millis % 1000),
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format_with(&FormatOptions::default()))
+    }
+}
+
+/// How durations are written: `15h 42m` by default, or compact `15h42m`,
+/// verbose `15 hours 42 minutes`, clock `15:42:12` or ISO 8601 `PT15H42M12S`.
+/// Compact and verbose lengths under an hour keep their seconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DurationStyle {
+    #[default]
+    Default,
+    Compact,
+    Verbose,
+    Clock,
+    Iso,
+}
+
+impl DurationStyle {
+    /// The magnitude of `d` in this style.
+    pub fn format(&self, d: TimeDelta) -> String {
+        let secs = d.num_seconds().abs();
+        let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+        let plural = |n: i64, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+        match self {
+            DurationStyle::Default => format_hm(d),
+            DurationStyle::Compact if secs < 60 => format!("{}s", s),
+            DurationStyle::Compact if h == 0 => format!("{}m{:02}s", m, s),
+            DurationStyle::Compact => format!("{}h{:02}m", h, m),
+            DurationStyle::Verbose if secs < 60 => plural(s, "second"),
+            DurationStyle::Verbose if h == 0 => format!("{} {}", plural(m, "minute"), plural(s, "second")),
+            DurationStyle::Verbose => format!("{} {}", plural(h, "hour"), plural(m, "minute")),
+            DurationStyle::Clock => format!("{:02}:{:02}:{:02}", h, m, s),
+            DurationStyle::Iso if secs == 0 => "PT0S".to_string(),
+            DurationStyle::Iso => {
+                let parts = [(h, 'H'), (m, 'M'), (s, 'S')];
+                let parts: String = parts.iter().filter(|(n, _)| *n > 0).map(|(n, unit)| format!("{}{}", n, unit)).collect();
+                format!("PT{}", parts)
+            }
+        }
+    }
+
+    /// `d` with an explicit sign, as [`format_signed`] does by default.
+    pub fn format_signed(&self, d: TimeDelta) -> String {
+        match self {
+            DurationStyle::Default => format_signed(d),
+            style => format!("{}{}", if d < TimeDelta::zero() { "-" } else { "+" }, style.format(d)),
+        }
+    }
+}
+
+impl FromStr for DurationStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DurationStyle, String> {
+        match s.to_lowercase().as_str() {
+            "default" => Ok(DurationStyle::Default),
+            "compact" => Ok(DurationStyle::Compact),
+            "verbose" => Ok(DurationStyle::Verbose),
+            "clock" => Ok(DurationStyle::Clock),
+            "iso" => Ok(DurationStyle::Iso),
+            _ => Err(format!("unknown duration style `{}` (expected default, compact, verbose, clock or iso)", s)),
+        }
+    }
+}
+
+/// Parses a signed humantime duration such as `-30m`, `+1h` or `1h 15m`.
+pub fn parse_signed(s: &str) -> Result<TimeDelta, String> {
+    let (sign, rest) = match s.trim().strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, s.trim().strip_prefix('+').unwrap_or(s.trim())),
+    };
+    let d = humantime::parse_duration(rest).map_err(|e| format!("invalid duration `{}`: {}", s, e))?;
+    Ok(TimeDelta::from_std(d).map_err(|e| e.to_string())? * sign)
+}
+
+/// Formats the magnitude of `d` as e.g. `1h 23m`, or `7m` below an hour.
+pub fn format_hm(d: TimeDelta) -> String {
+    let minutes = d.num_minutes().abs();
+    match minutes / 60 {
+        0 => format!("{}m", minutes),
+        h => format!("{}h {}m", h, minutes % 60),
+    }
+}
+
+/// Formats the magnitude of `d` as e.g. `12d 4h`, or like [`format_hm`] below a day.
+pub fn format_days(d: TimeDelta) -> String {
+    let hours = d.num_hours().abs();
+    match hours / 24 {
+        0 => format_hm(d),
+        days => format!("{}d {}h", days, hours % 24),
+    }
+}
+
+/// Formats `d` with an explicit sign as e.g. `-3m 12s` or `+1h 4m 0s`.
+pub fn format_signed(d: TimeDelta) -> String {
+    let sign = if d < TimeDelta::zero() { "-" } else { "+" };
+    let secs = d.num_seconds().abs();
+    match (secs / 3600, secs / 60 % 60) {
+        (0, 0) => format!("{}{}s", sign, secs),
+        (0, m) => format!("{}{}m {}s", sign, m, secs % 60),
+        (h, m) => format!("{}{}h {}m {}s", sign, h, m, secs % 60),
+    }
+}
@@ -0,0 +1,369 @@
+//! Eclipses seen from one location: solar eclipses by searching new moons
+//! for overlaps of the topocentric discs of the Sun and the Moon, lunar
+//! eclipses by searching full moons for the Moon entering the Earth's shadow.
+
+use crate::constants::STANDARD_ALTITUDE;
+use crate::moon::{ecliptic_to_equatorial, moon_position, EARTH_RADIUS, MOON_RADIUS, SYNODIC_MONTH};
+use crate::orbit::AU;
+use crate::solar::{sidereal_time, solar_parallax, sun_distance};
+use crate::timescale::TimeScales;
+
+const SUN_RADIUS: f64 = 696000.0;
+/// Mean motion of the Moon relative to the Sun in degrees per day.
+const ELONGATION_RATE: f64 = 360.0 / SYNODIC_MONTH;
+const MINUTE: f64 = 1.0 / 1440.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolarEclipseKind {
+    Partial,
+    Annular,
+    Total,
+}
+
+impl SolarEclipseKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolarEclipseKind::Partial => "partial",
+            SolarEclipseKind::Annular => "annular",
+            SolarEclipseKind::Total => "total",
+        }
+    }
+}
+
+/// Local circumstances of a solar eclipse. Times are UTC Julian dates.
+#[derive(Clone, Copy, Debug)]
+pub struct SolarEclipse {
+    pub kind: SolarEclipseKind,
+    pub first_contact: f64,
+    pub maximum: f64,
+    pub last_contact: f64,
+    /// Start and end of totality or annularity.
+    pub central: Option<(f64, f64)>,
+    /// Fraction of the Sun's diameter covered at maximum.
+    pub magnitude: f64,
+    /// Elevation of the Sun's centre at maximum; negative when the maximum
+    /// happens below the horizon and only part of the eclipse is visible.
+    pub sun_elevation: f64,
+}
+
+/// Apparent longitude of the Sun (mean equinox of date, corrected for
+/// aberration) at the TT Julian date `jde`. The Moon moves its own diameter
+/// in an hour, so this uses the fuller series of Meeus, chapter 25, rather
+/// than the sunrise equation's.
+fn apparent_sun_longitude(jde: f64) -> f64 {
+    let t = (jde - 2451545.0) / 36525.0;
+    let l0 = 280.46646 + 36000.76983 * t + 0.0003032 * t * t;
+    let m = (357.52911 + 35999.05029 * t - 0.0001537 * t * t).to_radians();
+    let c = (1.914602 - 0.004817 * t - 0.000014 * t * t) * m.sin() + (0.019993 - 0.000101 * t) * (2.0 * m).sin() + 0.000289 * (3.0 * m).sin();
+    (l0 + c - 0.005694).rem_euclid(360.0)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LunarEclipseKind {
+    Penumbral,
+    Partial,
+    Total,
+}
+
+impl LunarEclipseKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LunarEclipseKind::Penumbral => "penumbral",
+            LunarEclipseKind::Partial => "partial",
+            LunarEclipseKind::Total => "total",
+        }
+    }
+}
+
+/// Phases of a lunar eclipse as UTC Julian dates of their start and end.
+/// The phases are the same for every observer; only whether the Moon is up
+/// depends on the location.
+#[derive(Clone, Copy, Debug)]
+pub struct LunarEclipse {
+    pub kind: LunarEclipseKind,
+    pub penumbral: (f64, f64),
+    /// The Moon partly in the umbra.
+    pub partial: Option<(f64, f64)>,
+    /// The Moon wholly in the umbra.
+    pub total: Option<(f64, f64)>,
+    pub maximum: f64,
+    /// Fraction of the Moon's diameter in the umbra at maximum, negative for penumbral eclipses.
+    pub umbral_magnitude: f64,
+    pub penumbral_magnitude: f64,
+    /// Elevation of the Moon's centre at maximum; negative when it is below the horizon.
+    pub moon_elevation: f64,
+}
+
+/// Geocentric elongation of the Moon from the Sun in (-180, 180] degrees and
+/// the Moon's latitude at the TT Julian date `jde`.
+fn elongation(jde: f64) -> (f64, f64) {
+    let (lambda, beta, _) = moon_position(jde);
+    ((lambda - apparent_sun_longitude(jde) + 540.0) % 360.0 - 180.0, beta)
+}
+
+/// UTC Julian date at or after `j` at which the elongation next reaches
+/// `target` degrees, with the Moon's latitude then.
+pub(crate) fn next_elongation(j: f64, target: f64) -> (f64, f64) {
+    let scales = TimeScales::default();
+    let offset = |jde: f64| (elongation(jde).0 - target + 540.0) % 360.0 - 180.0;
+    let mut jde = scales.utc_to_tt(j);
+    jde += (-offset(jde)).rem_euclid(360.0) / ELONGATION_RATE;
+    for _ in 0..6 {
+        jde -= offset(jde) / ELONGATION_RATE;
+    }
+    (scales.tt_to_utc(jde), elongation(jde).1)
+}
+
+/// UTC Julian date of the first new moon at or after `j` and the Moon's
+/// ecliptic latitude then; eclipses need it within about 1.6° of the ecliptic.
+pub fn next_new_moon(j: f64) -> (f64, f64) {
+    next_elongation(j, 0.0)
+}
+
+/// Like [`next_new_moon`] for the full moon.
+pub fn next_full_moon(j: f64) -> (f64, f64) {
+    next_elongation(j, 180.0)
+}
+
+/// Unit vector times `r` of right ascension `ra` and declination `dec` in degrees.
+fn cartesian(ra: f64, dec: f64, r: f64) -> [f64; 3] {
+    let (ra, dec) = (ra.to_radians(), dec.to_radians());
+    [r * dec.cos() * ra.cos(), r * dec.cos() * ra.sin(), r * dec.sin()]
+}
+
+fn norm(v: [f64; 3]) -> f64 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+fn angle(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let dot = a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    (dot / (norm(a) * norm(b))).clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// Topocentric equatorial positions of the Sun and the Moon in km, and the
+/// observer's zenith.
+struct Sky {
+    sun: [f64; 3],
+    moon: [f64; 3],
+    zenith: [f64; 3],
+}
+
+impl Sky {
+    fn at(scales: &TimeScales, lat: f64, long: f64, j: f64) -> Sky {
+        let jde = scales.utc_to_tt(j);
+        let (lambda, beta, distance) = moon_position(jde);
+        let (ra_moon, dec_moon) = ecliptic_to_equatorial(lambda, beta, jde);
+        let (ra_sun, dec_sun) = ecliptic_to_equatorial(apparent_sun_longitude(jde), 0.0, jde);
+        let theta = sidereal_time(scales.utc_to_ut1(j) - 2451545.0, long);
+        // Geocentric position of the observer on the reference ellipsoid
+        let u = (0.99664719 * lat.to_radians().tan()).atan();
+        let t = theta.to_radians();
+        let observer = [EARTH_RADIUS * u.cos() * t.cos(), EARTH_RADIUS * u.cos() * t.sin(), EARTH_RADIUS * 0.99664719 * u.sin()];
+        let topocentric = |v: [f64; 3]| [v[0] - observer[0], v[1] - observer[1], v[2] - observer[2]];
+        Sky {
+            sun: topocentric(cartesian(ra_sun, dec_sun, sun_distance(jde) * AU)),
+            moon: topocentric(cartesian(ra_moon, dec_moon, distance)),
+            zenith: cartesian(theta, lat, 1.0),
+        }
+    }
+
+    fn separation(&self) -> f64 {
+        angle(self.sun, self.moon)
+    }
+
+    fn sun_radius(&self) -> f64 {
+        (SUN_RADIUS / norm(self.sun)).asin().to_degrees()
+    }
+
+    fn moon_radius(&self) -> f64 {
+        (MOON_RADIUS / norm(self.moon)).asin().to_degrees()
+    }
+
+    fn sun_elevation(&self) -> f64 {
+        90.0 - angle(self.sun, self.zenith)
+    }
+
+    fn moon_elevation(&self) -> f64 {
+        90.0 - angle(self.moon, self.zenith)
+    }
+}
+
+/// Geocentric distance of the Moon from the centre of the Earth's shadow
+/// and the radii of the Moon, the umbra and the penumbra, in degrees.
+struct Shadow {
+    separation: f64,
+    moon: f64,
+    umbra: f64,
+    penumbra: f64,
+}
+
+impl Shadow {
+    fn at(scales: &TimeScales, j: f64) -> Shadow {
+        let jde = scales.utc_to_tt(j);
+        let (lambda, beta, distance) = moon_position(jde);
+        let antisolar = apparent_sun_longitude(jde) + 180.0;
+        let r = sun_distance(jde);
+        let separation = angle(cartesian(lambda, beta, 1.0), cartesian(antisolar, 0.0, 1.0));
+        let moon_parallax = (EARTH_RADIUS / distance).asin().to_degrees();
+        let sun_parallax = solar_parallax(jde);
+        let sun = (SUN_RADIUS / (r * AU)).asin().to_degrees();
+        // Danjon's rule: the atmosphere enlarges the shadow by about 1/50
+        Shadow {
+            separation,
+            moon: (MOON_RADIUS / distance).asin().to_degrees(),
+            umbra: 1.02 * (0.998340 * moon_parallax - sun + sun_parallax),
+            penumbra: 1.02 * (0.998340 * moon_parallax + sun + sun_parallax),
+        }
+    }
+}
+
+/// Time at which `f` turns positive, stepping from `inside` (where it is
+/// negative) by `step` and bisecting the last step.
+fn contact(f: impl Fn(f64) -> f64, inside: f64, step: f64) -> f64 {
+    let mut a = inside;
+    while f(a + step) < 0.0 {
+        a += step;
+    }
+    let mut b = a + step;
+    for _ in 0..20 {
+        let mid = (a + b) / 2.0;
+        if f(mid) < 0.0 {
+            a = mid;
+        } else {
+            b = mid;
+        }
+    }
+    (a + b) / 2.0
+}
+
+/// Argument of the minimum of `f` in `[a, b]` by golden-section search.
+fn minimize(f: impl Fn(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    const PHI: f64 = 0.618033988749895;
+    for _ in 0..30 {
+        let (c, d) = (b - PHI * (b - a), a + PHI * (b - a));
+        if f(c) < f(d) {
+            b = d;
+        } else {
+            a = c;
+        }
+    }
+    (a + b) / 2.0
+}
+
+/// Local circumstances of the solar eclipse around the new moon at the UTC
+/// Julian date `new_moon`, or `None` if the discs do not overlap while the
+/// Sun is above the horizon.
+pub fn solar_eclipse_at(lat: f64, long: f64, new_moon: f64) -> Option<SolarEclipse> {
+    let scales = TimeScales::default();
+    let sky = |j: f64| Sky::at(&scales, lat, long, j);
+    let gap = |j: f64| {
+        let s = sky(j);
+        s.separation() - s.sun_radius() - s.moon_radius()
+    };
+    // Parallax shifts the local maximum by at most a few hours from the geocentric conjunction
+    let (closest, _) = (-72..=72)
+        .map(|i| new_moon + i as f64 * 5.0 * MINUTE)
+        .map(|j| (j, gap(j)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))?;
+    let maximum = minimize(|j| sky(j).separation(), closest - 5.0 * MINUTE, closest + 5.0 * MINUTE);
+    if gap(maximum) >= 0.0 {
+        return None;
+    }
+    let first_contact = contact(gap, maximum, -MINUTE);
+    let last_contact = contact(gap, maximum, MINUTE);
+    let steps = ((last_contact - first_contact) / MINUTE).ceil() as usize;
+    if !(0..=steps).any(|i| sky(first_contact + i as f64 * MINUTE).sun_elevation() > STANDARD_ALTITUDE) {
+        return None;
+    }
+    let at_maximum = sky(maximum);
+    let (sun, moon) = (at_maximum.sun_radius(), at_maximum.moon_radius());
+    let inner = |j: f64| {
+        let s = sky(j);
+        s.separation() - (s.sun_radius() - s.moon_radius()).abs()
+    };
+    let central = (inner(maximum) < 0.0).then(|| (contact(inner, maximum, -MINUTE / 10.0), contact(inner, maximum, MINUTE / 10.0)));
+    let kind = match central {
+        None => SolarEclipseKind::Partial,
+        Some(_) if moon > sun => SolarEclipseKind::Total,
+        Some(_) => SolarEclipseKind::Annular,
+    };
+    Some(SolarEclipse {
+        kind,
+        first_contact,
+        maximum,
+        last_contact,
+        central,
+        magnitude: (sun + moon - at_maximum.separation()) / (2.0 * sun),
+        sun_elevation: at_maximum.sun_elevation(),
+    })
+}
+
+/// Up to `count` solar eclipses visible from the location between the UTC
+/// Julian dates `from` and `until`.
+pub fn solar_eclipses(lat: f64, long: f64, from: f64, until: f64, count: usize) -> Vec<SolarEclipse> {
+    let mut eclipses = Vec::new();
+    let mut t = from - 0.5;
+    while eclipses.len() < count {
+        let (new_moon, beta) = next_new_moon(t);
+        if new_moon > until + 0.5 {
+            break;
+        }
+        t = new_moon + 1.0;
+        if beta.abs() > 1.6 {
+            continue;
+        }
+        eclipses.extend(solar_eclipse_at(lat, long, new_moon).filter(|e| e.last_contact > from && e.first_contact < until));
+    }
+    eclipses
+}
+
+/// The lunar eclipse around the full moon at the UTC Julian date
+/// `full_moon`, with the Moon's elevation seen from the location, or `None`
+/// if the Moon misses the penumbra.
+pub fn lunar_eclipse_at(lat: f64, long: f64, full_moon: f64) -> Option<LunarEclipse> {
+    let scales = TimeScales::default();
+    let shadow = |j: f64| Shadow::at(&scales, j);
+    let maximum = minimize(|j| shadow(j).separation, full_moon - 0.25, full_moon + 0.25);
+    let at_maximum = shadow(maximum);
+    let phase = |outside: fn(&Shadow) -> f64| {
+        let f = |j: f64| outside(&shadow(j));
+        (f(maximum) < 0.0).then(|| (contact(f, maximum, -MINUTE), contact(f, maximum, MINUTE)))
+    };
+    let penumbral = phase(|s| s.separation - s.penumbra - s.moon)?;
+    let partial = phase(|s| s.separation - s.umbra - s.moon);
+    let total = phase(|s| s.separation - s.umbra + s.moon);
+    let kind = match (partial, total) {
+        (_, Some(_)) => LunarEclipseKind::Total,
+        (Some(_), None) => LunarEclipseKind::Partial,
+        (None, None) => LunarEclipseKind::Penumbral,
+    };
+    let magnitude = |radius: f64| (radius + at_maximum.moon - at_maximum.separation) / (2.0 * at_maximum.moon);
+    Some(LunarEclipse {
+        kind,
+        penumbral,
+        partial,
+        total,
+        maximum,
+        umbral_magnitude: magnitude(at_maximum.umbra),
+        penumbral_magnitude: magnitude(at_maximum.penumbra),
+        moon_elevation: Sky::at(&scales, lat, long, maximum).moon_elevation(),
+    })
+}
+
+/// Up to `count` lunar eclipses between the UTC Julian dates `from` and `until`.
+pub fn lunar_eclipses(lat: f64, long: f64, from: f64, until: f64, count: usize) -> Vec<LunarEclipse> {
+    let mut eclipses = Vec::new();
+    let mut t = from - 0.5;
+    while eclipses.len() < count {
+        let (full_moon, beta) = next_full_moon(t);
+        if full_moon > until + 0.5 {
+            break;
+        }
+        t = full_moon + 1.0;
+        if beta.abs() > 1.6 {
+            continue;
+        }
+        eclipses.extend(lunar_eclipse_at(lat, long, full_moon).filter(|e| e.penumbral.1 > from && e.penumbral.0 < until));
+    }
+    eclipses
+}
@@ -0,0 +1,197 @@
+//! When and where a photo was taken, from the EXIF of a JPEG or of a
+//! TIFF-based raw file, and keywords for it in an XMP sidecar.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use crate::export::schtasks::xml_escape;
+
+const EXIF_IFD: u16 = 0x8769;
+const GPS_IFD: u16 = 0x8825;
+const DATE_TIME: u16 = 0x0132;
+const DATE_TIME_ORIGINAL: u16 = 0x9003;
+const OFFSET_TIME_ORIGINAL: u16 = 0x9011;
+const GPS_LATITUDE_REF: u16 = 0x0001;
+const GPS_LATITUDE: u16 = 0x0002;
+const GPS_LONGITUDE_REF: u16 = 0x0003;
+const GPS_LONGITUDE: u16 = 0x0004;
+const GPS_TIME_STAMP: u16 = 0x0007;
+const GPS_DATE_STAMP: u16 = 0x001D;
+
+/// The tags of a photo so2lar needs, each missing where the camera did not
+/// write it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Exif {
+    /// The camera's clock when the shutter opened, in its own zone.
+    pub taken: Option<NaiveDateTime>,
+    /// The zone of `taken`, which newer cameras record.
+    pub offset: Option<FixedOffset>,
+    /// The GPS receiver's UTC time of the fix.
+    pub gps_time: Option<DateTime<Utc>>,
+    pub lat: Option<f64>,
+    pub long: Option<f64>,
+}
+
+impl Exif {
+    /// The EXIF of the image at `path`.
+    pub fn load(path: &Path) -> Result<Exif, String> {
+        let data = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Exif::parse(&data).ok_or_else(|| format!("{}: no EXIF found", path.display()))
+    }
+
+    /// The EXIF of a JPEG, or of a file that is a TIFF such as most raw formats.
+    pub fn parse(data: &[u8]) -> Option<Exif> {
+        let tiff = match data.get(..4)? {
+            [0xFF, 0xD8, ..] => jpeg_exif(data)?,
+            b"II*\0" | b"MM\0*" => data,
+            _ => return None,
+        };
+        let tiff = Tiff { data: tiff, big_endian: tiff.get(..2)? == b"MM" };
+        let ifd0 = tiff.ifd(tiff.u32(4)? as usize)?;
+        let mut exif = Exif::default();
+        let text = |field: Option<&Field>| field.and_then(|f| tiff.ascii(f));
+        let exif_ifd = ifd0.iter().find(|f| f.tag == EXIF_IFD).and_then(|f| tiff.ifd(f.offset as usize)).unwrap_or_default();
+        let find = |ifd: &[Field], tag| ifd.iter().find(|f| f.tag == tag).copied();
+        let taken = text(find(&exif_ifd, DATE_TIME_ORIGINAL).as_ref()).or_else(|| text(find(&ifd0, DATE_TIME).as_ref()));
+        exif.taken = taken.and_then(|s| NaiveDateTime::parse_from_str(&s, "%Y:%m:%d %H:%M:%S").ok());
+        exif.offset = text(find(&exif_ifd, OFFSET_TIME_ORIGINAL).as_ref()).and_then(|s| s.parse().ok());
+        if let Some(gps) = ifd0.iter().find(|f| f.tag == GPS_IFD).and_then(|f| tiff.ifd(f.offset as usize)) {
+            let coordinate = |reference, value, negative| {
+                let [d, m, s] = tiff.rationals::<3>(&find(&gps, value)?)?;
+                let sign = if text(find(&gps, reference).as_ref())? == negative { -1.0 } else { 1.0 };
+                Some(sign * (d + m / 60.0 + s / 3600.0))
+            };
+            exif.lat = coordinate(GPS_LATITUDE_REF, GPS_LATITUDE, "S");
+            exif.long = coordinate(GPS_LONGITUDE_REF, GPS_LONGITUDE, "W");
+            let day = text(find(&gps, GPS_DATE_STAMP).as_ref()).and_then(|s| NaiveDate::parse_from_str(&s, "%Y:%m:%d").ok());
+            let time = find(&gps, GPS_TIME_STAMP).and_then(|f| tiff.rationals::<3>(&f)).and_then(|[h, m, s]| {
+                NaiveTime::from_num_seconds_from_midnight_opt((h * 3600.0 + m * 60.0 + s.trunc()) as u32, (s.fract() * 1e9) as u32)
+            });
+            exif.gps_time = day.zip(time).map(|(day, time)| day.and_time(time).and_utc());
+        }
+        Some(exif)
+    }
+
+    /// When the photo was taken: the GPS time, or the camera's clock in its
+    /// recorded zone, or else in `zone`.
+    pub fn time<Tz: TimeZone>(&self, zone: &Tz) -> Option<DateTime<Utc>> {
+        if let Some(time) = self.gps_time {
+            return Some(time);
+        }
+        let taken = self.taken?;
+        match self.offset {
+            Some(offset) => Some(offset.from_local_datetime(&taken).single()?.to_utc()),
+            None => Some(zone.from_local_datetime(&taken).earliest()?.to_utc()),
+        }
+    }
+
+    /// The coordinates of the GPS fix.
+    pub fn location(&self) -> Option<(f64, f64)> {
+        self.lat.zip(self.long)
+    }
+}
+
+/// The TIFF inside the APP1 segment of a JPEG.
+fn jpeg_exif(data: &[u8]) -> Option<&[u8]> {
+    let mut i = 2;
+    while *data.get(i)? == 0xFF {
+        let marker = *data.get(i + 1)?;
+        // Entropy-coded data follows the start of scan
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let length = u16::from_be_bytes([*data.get(i + 2)?, *data.get(i + 3)?]) as usize;
+        let segment = data.get(i + 4..i + 2 + length)?;
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return Some(&segment[6..]);
+        }
+        i += 2 + length;
+    }
+    None
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Field {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    /// The offset of the value, or the value itself where it fits in four bytes.
+    offset: u32,
+    /// Where in the TIFF the four bytes of `offset` are.
+    at: usize,
+}
+
+struct Tiff<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl Tiff<'_> {
+    fn u16(&self, at: usize) -> Option<u16> {
+        let b = self.data.get(at..at + 2)?.try_into().ok()?;
+        Some(if self.big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) })
+    }
+
+    fn u32(&self, at: usize) -> Option<u32> {
+        let b = self.data.get(at..at + 4)?.try_into().ok()?;
+        Some(if self.big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    }
+
+    fn ifd(&self, at: usize) -> Option<Vec<Field>> {
+        let count = self.u16(at)? as usize;
+        (0..count)
+            .map(|i| {
+                let entry = at + 2 + 12 * i;
+                Some(Field { tag: self.u16(entry)?, kind: self.u16(entry + 2)?, count: self.u32(entry + 4)?, offset: self.u32(entry + 8)?, at: entry + 8 })
+            })
+            .collect()
+    }
+
+    /// The bytes of a field's value.
+    fn value(&self, field: &Field, size: usize) -> Option<&[u8]> {
+        let length = size.checked_mul(field.count as usize)?;
+        let start = if length <= 4 { field.at } else { field.offset as usize };
+        self.data.get(start..start.checked_add(length)?)
+    }
+
+    fn ascii(&self, field: &Field) -> Option<String> {
+        const ASCII: u16 = 2;
+        if field.kind != ASCII {
+            return None;
+        }
+        let bytes = self.value(field, 1)?;
+        let text = String::from_utf8_lossy(bytes.split(|b| *b == 0).next()?).trim().to_string();
+        (!text.is_empty()).then_some(text)
+    }
+
+    fn rationals<const N: usize>(&self, field: &Field) -> Option<[f64; N]> {
+        const RATIONAL: u16 = 5;
+        if field.kind != RATIONAL || (field.count as usize) < N {
+            return None;
+        }
+        let start = field.offset as usize;
+        let mut values = [0.0; N];
+        for (i, value) in values.iter_mut().enumerate() {
+            let (numerator, denominator) = (self.u32(start + 8 * i)?, self.u32(start + 8 * i + 4)?);
+            *value = numerator as f64 / denominator as f64;
+        }
+        values.iter().all(|v| v.is_finite()).then_some(values)
+    }
+}
+
+/// The XMP sidecar of `image`, next to it with the extension `.xmp`.
+pub fn sidecar_path(image: &Path) -> PathBuf {
+    image.with_extension("xmp")
+}
+
+/// An XMP sidecar giving the photo `keywords` as its subject, which photo
+/// managers read as keywords.
+pub fn sidecar(keywords: &[String]) -> String {
+    let items: String = keywords.iter().map(|k| format!("     <rdf:li>{}</rdf:li>\n", xml_escape(k))).collect();
+    format!(
+        "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n  <rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n   <dc:subject>\n    <rdf:Bag>\n{}    </rdf:Bag>\n   </dc:subject>\n  </rdf:Description>\n </rdf:RDF>\n</x:xmpmeta>\n",
+        items
+    )
+}
@@ -0,0 +1,28 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::solar::SolarEvent;
+
+fn expand(command: &str, event: SolarEvent) -> String {
+    command.replace("{event}", event.name())
+}
+
+/// One crontab line per event, each matching only its exact date. A `%`
+/// in the command is escaped, as cron would end the command there.
+pub fn write_cron<W: Write>(w: &mut W, events: &[(SolarEvent, DateTime<FixedOffset>)], command: &str) -> io::Result<()> {
+    for (event, t) in events {
+        let command = expand(command, *event).replace('%', r"\%");
+        writeln!(w, "{} {} {} {} * {} # so2lar {}", t.format("%-M"), t.format("%-H"), t.format("%-d"), t.format("%-m"), command, event.name())?;
+    }
+    Ok(())
+}
+
+/// One `at` invocation per event, suitable for piping into `sh`.
+pub fn write_at<W: Write>(w: &mut W, events: &[(SolarEvent, DateTime<FixedOffset>)], command: &str) -> io::Result<()> {
+    for (event, t) in events {
+        let command = expand(command, *event).replace('\'', r"'\''");
+        writeln!(w, "echo '{}' | at -t {}", command, t.format("%Y%m%d%H%M.%S"))?;
+    }
+    Ok(())
+}
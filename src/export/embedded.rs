@@ -0,0 +1,126 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc};
+
+use crate::format::{json_object, json_string, DisplayZone};
+use crate::parallel;
+use crate::solar::{degrees::crossings_tz, jdn, sun_position, Twilight};
+
+/// Marks days the sun does not reach the altitude of a column.
+pub const NONE: i16 = i16::MAX;
+
+const COLUMNS: [&str; 4] = ["dawn", "sunrise", "sunset", "dusk"];
+
+/// Civil dawn, sunrise, sunset and civil dusk of every day of a year as
+/// minutes after local midnight, DST included, for devices that keep a
+/// local clock and neither do floating point nor know about time zones.
+/// On days the sun stays above an altitude its crossings are 0 and 1440,
+/// on days it stays below they are [`NONE`].
+pub struct Almanac {
+    pub lat: f64,
+    pub long: f64,
+    pub year: i32,
+    pub zone: DisplayZone,
+    pub days: Vec<[i16; 4]>,
+}
+
+impl Almanac {
+    /// The almanac of `year` with the sun crossing `horizon` at sunrise and
+    /// sunset.
+    pub fn new(lat: f64, long: f64, year: i32, zone: DisplayZone, horizon: f64) -> Option<Almanac> {
+        let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+        let dates: Vec<NaiveDate> = first.iter_days().take_while(|d| d.year() == year).collect();
+        let days = parallel::map(&dates, |date| {
+            let today = jdn(*date);
+            let midnight = date.and_time(NaiveTime::MIN);
+            let minute = |t: DateTime<Utc>| (zone.convert(&t).naive_local() - midnight).num_minutes() as i16;
+            let [[dawn, dusk], [rise, set]] = [Twilight::Civil.altitude(), horizon].map(|altitude| match crossings_tz(lat, long, today, altitude, &Utc) {
+                Some((up, down)) => [minute(up), minute(down)],
+                None if sun_position(lat, long, today - long / 360.0).0 > altitude => [0, 1440],
+                None => [NONE; 2],
+            });
+            [dawn, rise, set, dusk]
+        });
+        Some(Almanac { lat, long, year, zone, days })
+    }
+
+    fn month_starts(&self) -> String {
+        let starts: Vec<String> = (1..=12).map(|m| NaiveDate::from_ymd_opt(self.year, m, 1).unwrap().ordinal0().to_string()).collect();
+        starts.join(", ")
+    }
+
+    fn rows(&self, open: &str, close: &str, none: &str) -> Vec<String> {
+        let minute = |m: i16| if m == NONE { none.to_string() } else { m.to_string() };
+        self.days.iter().map(|d| format!("{}{}{}", open, d.map(minute).join(", "), close)).collect()
+    }
+
+    fn entries(&self, open: &str, close: &str, none: &str) -> String {
+        self.rows(open, close, none).chunks(4).map(|line| format!("    {},", line.join(", "))).collect::<Vec<_>>().join("\n")
+    }
+
+    fn header(&self) -> String {
+        format!(
+            "Generated by so2lar for {:.6}, {:.6} in {}, {} time.\nCivil dawn, sunrise, sunset and civil dusk in minutes after local midnight;\n0 and 1440 when the sun stays above, NONE when it stays below.",
+            self.lat,
+            self.long,
+            self.year,
+            if self.zone == DisplayZone::Utc { "UTC" } else { "local" }
+        )
+    }
+
+    pub fn write_c<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "/* {} */", self.header().replace('\n', "\n * "))?;
+        writeln!(w, "#include <stdint.h>\n")?;
+        writeln!(w, "#define SO2LAR_YEAR {}", self.year)?;
+        writeln!(w, "#define SO2LAR_DAYS {}", self.days.len())?;
+        writeln!(w, "#define SO2LAR_NONE INT16_MAX")?;
+        writeln!(w, "enum {{ SO2LAR_DAWN, SO2LAR_SUNRISE, SO2LAR_SUNSET, SO2LAR_DUSK }};\n")?;
+        writeln!(w, "static const uint16_t so2lar_month_start[12] = {{ {} }};\n", self.month_starts())?;
+        writeln!(w, "static const int16_t so2lar_almanac[SO2LAR_DAYS][4] = {{")?;
+        writeln!(w, "{}", self.entries("{", "}", "SO2LAR_NONE"))?;
+        writeln!(w, "}};\n")?;
+        writeln!(w, "/* Minute of `column` on `day` of `month`, both from 1. */")?;
+        writeln!(w, "static inline int16_t so2lar_minute(int month, int day, int column) {{")?;
+        writeln!(w, "    return so2lar_almanac[so2lar_month_start[month - 1] + day - 1][column];\n}}\n")?;
+        writeln!(w, "/* 1 while the sun is up at `minute` after midnight, 0 otherwise. */")?;
+        writeln!(w, "static inline int so2lar_is_day(int month, int day, int minute) {{")?;
+        writeln!(w, "    int16_t rise = so2lar_minute(month, day, SO2LAR_SUNRISE), set = so2lar_minute(month, day, SO2LAR_SUNSET);")?;
+        writeln!(w, "    return rise != SO2LAR_NONE && minute >= rise && minute < set;\n}}")
+    }
+
+    pub fn write_rust<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "// {}\n", self.header().replace('\n', "\n// "))?;
+        writeln!(w, "pub const YEAR: i32 = {};", self.year)?;
+        writeln!(w, "pub const NONE: i16 = i16::MAX;")?;
+        writeln!(w, "pub const DAWN: usize = 0;\npub const SUNRISE: usize = 1;\npub const SUNSET: usize = 2;\npub const DUSK: usize = 3;\n")?;
+        writeln!(w, "const MONTH_START: [u16; 12] = [{}];\n", self.month_starts())?;
+        writeln!(w, "pub static ALMANAC: [[i16; 4]; {}] = [", self.days.len())?;
+        writeln!(w, "{}", self.entries("[", "]", "NONE"))?;
+        writeln!(w, "];\n")?;
+        writeln!(w, "/// Minute of `column` on `day` of `month`, both from 1.")?;
+        writeln!(w, "pub fn minute(month: u8, day: u8, column: usize) -> i16 {{")?;
+        writeln!(w, "    ALMANAC[MONTH_START[month as usize - 1] as usize + day as usize - 1][column]\n}}\n")?;
+        writeln!(w, "/// Whether the sun is up at `minute` after midnight.")?;
+        writeln!(w, "pub fn is_day(month: u8, day: u8, minute: i16) -> bool {{")?;
+        writeln!(w, "    let (rise, set) = (self::minute(month, day, SUNRISE), self::minute(month, day, SUNSET));")?;
+        writeln!(w, "    rise != NONE && minute >= rise && minute < set\n}}")
+    }
+
+    /// The days as arrays in the order of `columns`, with `null` for [`NONE`].
+    pub fn write_json<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let columns: Vec<String> = COLUMNS.iter().map(|c| json_string(c)).collect();
+        let zone = if self.zone == DisplayZone::Utc { "utc" } else { "local" };
+        writeln!(
+            w,
+            "{}",
+            json_object(&[
+                ("year", self.year.to_string()),
+                ("latitude", format!("{:.6}", self.lat)),
+                ("longitude", format!("{:.6}", self.long)),
+                ("zone", json_string(zone)),
+                ("columns", format!("[{}]", columns.join(","))),
+                ("days", format!("[{}]", self.rows("[", "]", "null").join(",").replace(", ", ","))),
+            ])
+        )
+    }
+}
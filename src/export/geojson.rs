@@ -0,0 +1,59 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::format::{json_object, json_string};
+use crate::solar::{datetime2julian, subsolar_point, SolarEvent};
+
+/// Angular distance from the subsolar point of the places seeing the sun's
+/// upper limb on the refracted horizon.
+const HORIZON_DISTANCE: f64 = 90.833;
+/// Bearings from the subsolar point between the points of the line.
+const STEP: f64 = 0.5;
+
+/// The places where `event` happens at `t`, as runs of `(long, lat)` points
+/// in degrees, split where a run crosses the antimeridian. They lie on the
+/// small circle 90.833° from the subsolar point: sunrise on its half west of
+/// the sun, where the sun has yet to culminate, sunset on the eastern half.
+pub fn event_line(event: SolarEvent, t: &DateTime<Utc>) -> Vec<Vec<(f64, f64)>> {
+    let (lat0, long0) = subsolar_point(datetime2julian(t));
+    let (rlat, d) = (lat0.to_radians(), HORIZON_DISTANCE.to_radians());
+    let points: Vec<((f64, f64), bool)> = (0..(360.0 / STEP) as usize)
+        .map(|i| {
+            let bearing = (i as f64 * STEP).to_radians();
+            let lat = (rlat.sin() * d.cos() + rlat.cos() * d.sin() * bearing.cos()).asin();
+            let long = long0.to_radians() + (bearing.sin() * d.sin() * rlat.cos()).atan2(d.cos() - rlat.sin() * lat.sin());
+            let long = (long.to_degrees() + 540.0).rem_euclid(360.0) - 180.0;
+            let hour_angle = (long - long0 + 540.0).rem_euclid(360.0) - 180.0;
+            ((long, lat.to_degrees()), (hour_angle < 0.0) == (event == SolarEvent::Sunrise))
+        })
+        .collect();
+    // Start the walk around the circle where the line begins
+    let start = (0..points.len()).find(|&i| points[i].1 && !points[(i + points.len() - 1) % points.len()].1).unwrap_or(0);
+    let mut runs: Vec<Vec<(f64, f64)>> = vec![Vec::new()];
+    for (point, on_line) in points[start..].iter().chain(&points[..start]) {
+        if !on_line {
+            continue;
+        }
+        let last = runs.last().and_then(|run| run.last());
+        if last.is_some_and(|last| (last.0 - point.0).abs() > 180.0) {
+            runs.push(Vec::new());
+        }
+        runs.last_mut().unwrap().push(*point);
+    }
+    runs.retain(|run| run.len() > 1);
+    runs
+}
+
+/// A GeoJSON feature collection with the line of `event` at `t` as a
+/// MultiLineString.
+pub fn write_event_line<W: Write>(w: &mut W, event: SolarEvent, t: &DateTime<Utc>) -> io::Result<()> {
+    let runs: Vec<String> = event_line(event, t)
+        .iter()
+        .map(|run| format!("[{}]", run.iter().map(|(long, lat)| format!("[{:.4},{:.4}]", long, lat)).collect::<Vec<_>>().join(",")))
+        .collect();
+    let geometry = json_object(&[("type", json_string("MultiLineString")), ("coordinates", format!("[{}]", runs.join(",")))]);
+    let properties = json_object(&[("event", json_string(event.name())), ("time", json_string(&t.to_rfc3339_opts(SecondsFormat::Secs, true)))]);
+    let feature = json_object(&[("type", json_string("Feature")), ("properties", properties), ("geometry", geometry)]);
+    writeln!(w, "{}", json_object(&[("type", json_string("FeatureCollection")), ("features", format!("[{}]", feature))]))
+}
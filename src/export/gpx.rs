@@ -0,0 +1,105 @@
+use std::error::Error;
+use std::io::Write;
+use std::sync::LazyLock;
+
+use chrono::DateTime;
+use regex::{Captures, Regex};
+
+use crate::constants::STANDARD_ALTITUDE;
+use crate::glare::RoutePoint;
+use crate::solar::{datetime2julian, sun_position};
+
+const NAMESPACE: &str = "urn:so2lar:gpx:1";
+
+static POINT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<(trkpt|wpt|rtept)\b([^>]*)>(.*?)</(trkpt|wpt|rtept)>").unwrap());
+static LAT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\blat\s*=\s*["']([^"']+)["']"#).unwrap());
+static LON: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\blon\s*=\s*["']([^"']+)["']"#).unwrap());
+static TIME: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<time>\s*([^<]+?)\s*</time>").unwrap());
+
+struct Annotation {
+    lat: f64,
+    long: f64,
+    time: String,
+    elevation: f64,
+    azimuth: f64,
+}
+
+impl Annotation {
+    fn daylight(&self) -> bool {
+        self.elevation > STANDARD_ALTITUDE
+    }
+}
+
+fn annotate(attrs: &str, body: &str) -> Result<Option<Annotation>, Box<dyn Error>> {
+    let attr = |re: &Regex, name: &str| -> Result<f64, Box<dyn Error>> {
+        let value = re.captures(attrs).ok_or_else(|| format!("point without `{}` attribute", name))?;
+        Ok(value[1].trim().parse()?)
+    };
+    let Some(time) = TIME.captures(body) else {
+        return Ok(None);
+    };
+    let (lat, long) = (attr(&LAT, "lat")?, attr(&LON, "lon")?);
+    let t = DateTime::parse_from_rfc3339(&time[1])?;
+    let (elevation, azimuth) = sun_position(lat, long, datetime2julian(&t));
+    Ok(Some(Annotation { lat, long, time: time[1].to_string(), elevation, azimuth }))
+}
+
+/// Copies `gpx` adding sun elevation, azimuth and a daylight flag as
+/// extensions to every timestamped track, route and waypoint.
+pub fn write_annotated_gpx<W: Write>(w: &mut W, gpx: &str) -> Result<(), Box<dyn Error>> {
+    let mut error = None;
+    let annotated = POINT.replace_all(gpx, |caps: &Captures| {
+        let (kind, attrs, body) = (&caps[1], &caps[2], &caps[3]);
+        match annotate(attrs, body) {
+            Ok(Some(a)) => {
+                let ext = format!(
+                    "<so2lar:sun_elevation>{:.3}</so2lar:sun_elevation><so2lar:sun_azimuth>{:.3}</so2lar:sun_azimuth><so2lar:daylight>{}</so2lar:daylight>",
+                    a.elevation, a.azimuth, a.daylight()
+                );
+                let body = match body.find("</extensions>") {
+                    Some(i) => format!("{}{}{}", &body[..i], ext, &body[i..]),
+                    None => format!("{}<extensions>{}</extensions>", body, ext),
+                };
+                format!("<{}{}>{}</{}>", kind, attrs, body, kind)
+            }
+            Ok(None) => caps[0].to_string(),
+            Err(e) => {
+                error.get_or_insert(e);
+                caps[0].to_string()
+            }
+        }
+    });
+    if let Some(e) = error {
+        return Err(e);
+    }
+    let header = Regex::new(r"<gpx\b").unwrap();
+    let annotated = if annotated.contains(NAMESPACE) {
+        annotated.into_owned()
+    } else {
+        header.replace(&annotated, format!(r#"<gpx xmlns:so2lar="{}""#, NAMESPACE)).into_owned()
+    };
+    w.write_all(annotated.as_bytes())?;
+    Ok(())
+}
+
+/// Writes one CSV row per timestamped point of `gpx`.
+pub fn write_annotated_csv<W: Write>(w: &mut W, gpx: &str) -> Result<(), Box<dyn Error>> {
+    writeln!(w, "kind,lat,lon,time,sun_elevation,sun_azimuth,daylight")?;
+    for caps in POINT.captures_iter(gpx) {
+        if let Some(a) = annotate(&caps[2], &caps[3])? {
+            writeln!(w, "{},{},{},{},{:.3},{:.3},{}", &caps[1], a.lat, a.long, a.time, a.elevation, a.azimuth, a.daylight())?;
+        }
+    }
+    Ok(())
+}
+
+/// Position and time of every timestamped point of `gpx`, in document order.
+pub fn timed_points(gpx: &str) -> Result<Vec<RoutePoint>, Box<dyn Error>> {
+    let mut points = Vec::new();
+    for caps in POINT.captures_iter(gpx) {
+        if let Some(a) = annotate(&caps[2], &caps[3])? {
+            points.push((a.lat, a.long, DateTime::parse_from_rfc3339(&a.time)?.to_utc()));
+        }
+    }
+    Ok(points)
+}
@@ -0,0 +1,66 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::duration::parse_signed;
+
+/// A reminder attached to calendar events, written as `-30m@sunset`. Without
+/// the `@event` part the alarm is attached to every event.
+pub struct Alarm {
+    offset: TimeDelta,
+    event: Option<String>,
+}
+
+impl FromStr for Alarm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Alarm, String> {
+        let (offset, event) = match s.split_once('@') {
+            Some((offset, event)) => (offset, Some(event.to_lowercase())),
+            None => (s, None),
+        };
+        let offset = parse_signed(offset).map_err(|e| format!("invalid alarm `{}`: {}", s, e))?;
+        Ok(Alarm { offset, event })
+    }
+}
+
+impl Alarm {
+    fn applies_to(&self, event: &str) -> bool {
+        self.event.as_deref().is_none_or(|e| e == event.to_lowercase())
+    }
+
+    fn trigger(&self) -> String {
+        let secs = self.offset.num_seconds();
+        let sign = if secs < 0 { "-" } else { "" };
+        let secs = secs.abs();
+        format!("{}PT{}H{}M{}S", sign, secs / 3600, secs / 60 % 60, secs % 60)
+    }
+}
+
+pub struct Event {
+    pub name: &'static str,
+    pub time: DateTime<Utc>,
+}
+
+fn stamp(t: &DateTime<Utc>) -> String {
+    t.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// The calendar of `events`, stamped as written at `now`.
+pub fn write_ics<W: Write>(w: &mut W, lat: f64, long: f64, events: &[Event], alarms: &[Alarm], now: DateTime<Utc>) -> io::Result<()> {
+    let now = stamp(&now);
+    write!(w, "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//so2lar//EN\r\nCALSCALE:GREGORIAN\r\n")?;
+    for event in events {
+        let start = stamp(&event.time);
+        write!(w, "BEGIN:VEVENT\r\n")?;
+        write!(w, "UID:{}-{}-{:.4}-{:.4}@so2lar\r\n", event.name.to_lowercase(), start, lat, long)?;
+        write!(w, "DTSTAMP:{}\r\nDTSTART:{}\r\nDTEND:{}\r\n", now, start, start)?;
+        write!(w, "SUMMARY:{}\r\nGEO:{:.6};{:.6}\r\nTRANSP:TRANSPARENT\r\n", event.name, lat, long)?;
+        for alarm in alarms.iter().filter(|a| a.applies_to(event.name)) {
+            write!(w, "BEGIN:VALARM\r\nACTION:DISPLAY\r\nDESCRIPTION:{}\r\nTRIGGER:{}\r\nEND:VALARM\r\n", event.name, alarm.trigger())?;
+        }
+        write!(w, "END:VEVENT\r\n")?;
+    }
+    write!(w, "END:VCALENDAR\r\n")
+}
@@ -0,0 +1,63 @@
+use std::io::{self, Write};
+
+use crate::solar::{julian2utc, sun_position};
+
+const EARTH_RADIUS: f64 = 6_371_000.0;
+const RAY_LENGTH: f64 = 10_000.0;
+const PATH_RADIUS: f64 = 5_000.0;
+const PATH_STEP: f64 = 10.0 / 1440.0;
+
+fn destination(lat: f64, long: f64, bearing: f64, distance: f64) -> (f64, f64) {
+    let (rlat, rlong, rbrg) = (lat.to_radians(), long.to_radians(), bearing.to_radians());
+    let d = distance / EARTH_RADIUS;
+    let lat2 = (rlat.sin() * d.cos() + rlat.cos() * d.sin() * rbrg.cos()).asin();
+    let long2 = rlong + (rbrg.sin() * d.sin() * rlat.cos()).atan2(d.cos() - rlat.sin() * lat2.sin());
+    (lat2.to_degrees(), (long2.to_degrees() + 540.0) % 360.0 - 180.0)
+}
+
+fn write_ray<W: Write>(w: &mut W, name: &str, lat: f64, long: f64, j: f64) -> io::Result<()> {
+    let (_, azimuth) = sun_position(lat, long, j);
+    let (lat2, long2) = destination(lat, long, azimuth, RAY_LENGTH);
+    writeln!(w, "    <Placemark>")?;
+    writeln!(w, "      <name>{} {} ({:.1}°)</name>", name, julian2utc(j).to_rfc3339(), azimuth)?;
+    writeln!(w, "      <styleUrl>#ray</styleUrl>")?;
+    writeln!(w, "      <LineString><coordinates>{},{},0 {},{},0</coordinates></LineString>", long, lat, long2, lat2)?;
+    writeln!(w, "    </Placemark>")
+}
+
+/// Writes the sun trajectory between `rise` and `set` (true Julian dates) as
+/// a 3D line above a circle around the observer, plus rays towards the
+/// sunrise and sunset azimuths.
+pub fn write_kml<W: Write>(w: &mut W, lat: f64, long: f64, rise: f64, set: f64) -> io::Result<()> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(w, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(w, "  <Document>")?;
+    writeln!(w, "    <name>Sun path {}</name>", julian2utc(rise).date_naive())?;
+    writeln!(w, r#"    <Style id="ray"><LineStyle><color>ff00aaff</color><width>3</width></LineStyle></Style>"#)?;
+    writeln!(w, r#"    <Style id="path"><LineStyle><color>ff00ffff</color><width>2</width></LineStyle></Style>"#)?;
+    writeln!(w, "    <Placemark>")?;
+    writeln!(w, "      <name>Observer</name>")?;
+    writeln!(w, "      <Point><coordinates>{},{},0</coordinates></Point>", long, lat)?;
+    writeln!(w, "    </Placemark>")?;
+    write_ray(w, "Sunrise", lat, long, rise)?;
+    write_ray(w, "Sunset", lat, long, set)?;
+    writeln!(w, "    <Placemark>")?;
+    writeln!(w, "      <name>Sun path</name>")?;
+    writeln!(w, "      <styleUrl>#path</styleUrl>")?;
+    writeln!(w, "      <LineString>")?;
+    writeln!(w, "        <altitudeMode>relativeToGround</altitudeMode>")?;
+    write!(w, "        <coordinates>")?;
+    let steps = ((set - rise) / PATH_STEP).ceil() as usize;
+    for i in 0..=steps {
+        let j = (rise + i as f64 * PATH_STEP).min(set);
+        let (elevation, azimuth) = sun_position(lat, long, j);
+        let (lat2, long2) = destination(lat, long, azimuth, PATH_RADIUS);
+        let altitude = PATH_RADIUS * elevation.clamp(0.0, 85.0).to_radians().tan();
+        write!(w, "{},{},{:.0} ", long2, lat2, altitude)?;
+    }
+    writeln!(w, "</coordinates>")?;
+    writeln!(w, "      </LineString>")?;
+    writeln!(w, "    </Placemark>")?;
+    writeln!(w, "  </Document>")?;
+    writeln!(w, "</kml>")
+}
@@ -0,0 +1,14 @@
+pub mod cron;
+pub mod embedded;
+pub mod geojson;
+pub mod gpx;
+pub mod ics;
+pub mod kml;
+pub mod nightlight;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+pub mod relay;
+pub mod schtasks;
+pub mod sqlite;
+pub mod systemd;
+pub mod table;
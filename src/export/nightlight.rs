@@ -0,0 +1,39 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+/// Colour temperature of the screen at night in kelvin, wlsunset's default.
+pub const NIGHT_TEMPERATURE: u32 = 4000;
+
+/// A day for a Wayland night-light tool: warm from sunset to sunrise,
+/// fading over `transition`, the length of civil twilight.
+pub struct NightLight {
+    pub sunrise: DateTime<FixedOffset>,
+    pub sunset: DateTime<FixedOffset>,
+    pub transition: TimeDelta,
+    pub temperature: u32,
+}
+
+impl NightLight {
+    /// Arguments for wlsunset's manual mode, which fades from `-S` minus
+    /// `-d` to `-S` in the morning and from `-s` to `-s` plus `-d` at night.
+    pub fn wlsunset_args(&self) -> Vec<String> {
+        vec![
+            "-S".to_string(),
+            self.sunrise.format("%H:%M").to_string(),
+            "-s".to_string(),
+            self.sunset.format("%H:%M").to_string(),
+            "-d".to_string(),
+            self.transition.num_seconds().to_string(),
+            "-t".to_string(),
+            self.temperature.to_string(),
+        ]
+    }
+
+    /// hyprsunset.conf switching to `temperature` at sunset and back at sunrise.
+    pub fn write_hyprsunset<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "# Written by so2lar for {}", self.sunrise.format("%Y-%m-%d"))?;
+        writeln!(w, "profile {{\n    time = {}\n    identity = true\n}}\n", self.sunrise.format("%-H:%M"))?;
+        writeln!(w, "profile {{\n    time = {}\n    temperature = {}\n}}", self.sunset.format("%-H:%M"), self.temperature)
+    }
+}
@@ -0,0 +1,275 @@
+//! A minimal Parquet writer: one row group, one uncompressed PLAIN data page
+//! per column, typed dates and UTC timestamps, so DuckDB, pandas or Spark
+//! read the output directly.
+
+use std::io::{self, Write};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+const MAGIC: &[u8] = b"PAR1";
+
+/// One column of values, all columns of a file having the same length.
+pub enum Column {
+    Date(Vec<NaiveDate>),
+    /// Microseconds since the epoch in UTC; `None` is written as null.
+    Timestamp(Vec<Option<DateTime<Utc>>>),
+    Int64(Vec<i64>),
+    Double(Vec<f64>),
+}
+
+impl Column {
+    fn len(&self) -> usize {
+        match self {
+            Column::Date(v) => v.len(),
+            Column::Timestamp(v) => v.len(),
+            Column::Int64(v) => v.len(),
+            Column::Double(v) => v.len(),
+        }
+    }
+
+    /// Parquet physical type: INT32, INT64 or DOUBLE.
+    fn physical_type(&self) -> i32 {
+        match self {
+            Column::Date(_) => 1,
+            Column::Timestamp(_) | Column::Int64(_) => 2,
+            Column::Double(_) => 5,
+        }
+    }
+
+    fn nullable(&self) -> bool {
+        matches!(self, Column::Timestamp(_))
+    }
+
+    /// Definition levels and PLAIN-encoded values of a data page.
+    fn page_data(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        match self {
+            Column::Date(v) => {
+                let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+                for date in v {
+                    data.extend(((*date - epoch).num_days() as i32).to_le_bytes());
+                }
+            }
+            Column::Timestamp(v) => {
+                let levels = definition_levels(v.iter().map(Option::is_some));
+                data.extend((levels.len() as u32).to_le_bytes());
+                data.extend(levels);
+                for t in v.iter().flatten() {
+                    data.extend(t.timestamp_micros().to_le_bytes());
+                }
+            }
+            Column::Int64(v) => v.iter().for_each(|n| data.extend(n.to_le_bytes())),
+            Column::Double(v) => v.iter().for_each(|x| data.extend(x.to_le_bytes())),
+        }
+        data
+    }
+
+    fn schema_element(&self, name: &str) -> Vec<u8> {
+        let mut s = Struct::default();
+        s.i32(1, self.physical_type());
+        s.i32(3, if self.nullable() { 1 } else { 0 });
+        s.binary(4, name.as_bytes());
+        let mut logical = Struct::default();
+        match self {
+            Column::Date(_) => {
+                // converted_type DATE, logicalType DATE
+                s.i32(6, 6);
+                logical.with_struct(6, &Struct::default());
+                s.with_struct(10, &logical);
+            }
+            Column::Timestamp(_) => {
+                // converted_type TIMESTAMP_MICROS, logicalType TIMESTAMP(isAdjustedToUTC, MICROS)
+                s.i32(6, 10);
+                let mut unit = Struct::default();
+                unit.with_struct(2, &Struct::default());
+                let mut timestamp = Struct::default();
+                timestamp.bool(1, true);
+                timestamp.with_struct(2, &unit);
+                logical.with_struct(8, &timestamp);
+                s.with_struct(10, &logical);
+            }
+            Column::Int64(_) | Column::Double(_) => {}
+        }
+        s.end()
+    }
+}
+
+/// Definition levels with a bit width of 1 as runs of the RLE/bit-packed
+/// hybrid encoding.
+fn definition_levels(defined: impl Iterator<Item = bool>) -> Vec<u8> {
+    let mut runs: Vec<(bool, u64)> = Vec::new();
+    for d in defined {
+        match runs.last_mut() {
+            Some((value, n)) if *value == d => *n += 1,
+            _ => runs.push((d, 1)),
+        }
+    }
+    let mut out = Vec::new();
+    for (value, n) in runs {
+        varint(&mut out, n << 1);
+        out.push(value as u8);
+    }
+    out
+}
+
+/// Writes `columns` as a Parquet file with one row group.
+pub fn write_parquet<W: Write>(w: &mut W, columns: &[(&str, Column)]) -> io::Result<()> {
+    let rows = columns.first().map_or(0, |(_, c)| c.len());
+    if columns.iter().any(|(_, c)| c.len() != rows) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Parquet columns differ in length"));
+    }
+    w.write_all(MAGIC)?;
+    let mut offset = MAGIC.len() as i64;
+    let mut chunks = Vec::new();
+    let mut total = 0;
+    for (name, column) in columns {
+        let data = column.page_data();
+        let mut page = Struct::default();
+        page.i32(1, 0);
+        page.i32(2, data.len() as i32);
+        page.i32(3, data.len() as i32);
+        let mut header = Struct::default();
+        header.i32(1, rows as i32);
+        header.i32(2, 0);
+        header.i32(3, 3);
+        header.i32(4, 3);
+        page.with_struct(5, &header);
+        let page = page.end();
+        w.write_all(&page)?;
+        w.write_all(&data)?;
+        let size = (page.len() + data.len()) as i64;
+
+        let mut meta = Struct::default();
+        meta.i32(1, column.physical_type());
+        meta.list(2, I32, &[encode_i32(0), encode_i32(3)]);
+        meta.list(3, BINARY, &[encode_binary(name.as_bytes())]);
+        meta.i32(4, 0);
+        meta.i64(5, rows as i64);
+        meta.i64(6, size);
+        meta.i64(7, size);
+        meta.i64(9, offset);
+        let mut chunk = Struct::default();
+        chunk.i64(2, offset);
+        chunk.with_struct(3, &meta);
+        chunks.push(chunk.end());
+        offset += size;
+        total += size;
+    }
+
+    let mut root = Struct::default();
+    root.binary(4, b"schema");
+    root.i32(5, columns.len() as i32);
+    let mut schema = vec![root.end()];
+    schema.extend(columns.iter().map(|(name, column)| column.schema_element(name)));
+    let mut row_group = Struct::default();
+    row_group.list(1, STRUCT, &chunks);
+    row_group.i64(2, total);
+    row_group.i64(3, rows as i64);
+    let mut file = Struct::default();
+    file.i32(1, 1);
+    file.list(2, STRUCT, &schema);
+    file.i64(3, rows as i64);
+    file.list(4, STRUCT, &[row_group.end()]);
+    file.binary(6, concat!("so2lar ", env!("CARGO_PKG_VERSION")).as_bytes());
+    let footer = file.end();
+    w.write_all(&footer)?;
+    w.write_all(&(footer.len() as u32).to_le_bytes())?;
+    w.write_all(MAGIC)
+}
+
+// Thrift compact protocol, which Parquet uses for its metadata
+const TRUE: u8 = 1;
+const FALSE: u8 = 2;
+const I32: u8 = 5;
+const I64: u8 = 6;
+const BINARY: u8 = 8;
+const LIST: u8 = 9;
+const STRUCT: u8 = 12;
+
+fn varint(out: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        out.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn encode_i32(n: i32) -> Vec<u8> {
+    let mut out = Vec::new();
+    varint(&mut out, zigzag(n as i64));
+    out
+}
+
+fn encode_binary(b: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    varint(&mut out, b.len() as u64);
+    out.extend(b);
+    out
+}
+
+/// A struct being encoded; fields must be added in increasing id order.
+#[derive(Default)]
+struct Struct {
+    out: Vec<u8>,
+    last: i16,
+}
+
+impl Struct {
+    fn field(&mut self, id: i16, kind: u8) {
+        match id - self.last {
+            delta @ 1..=15 => self.out.push((delta as u8) << 4 | kind),
+            _ => {
+                self.out.push(kind);
+                varint(&mut self.out, zigzag(id as i64));
+            }
+        }
+        self.last = id;
+    }
+
+    fn bool(&mut self, id: i16, b: bool) {
+        self.field(id, if b { TRUE } else { FALSE });
+    }
+
+    fn i32(&mut self, id: i16, n: i32) {
+        self.field(id, I32);
+        varint(&mut self.out, zigzag(n as i64));
+    }
+
+    fn i64(&mut self, id: i16, n: i64) {
+        self.field(id, I64);
+        varint(&mut self.out, zigzag(n));
+    }
+
+    fn binary(&mut self, id: i16, b: &[u8]) {
+        self.field(id, BINARY);
+        self.out.extend(encode_binary(b));
+    }
+
+    /// A list of already encoded elements of type `kind`.
+    fn list(&mut self, id: i16, kind: u8, elements: &[Vec<u8>]) {
+        self.field(id, LIST);
+        match elements.len() {
+            n @ 0..=14 => self.out.push((n as u8) << 4 | kind),
+            n => {
+                self.out.push(0xf0 | kind);
+                varint(&mut self.out, n as u64);
+            }
+        }
+        elements.iter().for_each(|e| self.out.extend(e));
+    }
+
+    fn with_struct(&mut self, id: i16, s: &Struct) {
+        self.field(id, STRUCT);
+        self.out.extend(s.end());
+    }
+
+    fn end(&self) -> Vec<u8> {
+        let mut out = self.out.clone();
+        out.push(0);
+        out
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::window::Window;
+
+/// A relay switched on at `on` and off at `off`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Switch {
+    pub on: DateTime<Utc>,
+    pub off: DateTime<Utc>,
+}
+
+/// On and off times of `window` for `days` days from the day `today`. A
+/// window whose end comes before its start, such as `sunset-15m to
+/// sunrise+15m`, runs overnight into the next day. Days without the start
+/// event are left out.
+pub fn schedule(window: &Window, lat: f64, long: f64, today: f64, days: u32) -> Vec<Switch> {
+    (0..days)
+        .filter_map(|day| {
+            let today = today + day as f64;
+            let on = window.start.time(lat, long, today)?;
+            let off = [today, today + 1.0]
+                .into_iter()
+                .filter_map(|day| window.end.time(lat, long, day))
+                .find(|off| *off > on)?;
+            Some(Switch { on, off })
+        })
+        .collect()
+}
+
+/// An ESPHome `time:` block turning the switch `id` on and off at each
+/// switch time, dated to the day, so the device needs no sun component.
+/// The entries carry no year and repeat after one.
+pub fn write_esphome<W: Write>(w: &mut W, switches: &[Switch], id: &str, window: &Window) -> io::Result<()> {
+    writeln!(w, "# Written by so2lar: {} on {}", id, window)?;
+    writeln!(w, "time:\n  - platform: sntp\n    timezone: UTC\n    on_time:")?;
+    for switch in switches {
+        for (t, action) in [(switch.on, "turn_on"), (switch.off, "turn_off")] {
+            // Entries fire at second 0, so round to the nearest minute
+            let t = t + TimeDelta::seconds(30);
+            writeln!(w, "      - seconds: 0")?;
+            writeln!(w, "        minutes: {}\n        hours: {}", t.format("%-M"), t.format("%-H"))?;
+            writeln!(w, "        days_of_month: {}\n        months: {}", t.format("%-d"), t.format("%-m"))?;
+            writeln!(w, "        then:\n          - switch.{}: {}", action, id)?;
+        }
+    }
+    Ok(())
+}
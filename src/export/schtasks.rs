@@ -0,0 +1,136 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{DateTime, Local};
+
+use crate::solar::SolarEvent;
+
+/// A Windows scheduled task running `command` at the next `event`, the
+/// Task Scheduler counterpart of [`crate::export::systemd::TimerSpec`].
+pub struct TaskSpec<'a> {
+    pub event: SolarEvent,
+    /// Task name below the `so2lar` folder of the Task Scheduler library.
+    pub name: &'a str,
+    /// Command line run through `cmd.exe /C`.
+    pub command: &'a str,
+    pub next: DateTime<Local>,
+    /// Program and arguments that recreate these tasks, run daily by the refresh task.
+    pub refresh_command: (String, String),
+}
+
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// `s` in double quotes as one argument of a Windows command line. Programs
+/// read backslashes literally except before a quote, where each pair is one
+/// backslash, so those, and a trailing run before the closing quote, are doubled.
+pub fn quote_argument(s: &str) -> String {
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in s.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        let escapes = if c == '"' { 2 * backslashes + 1 } else { backslashes };
+        quoted.extend(std::iter::repeat_n('\\', escapes));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.extend(std::iter::repeat_n('\\', 2 * backslashes));
+    quoted.push('"');
+    quoted
+}
+
+/// Task definition XML, see the Task Scheduler schema.
+fn task_xml(description: &str, trigger: &str, program: &str, arguments: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-16\"?>\r\n\
+         <Task version=\"1.2\" xmlns=\"http://schemas.microsoft.com/windows/2004/02/mit/task\">\r\n\
+         \x20 <RegistrationInfo>\r\n\
+         \x20   <Description>{}</Description>\r\n\
+         \x20 </RegistrationInfo>\r\n\
+         \x20 <Triggers>\r\n\
+         {}\
+         \x20 </Triggers>\r\n\
+         \x20 <Settings>\r\n\
+         \x20   <StartWhenAvailable>true</StartWhenAvailable>\r\n\
+         \x20   <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>\r\n\
+         \x20   <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>\r\n\
+         \x20 </Settings>\r\n\
+         \x20 <Actions>\r\n\
+         \x20   <Exec>\r\n\
+         \x20     <Command>{}</Command>\r\n\
+         \x20     <Arguments>{}</Arguments>\r\n\
+         \x20   </Exec>\r\n\
+         \x20 </Actions>\r\n\
+         </Task>\r\n",
+        xml_escape(description),
+        trigger,
+        xml_escape(program),
+        xml_escape(arguments)
+    )
+}
+
+/// `schtasks` expects the XML in UTF-16 with a byte order mark.
+fn write_utf16(path: &Path, s: &str) -> io::Result<()> {
+    let bytes: Vec<u8> = std::iter::once(0xfeff).chain(s.encode_utf16()).flat_map(u16::to_le_bytes).collect();
+    fs::write(path, bytes)
+}
+
+impl TaskSpec<'_> {
+    /// Full name of the task and of its refresh task in the Task Scheduler library.
+    pub fn task_names(&self) -> (String, String) {
+        (format!("so2lar\\{}", self.name), format!("so2lar\\{}-refresh", self.name))
+    }
+
+    /// Writes `<name>.xml` running the command at the next event and
+    /// `<name>-refresh.xml` recreating both every night. Returns the paths of
+    /// the written files.
+    pub fn write(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        let task = dir.join(format!("{}.xml", self.name));
+        let start = self.next.format("%Y-%m-%dT%H:%M:%S%:z");
+        write_utf16(
+            &task,
+            &task_xml(
+                &format!("Generated by so2lar: run at {}, rewritten by the {}-refresh task", self.event.name(), self.name),
+                &format!("    <TimeTrigger>\r\n      <StartBoundary>{}</StartBoundary>\r\n    </TimeTrigger>\r\n", start),
+                "cmd.exe",
+                &format!("/C {}", self.command),
+            ),
+        )?;
+        let refresh = dir.join(format!("{}-refresh.xml", self.name));
+        let midnight = self.next.date_naive().and_hms_opt(0, 5, 0).unwrap().format("%Y-%m-%dT%H:%M:%S");
+        write_utf16(
+            &refresh,
+            &task_xml(
+                &format!("Generated by so2lar: re-arm the {} task for the next {}", self.name, self.event.name()),
+                &format!(
+                    "    <CalendarTrigger>\r\n      <StartBoundary>{}</StartBoundary>\r\n      <ScheduleByDay>\r\n        <DaysInterval>1</DaysInterval>\r\n      </ScheduleByDay>\r\n    </CalendarTrigger>\r\n",
+                    midnight
+                ),
+                &self.refresh_command.0,
+                &self.refresh_command.1,
+            ),
+        )?;
+        Ok(vec![task, refresh])
+    }
+
+    /// `schtasks` invocations registering the written files, replacing earlier versions.
+    pub fn commands(&self, paths: &[PathBuf]) -> Vec<Command> {
+        let (task, refresh) = self.task_names();
+        [task, refresh]
+            .into_iter()
+            .zip(paths)
+            .map(|(name, path)| {
+                let mut command = Command::new("schtasks");
+                command.args(["/Create", "/F", "/TN", &name, "/XML"]).arg(path);
+                command
+            })
+            .collect()
+    }
+}
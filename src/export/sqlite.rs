@@ -0,0 +1,73 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::format::Precision;
+
+/// Prefix of `--output` naming a database rather than a file.
+pub const PREFIX: &str = "sqlite:";
+
+/// One solar event at one location.
+#[derive(Clone, Debug)]
+pub struct EventRow {
+    pub lat: f64,
+    pub long: f64,
+    pub date: NaiveDate,
+    pub event: &'static str,
+    pub time: DateTime<Utc>,
+    pub azimuth: f64,
+}
+
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS solar_events (
+    lat REAL NOT NULL,
+    long REAL NOT NULL,
+    date TEXT NOT NULL,
+    event TEXT NOT NULL,
+    time TEXT NOT NULL,
+    azimuth REAL,
+    PRIMARY KEY (lat, long, date, event)
+);";
+
+/// SQL creating the `solar_events` table if needed and inserting `rows` in
+/// one transaction; rows already stored for the same location, date and
+/// event are replaced, so runs over overlapping ranges update the table.
+pub fn write_sql<W: Write>(w: &mut W, rows: &[EventRow]) -> io::Result<()> {
+    writeln!(w, "{}", SCHEMA)?;
+    writeln!(w, "BEGIN;")?;
+    for row in rows {
+        writeln!(
+            w,
+            "INSERT OR REPLACE INTO solar_events VALUES ({}, {}, '{}', '{}', '{}', {:.4});",
+            row.lat,
+            row.long,
+            row.date,
+            row.event,
+            Precision::Seconds.rfc3339(&row.time),
+            row.azimuth
+        )?;
+    }
+    writeln!(w, "COMMIT;")
+}
+
+/// Stores `rows` in the SQLite database at `path`, creating it if needed,
+/// through the `sqlite3` shell.
+pub fn write_database(path: &Path, rows: &[EventRow]) -> Result<(), Box<dyn Error>> {
+    let mut child = Command::new("sqlite3")
+        .args(["-bail", "-batch"])
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("cannot run sqlite3: {} (or write the statements with --format sql)", e))?;
+    let mut stdin = io::BufWriter::new(child.stdin.take().unwrap());
+    write_sql(&mut stdin, rows)?;
+    drop(stdin.into_inner().map_err(|e| e.into_error())?);
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("sqlite3 exited with {}", status).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+
+use crate::paths::xdg_config_home;
+use crate::solar::SolarEvent;
+
+/// `$XDG_CONFIG_HOME/systemd/user`, falling back to `~/.config/systemd/user`.
+pub fn user_unit_dir() -> Option<PathBuf> {
+    Some(xdg_config_home()?.join("systemd").join("user"))
+}
+
+pub struct TimerSpec<'a> {
+    pub event: SolarEvent,
+    pub unit: &'a str,
+    pub next: DateTime<Local>,
+    /// Command line that regenerates these units, run daily by the refresh timer.
+    pub refresh_command: String,
+}
+
+impl TimerSpec<'_> {
+    fn stem(&self) -> String {
+        let unit = self.unit.strip_suffix(".service").unwrap_or(self.unit);
+        format!("so2lar-{}-{}", unit, self.event.name())
+    }
+
+    /// Writes `<stem>.timer` firing `unit` at the next event plus a
+    /// `<stem>-refresh.{service,timer}` pair re-arming it every night.
+    /// Returns the paths of the written files.
+    pub fn write(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        let stem = self.stem();
+        let timer = dir.join(format!("{}.timer", stem));
+        fs::write(
+            &timer,
+            format!(
+                "# Generated by so2lar, rewritten by {stem}-refresh.service\n\
+                 [Unit]\n\
+                 Description=Start {unit} at {event}\n\n\
+                 [Timer]\n\
+                 OnCalendar={next}\n\
+                 AccuracySec=1s\n\
+                 Unit={unit}\n\n\
+                 [Install]\n\
+                 WantedBy=timers.target\n",
+                stem = stem,
+                unit = self.unit,
+                event = self.event.name(),
+                next = self.next.to_utc().format("%Y-%m-%d %H:%M:%S UTC"),
+            ),
+        )?;
+        let service = dir.join(format!("{}-refresh.service", stem));
+        fs::write(
+            &service,
+            format!(
+                "# Generated by so2lar\n\
+                 [Unit]\n\
+                 Description=Re-arm {stem}.timer for the next {event}\n\n\
+                 [Service]\n\
+                 Type=oneshot\n\
+                 ExecStart={command}\n\
+                 ExecStartPost=systemctl --user daemon-reload\n\
+                 ExecStartPost=systemctl --user restart {stem}.timer\n",
+                stem = stem,
+                event = self.event.name(),
+                command = self.refresh_command,
+            ),
+        )?;
+        let refresh = dir.join(format!("{}-refresh.timer", stem));
+        fs::write(
+            &refresh,
+            format!(
+                "# Generated by so2lar\n\
+                 [Unit]\n\
+                 Description=Daily refresh of {stem}.timer\n\n\
+                 [Timer]\n\
+                 OnCalendar=*-*-* 00:05:00\n\
+                 Persistent=true\n\n\
+                 [Install]\n\
+                 WantedBy=timers.target\n",
+                stem = stem,
+            ),
+        )?;
+        Ok(vec![timer, service, refresh])
+    }
+}
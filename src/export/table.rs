@@ -0,0 +1,81 @@
+use std::io::{self, Write};
+
+use chrono::{Datelike, NaiveDate, NaiveTime};
+
+use crate::parallel;
+use crate::solar::{degrees::sunrise_sunset, jdn};
+
+/// Marks days without sunrise or sunset.
+pub const NONE: i16 = i16::MAX;
+
+const MAGIC: &[u8; 4] = b"SO2T";
+const VERSION: u8 = 1;
+
+/// Sunrise and sunset of every day of a year as minutes after 00:00 UTC of
+/// the day, small enough for firmware to embed. Times before midnight or
+/// after the next one are negative or above 1440.
+pub struct YearTable {
+    pub lat: f64,
+    pub long: f64,
+    pub year: i32,
+    pub days: Vec<[i16; 2]>,
+}
+
+impl YearTable {
+    pub fn new(lat: f64, long: f64, year: i32) -> Option<YearTable> {
+        let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+        let dates: Vec<NaiveDate> = first.iter_days().take_while(|d| d.year() == year).collect();
+        let days = parallel::map(&dates, |date| {
+            let midnight = date.and_time(NaiveTime::MIN);
+            match sunrise_sunset(lat, long, jdn(*date)) {
+                Some((rise, set)) => [rise, set].map(|t| (t.naive_utc() - midnight).num_minutes() as i16),
+                None => [NONE; 2],
+            }
+        });
+        Some(YearTable { lat, long, year, days })
+    }
+
+    /// Little-endian binary layout: `SO2T`, version byte, year (i16), day
+    /// count (u16), latitude and longitude in microdegrees (i32), then a
+    /// sunrise and a sunset minute (i16) per day.
+    pub fn write_binary<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(MAGIC)?;
+        w.write_all(&[VERSION])?;
+        w.write_all(&(self.year as i16).to_le_bytes())?;
+        w.write_all(&(self.days.len() as u16).to_le_bytes())?;
+        w.write_all(&((self.lat * 1e6).round() as i32).to_le_bytes())?;
+        w.write_all(&((self.long * 1e6).round() as i32).to_le_bytes())?;
+        for day in &self.days {
+            w.write_all(&day[0].to_le_bytes())?;
+            w.write_all(&day[1].to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn entries(&self, open: &str, close: &str) -> String {
+        let entries: Vec<String> = self.days.iter().map(|d| format!("{}{}, {}{}", open, d[0], d[1], close)).collect();
+        entries.chunks(6).map(|line| format!("    {},", line.join(", "))).collect::<Vec<_>>().join("\n")
+    }
+
+    pub fn write_c<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "/* Generated by so2lar for {:.6}, {:.6} in {}.", self.lat, self.long, self.year)?;
+        writeln!(w, " * Sunrise and sunset in minutes after 00:00 UTC, SO2LAR_NONE during polar day or night. */")?;
+        writeln!(w, "#include <stdint.h>\n")?;
+        writeln!(w, "#define SO2LAR_YEAR {}", self.year)?;
+        writeln!(w, "#define SO2LAR_DAYS {}", self.days.len())?;
+        writeln!(w, "#define SO2LAR_NONE INT16_MAX\n")?;
+        writeln!(w, "static const int16_t so2lar_rise_set[SO2LAR_DAYS][2] = {{")?;
+        writeln!(w, "{}", self.entries("{", "}").replace(&NONE.to_string(), "SO2LAR_NONE"))?;
+        writeln!(w, "}};")
+    }
+
+    pub fn write_rust<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "// Generated by so2lar for {:.6}, {:.6} in {}.", self.lat, self.long, self.year)?;
+        writeln!(w, "// Sunrise and sunset in minutes after 00:00 UTC, NONE during polar day or night.\n")?;
+        writeln!(w, "pub const YEAR: i32 = {};", self.year)?;
+        writeln!(w, "pub const NONE: i16 = i16::MAX;\n")?;
+        writeln!(w, "pub static RISE_SET: [[i16; 2]; {}] = [", self.days.len())?;
+        writeln!(w, "{}", self.entries("[", "]").replace(&NONE.to_string(), "NONE"))?;
+        writeln!(w, "];")
+    }
+}
@@ -0,0 +1,77 @@
+//! GET requests of the network features, made with `curl` and kept on disk
+//! so repeated runs do not ask the services again while an answer is fresh,
+//! and work offline with what was fetched before.
+
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use crate::hmac::{hex, sha256};
+use crate::paths;
+use crate::timing::{self, Stage};
+
+/// Responses by URL in files under a directory, one per URL.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HttpCache {
+    /// Where responses are kept; without one nothing is cached.
+    pub dir: Option<PathBuf>,
+    /// Answer only from the cache, however old, and never go to the network.
+    pub offline: bool,
+}
+
+impl HttpCache {
+    /// The cache under the user's cache directory.
+    pub fn new(offline: bool) -> HttpCache {
+        HttpCache { dir: paths::http_cache_dir(), offline }
+    }
+
+    fn path(&self, url: &str) -> Option<PathBuf> {
+        Some(self.dir.as_ref()?.join(hex(&sha256(url.as_bytes()))))
+    }
+
+    /// The body at `url`, from the cache if it was fetched less than `ttl`
+    /// ago. When the request fails an older copy is used instead.
+    pub fn get(&self, url: &str, ttl: Duration) -> Result<String, Box<dyn Error>> {
+        timing::measure(Stage::Network, || self.lookup(url, ttl))
+    }
+
+    fn lookup(&self, url: &str, ttl: Duration) -> Result<String, Box<dyn Error>> {
+        let path = self.path(url);
+        let cached = path.as_ref().and_then(|p| Some((fs::read_to_string(p).ok()?, fs::metadata(p).ok()?.modified().ok()?)));
+        let age = |modified: SystemTime| SystemTime::now().duration_since(modified).unwrap_or_default();
+        match cached {
+            Some((body, modified)) if self.offline || age(modified) < ttl => {
+                info!("Cached response for {}", url);
+                timing::request(true);
+                return Ok(body);
+            }
+            None if self.offline => return Err(format!("offline and {} is not cached", url).into()),
+            _ => {}
+        }
+        timing::request(false);
+        let body = match fetch(url) {
+            Ok(body) => body,
+            Err(e) => {
+                let (body, _) = cached.ok_or(e)?;
+                warn!("Using a stale cached response for {}", url);
+                return Ok(body);
+            }
+        };
+        if let Some(path) = &path {
+            if let Err(e) = path.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::write(path, &body)) {
+                warn!("Cannot cache {}: {}", url, e);
+            }
+        }
+        Ok(body)
+    }
+}
+
+fn fetch(url: &str) -> Result<String, Box<dyn Error>> {
+    let output = Command::new("curl").args(["-fsS", "--max-time", "10"]).arg(url).output().map_err(|e| format!("cannot run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string().into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
@@ -0,0 +1,134 @@
+//! Reverse searches for the dates on which a daily quantity meets a condition.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{NaiveDate, NaiveTime, TimeDelta};
+
+use crate::duration::format_hm;
+
+/// How far ahead searches look, enough to cover a full cycle of the seasons.
+pub const SEARCH_DAYS: u32 = 731;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Comparison {
+    pub fn holds<T: PartialOrd>(&self, value: T, threshold: T) -> bool {
+        match self {
+            Comparison::Less => value < threshold,
+            Comparison::LessEqual => value <= threshold,
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterEqual => value >= threshold,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterEqual => ">=",
+        }
+    }
+
+    /// Splits a leading operator off `s`, defaulting to `>=` without one.
+    fn split(s: &str) -> (Comparison, &str) {
+        let s = s.trim();
+        for (prefix, comparison) in [
+            ("<=", Comparison::LessEqual),
+            (">=", Comparison::GreaterEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ] {
+            if let Some(rest) = s.strip_prefix(prefix) {
+                return (comparison, rest.trim_start());
+            }
+        }
+        (Comparison::GreaterEqual, s)
+    }
+}
+
+/// A day length condition such as `>=16h` or `<8h 30m`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DayLengthCondition {
+    pub comparison: Comparison,
+    pub length: TimeDelta,
+}
+
+impl DayLengthCondition {
+    pub fn holds(&self, length: TimeDelta) -> bool {
+        self.comparison.holds(length, self.length)
+    }
+}
+
+impl FromStr for DayLengthCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DayLengthCondition, String> {
+        let (comparison, rest) = Comparison::split(s);
+        let length = humantime::parse_duration(rest).map_err(|e| format!("invalid day length `{}`: {}", s, e))?;
+        Ok(DayLengthCondition { comparison, length: TimeDelta::from_std(length).map_err(|e| e.to_string())? })
+    }
+}
+
+impl fmt::Display for DayLengthCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.comparison.symbol(), format_hm(self.length))
+    }
+}
+
+/// A condition on the clock time of sunrise, `before` or `after` it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClockCondition {
+    pub comparison: Comparison,
+    pub time: NaiveTime,
+}
+
+impl ClockCondition {
+    pub fn before(time: &str) -> Result<ClockCondition, String> {
+        Ok(ClockCondition { comparison: Comparison::Less, time: parse_clock(time)? })
+    }
+
+    pub fn after(time: &str) -> Result<ClockCondition, String> {
+        Ok(ClockCondition { comparison: Comparison::Greater, time: parse_clock(time)? })
+    }
+
+    pub fn holds(&self, time: NaiveTime) -> bool {
+        self.comparison.holds(time, self.time)
+    }
+}
+
+impl fmt::Display for ClockCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let word = if self.comparison == Comparison::Less { "before" } else { "after" };
+        write!(f, "{} {}", word, self.time.format("%H:%M"))
+    }
+}
+
+fn parse_clock(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+        .map_err(|_| format!("invalid clock time `{}` (expected HH:MM)", s))
+}
+
+/// First run of consecutive days on which `holds` is true, searching
+/// [`SEARCH_DAYS`] days from `from`: its first and last day, the latter
+/// `None` if the run lasts beyond the search.
+pub fn find_run(from: NaiveDate, holds: impl Fn(NaiveDate) -> bool) -> Option<(NaiveDate, Option<NaiveDate>)> {
+    let mut days = from.iter_days().take(SEARCH_DAYS as usize);
+    let first = days.find(|d| holds(*d))?;
+    let mut last = first;
+    for day in days {
+        if !holds(day) {
+            return Some((first, Some(last)));
+        }
+        last = day;
+    }
+    Some((first, None))
+}
@@ -0,0 +1,424 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use std::fmt::Display;
+
+use chrono::{DateTime, DurationRound, FixedOffset, Local, NaiveDateTime, Offset, SecondsFormat, TimeDelta, TimeZone, Utc};
+
+use crate::bearing::BearingFormat;
+use crate::color::{self, Tone};
+use crate::duration::DurationStyle;
+
+/// Time zone in which times are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DisplayZone {
+    #[default]
+    Local,
+    Utc,
+}
+
+impl DisplayZone {
+    pub fn offset_at_utc(&self, t: &NaiveDateTime) -> FixedOffset {
+        match self {
+            DisplayZone::Local => Local.offset_from_utc_datetime(t).fix(),
+            DisplayZone::Utc => Utc.fix(),
+        }
+    }
+
+    pub fn convert<Tz: TimeZone>(&self, t: &DateTime<Tz>) -> DateTime<FixedOffset> {
+        t.with_timezone(&self.offset_at_utc(&t.naive_utc()))
+    }
+}
+
+/// How library values render through their `format_with` methods; the
+/// defaults are also what their `Display` implementations print.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    pub zone: DisplayZone,
+    pub precision: Precision,
+    pub bearings: BearingFormat,
+    pub durations: DurationStyle,
+}
+
+/// Resolution to which displayed times are rounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    Minutes,
+    #[default]
+    Seconds,
+    Millis,
+}
+
+impl Precision {
+    pub fn round<Tz: TimeZone>(&self, t: &DateTime<Tz>) -> DateTime<Tz> {
+        let step = match self {
+            Precision::Minutes => TimeDelta::minutes(1),
+            Precision::Seconds => TimeDelta::seconds(1),
+            Precision::Millis => TimeDelta::milliseconds(1),
+        };
+        t.clone().duration_round(step).unwrap_or_else(|_| t.clone())
+    }
+
+    /// `strftime` pattern of the time of day.
+    pub fn time_pattern(&self) -> &'static str {
+        match self {
+            Precision::Minutes => "%H:%M",
+            Precision::Seconds => "%H:%M:%S",
+            Precision::Millis => "%H:%M:%S%.3f",
+        }
+    }
+
+    /// Time of day of `t` after rounding, e.g. `05:42:17`.
+    pub fn time<Tz: TimeZone>(&self, t: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        self.round(t).format(self.time_pattern()).to_string()
+    }
+
+    /// RFC 3339 timestamp of `t` after rounding; minutes keep `:00` seconds.
+    pub fn rfc3339<Tz: TimeZone>(&self, t: &DateTime<Tz>) -> String
+    where
+        Tz::Offset: Display,
+    {
+        let seconds = if *self == Precision::Millis { SecondsFormat::Millis } else { SecondsFormat::Secs };
+        self.round(t).to_rfc3339_opts(seconds, false)
+    }
+}
+
+impl FromStr for Precision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Precision, String> {
+        match s.to_lowercase().as_str() {
+            "minutes" | "min" => Ok(Precision::Minutes),
+            "seconds" | "s" => Ok(Precision::Seconds),
+            "millis" | "ms" => Ok(Precision::Millis),
+            _ => Err(format!("unknown precision `{}` (expected minutes, seconds or millis)", s)),
+        }
+    }
+}
+
+/// Version of the JSON output, given as `schema_version` in its objects.
+///
+/// Within a version, fields keep their names, types and meaning. A new version
+/// only adds fields or event names, which are then listed in [`ADDED_FIELDS`];
+/// `--format json-vN` leaves out everything added after version N, so scripts
+/// pinned to it see the same fields in every later release.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// JSON keys and values of `event` columns, with the schema version that
+/// added them, for pinned output.
+pub const ADDED_FIELDS: &[(&str, u32)] = &[("solar_noon", 2), ("noon_altitude", 2), ("culmination", 2), ("moon_elevation", 2), ("moon_azimuth", 2), ("sunrise_cloud_cover", 2), ("sunset_cloud_cover", 2)];
+
+/// Whether `key` was part of schema `version`.
+fn in_schema(key: &str, version: u32) -> bool {
+    ADDED_FIELDS.iter().find(|(k, _)| *k == key).is_none_or(|(_, since)| *since <= version)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Csv,
+    /// JSON with the fields of the given schema version.
+    Json(u32),
+    Markdown,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s.to_lowercase().as_str() {
+            "table" | "text" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json(SCHEMA_VERSION)),
+            "markdown" | "md" => Ok(Format::Markdown),
+            json if json.starts_with("json-v") => match json["json-v".len()..].parse() {
+                Ok(version @ 1..=SCHEMA_VERSION) => Ok(Format::Json(version)),
+                _ => Err(format!("unknown JSON schema version in `{}` (expected 1 to {})", s, SCHEMA_VERSION)),
+            },
+            _ => Err(format!("unknown format `{}` (expected table, csv, json, json-vN or markdown)", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+impl Value {
+    fn plain(&self) -> String {
+        match self {
+            Value::Text(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Null => "-".to_string(),
+        }
+    }
+
+    pub fn json(&self) -> String {
+        match self {
+            Value::Text(s) => json_string(s),
+            Value::Number(n) if n.is_finite() => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Number(_) | Value::Null => "null".to_string(),
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::Text(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Value {
+        Value::Number(n)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Value {
+        Value::Number(n as f64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(v: Option<T>) -> Value {
+        v.map_or(Value::Null, Into::into)
+    }
+}
+
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A JSON object with keys in insertion order.
+pub fn json_object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields.iter().map(|(k, v)| format!("{}:{}", json_string(k), v)).collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub struct Table {
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<Value>>,
+    /// Colours of the rows in terminal tables, by index.
+    pub tones: Vec<Option<Tone>>,
+}
+
+impl Table {
+    pub fn new(columns: Vec<&'static str>) -> Table {
+        Table { columns, rows: Vec::new(), tones: Vec::new() }
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        self.rows.push(row);
+        self.tones.push(None);
+    }
+
+    /// Adds a row shown in the colour of `tone` on terminals.
+    pub fn push_toned(&mut self, row: Vec<Value>, tone: Tone) {
+        self.rows.push(row);
+        self.tones.push(Some(tone));
+    }
+
+    /// The rows as a JSON array of objects keyed by column name.
+    pub fn json(&self) -> String {
+        self.json_schema(SCHEMA_VERSION)
+    }
+
+    /// The rows as [`json`](Table::json) with the columns and events of
+    /// schema `version`.
+    pub fn json_schema(&self, version: u32) -> String {
+        let event = self.columns.iter().position(|c| *c == "event");
+        let rows: Vec<String> = self
+            .rows
+            .iter()
+            .filter(|row| !matches!(event.map(|i| &row[i]), Some(Value::Text(e)) if !in_schema(e, version)))
+            .map(|row| {
+                let fields: Vec<(&str, String)> =
+                    self.columns.iter().zip(row).filter(|(c, _)| in_schema(c, version)).map(|(c, v)| (*c, v.json())).collect();
+                json_object(&fields)
+            })
+            .collect();
+        format!("[{}]", rows.join(","))
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W, format: Format) -> io::Result<()> {
+        format.table(w, self)
+    }
+}
+
+/// Summary value of a report with its label and human-readable rendering.
+pub struct Field {
+    pub label: &'static str,
+    pub key: &'static str,
+    pub value: Value,
+    pub text: String,
+}
+
+/// Renders command output. [`Format`] implements the built-in formats;
+/// other encodings such as YAML implement this trait.
+pub trait OutputFormatter {
+    /// Rows with named columns.
+    fn table(&self, w: &mut dyn Write, table: &Table) -> io::Result<()>;
+
+    /// A single record of named fields; `text` is how it reads for people.
+    fn record(&self, w: &mut dyn Write, fields: &[(&str, Value)], text: &str) -> io::Result<()>;
+
+    /// A titled table followed by summary fields. `meta` describes the
+    /// report for machine-readable formats.
+    fn report(&self, w: &mut dyn Write, title: &str, meta: &[(&str, Value)], table: &Table, summary: &[Field]) -> io::Result<()> {
+        let _ = meta;
+        plain_report(self, w, title, table, summary)
+    }
+}
+
+fn plain_report<F: OutputFormatter + ?Sized>(f: &F, w: &mut dyn Write, title: &str, table: &Table, summary: &[Field]) -> io::Result<()> {
+    writeln!(w, "{}", title)?;
+    writeln!(w)?;
+    f.table(w, table)?;
+    writeln!(w)?;
+    for field in summary {
+        writeln!(w, "{:<22} {}", format!("{}:", field.label), field.text)?;
+    }
+    Ok(())
+}
+
+impl OutputFormatter for Format {
+    fn table(&self, w: &mut dyn Write, table: &Table) -> io::Result<()> {
+        let cells: Vec<Vec<String>> = table.rows.iter().map(|row| row.iter().map(Value::plain).collect()).collect();
+        match self {
+            Format::Table => {
+                let widths: Vec<usize> = (0..table.columns.len())
+                    .map(|i| cells.iter().map(|r| r[i].chars().count()).chain([table.columns[i].len()]).max().unwrap())
+                    .collect();
+                let line = |row: &[String]| {
+                    let padded: Vec<String> = row.iter().zip(&widths).map(|(c, width)| format!("{:<width$}", c, width = width)).collect();
+                    padded.join("  ").trim_end().to_string()
+                };
+                writeln!(w, "{}", color::header(&line(&table.columns.iter().map(|c| c.to_string()).collect::<Vec<_>>())))?;
+                for (i, row) in cells.iter().enumerate() {
+                    match table.tones.get(i).copied().flatten() {
+                        Some(tone) => writeln!(w, "{}", color::paint(tone, &line(row)))?,
+                        None => writeln!(w, "{}", line(row))?,
+                    }
+                }
+                Ok(())
+            }
+            Format::Markdown => {
+                writeln!(w, "| {} |", table.columns.join(" | "))?;
+                writeln!(w, "|{}", "---|".repeat(table.columns.len()))?;
+                for row in &cells {
+                    writeln!(w, "| {} |", row.join(" | "))?;
+                }
+                Ok(())
+            }
+            Format::Csv => {
+                writeln!(w, "{}", table.columns.join(","))?;
+                for row in &table.rows {
+                    writeln!(w, "{}", csv_row(row))?;
+                }
+                Ok(())
+            }
+            Format::Json(version) => writeln!(w, "{}", table.json_schema(*version)),
+        }
+    }
+
+    fn record(&self, w: &mut dyn Write, fields: &[(&str, Value)], text: &str) -> io::Result<()> {
+        match self {
+            Format::Table | Format::Markdown => writeln!(w, "{}", text.trim_end()),
+            Format::Csv => {
+                let keys: Vec<&str> = fields.iter().map(|(k, _)| *k).collect();
+                writeln!(w, "{}", keys.join(","))?;
+                let values: Vec<Value> = fields.iter().map(|(_, v)| v.clone()).collect();
+                writeln!(w, "{}", csv_row(&values))
+            }
+            Format::Json(version) => {
+                let mut json = vec![("schema_version", version.to_string())];
+                json.extend(fields.iter().filter(|(k, _)| in_schema(k, *version)).map(|(k, v)| (*k, v.json())));
+                writeln!(w, "{}", json_object(&json))
+            }
+        }
+    }
+
+    fn report(&self, w: &mut dyn Write, title: &str, meta: &[(&str, Value)], table: &Table, summary: &[Field]) -> io::Result<()> {
+        match self {
+            Format::Table => plain_report(self, w, title, table, summary),
+            Format::Markdown => {
+                writeln!(w, "## {}", title)?;
+                writeln!(w)?;
+                self.table(w, table)?;
+                writeln!(w)?;
+                for field in summary {
+                    writeln!(w, "- **{}:** {}", field.label, field.text)?;
+                }
+                Ok(())
+            }
+            // Only the rows, so that the output stays a single rectangular table
+            Format::Csv => self.table(w, table),
+            Format::Json(version) => {
+                let summary: Vec<(&str, String)> = summary.iter().filter(|f| in_schema(f.key, *version)).map(|f| (f.key, f.value.json())).collect();
+                let mut fields = vec![("schema_version", version.to_string())];
+                fields.extend(meta.iter().filter(|(k, _)| in_schema(k, *version)).map(|(k, v)| (*k, v.json())));
+                fields.push(("days", table.json_schema(*version)));
+                fields.push(("summary", json_object(&summary)));
+                writeln!(w, "{}", json_object(&fields))
+            }
+        }
+    }
+}
+
+pub(crate) fn csv_row(row: &[Value]) -> String {
+    let fields: Vec<String> = row
+        .iter()
+        .map(|v| match v {
+            Value::Null => String::new(),
+            v => csv_field(&v.plain()),
+        })
+        .collect();
+    fields.join(",")
+}
@@ -0,0 +1,103 @@
+//! Low sun ahead: when the sun stands close to the direction of travel.
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::constants::STANDARD_ALTITUDE;
+use crate::solar::{datetime2julian, sun_position};
+
+/// Latitude, longitude and time of a point of a route.
+pub type RoutePoint = (f64, f64, DateTime<Utc>);
+
+/// Direction of travel and sun position at one moment.
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub time: DateTime<Utc>,
+    pub heading: f64,
+    pub elevation: f64,
+    pub azimuth: f64,
+}
+
+/// A stretch of glare from the first dazzling sample to the next one that is not.
+#[derive(Clone, Copy, Debug)]
+pub struct Period {
+    pub start: Sample,
+    pub end: DateTime<Utc>,
+}
+
+/// Sun positions that dazzle: above the horizon, below `max_elevation` and
+/// within `half_angle` degrees of the heading.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlareCone {
+    pub half_angle: f64,
+    pub max_elevation: f64,
+}
+
+impl Default for GlareCone {
+    fn default() -> GlareCone {
+        // Roughly what a sun visor leaves uncovered
+        GlareCone { half_angle: 20.0, max_elevation: 25.0 }
+    }
+}
+
+impl GlareCone {
+    pub fn dazzles(&self, s: &Sample) -> bool {
+        let off = ((s.azimuth - s.heading).rem_euclid(360.0) + 180.0).rem_euclid(360.0) - 180.0;
+        s.elevation > STANDARD_ALTITUDE && s.elevation < self.max_elevation && off.abs() <= self.half_angle
+    }
+
+    pub fn periods(&self, samples: &[Sample]) -> Vec<Period> {
+        let mut periods = Vec::new();
+        let mut start: Option<Sample> = None;
+        for s in samples {
+            match (self.dazzles(s), start) {
+                (true, None) => start = Some(*s),
+                (false, Some(first)) => {
+                    periods.push(Period { start: first, end: s.time });
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let (Some(first), Some(last)) = (start, samples.last()) {
+            periods.push(Period { start: first, end: last.time });
+        }
+        periods
+    }
+}
+
+fn sample(lat: f64, long: f64, heading: f64, time: DateTime<Utc>) -> Sample {
+    let (elevation, azimuth) = sun_position(lat, long, datetime2julian(&time));
+    Sample { time, heading, elevation, azimuth }
+}
+
+/// Minute-by-minute samples travelling on a fixed `heading` from `from` to `to`.
+pub fn fixed_heading(lat: f64, long: f64, heading: f64, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<Sample> {
+    let minutes = (to - from).num_minutes().max(0);
+    (0..=minutes).map(|m| sample(lat, long, heading, from + TimeDelta::minutes(m))).collect()
+}
+
+/// Initial great-circle bearing in degrees from the first point to the second.
+pub fn initial_bearing(lat1: f64, long1: f64, lat2: f64, long2: f64) -> f64 {
+    let (p1, p2, dl) = (lat1.to_radians(), lat2.to_radians(), (long2 - long1).to_radians());
+    let y = dl.sin() * p2.cos();
+    let x = p1.cos() * p2.sin() - p1.sin() * p2.cos() * dl.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Samples at the timestamped points of a route, heading towards the next
+/// point; points that do not move keep the previous heading.
+pub fn route(points: &[RoutePoint]) -> Vec<Sample> {
+    let mut heading = 0.0;
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(lat, long, time))| {
+            if let Some(&(next_lat, next_long, _)) = points.get(i + 1) {
+                if (next_lat, next_long) != (lat, long) {
+                    heading = initial_bearing(lat, long, next_lat, next_long);
+                }
+            }
+            sample(lat, long, heading, time)
+        })
+        .collect()
+}
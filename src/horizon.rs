@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::constants::STANDARD_ALTITUDE;
+use crate::solar::sun_position;
+
+/// Terrain elevation of the horizon as a function of azimuth, read from
+/// `azimuth,elevation` lines in degrees. Values between samples are linearly
+/// interpolated, wrapping around at north. Obstacles stand in front of the
+/// terrain where they are higher.
+pub struct HorizonProfile {
+    points: Vec<(f64, f64)>,
+    obstacles: Vec<Obstacle>,
+}
+
+/// Something near the observer that blocks the sun, such as a building at
+/// 220° standing 15° high and 30° wide: `220,15,30`, or named
+/// `building:220,15,30`. Angles are in degrees, the bearing that of its middle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Obstacle {
+    pub name: Option<String>,
+    pub bearing: f64,
+    pub height: f64,
+    pub width: f64,
+}
+
+impl Obstacle {
+    fn covers(&self, azimuth: f64) -> bool {
+        ((azimuth - self.bearing + 180.0).rem_euclid(360.0) - 180.0).abs() <= self.width / 2.0
+    }
+}
+
+impl FromStr for Obstacle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Obstacle, String> {
+        let (name, rest) = match s.split_once(':') {
+            Some((name, rest)) => (Some(name.trim().to_string()), rest),
+            None => (None, s),
+        };
+        let fields: Vec<f64> = rest.split(',').map(|f| f.trim().trim_end_matches('°').parse()).collect::<Result<_, _>>().map_err(|_| format!("invalid obstacle `{}`", s))?;
+        let [bearing, height, width] = fields[..] else {
+            return Err(format!("invalid obstacle `{}` (expected BEARING,HEIGHT,WIDTH in degrees)", s));
+        };
+        if !(0.0..=90.0).contains(&height) || !(0.0..=360.0).contains(&width) {
+            return Err(format!("invalid obstacle `{}`: height must be 0 to 90° and width 0 to 360°", s));
+        }
+        Ok(Obstacle { name, bearing: bearing.rem_euclid(360.0), height, width })
+    }
+}
+
+impl HorizonProfile {
+    pub fn from_csv(path: &Path) -> Result<HorizonProfile, Box<dyn Error>> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(s: &str) -> Result<HorizonProfile, Box<dyn Error>> {
+        let mut points = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split([',', ';']).map(str::trim).collect();
+            let (Some(az), Some(el)) = (fields.first(), fields.get(1)) else {
+                return Err(format!("line {}: expected `azimuth,elevation`", i + 1).into());
+            };
+            // A non-numeric first row is a CSV header
+            let Ok(az) = az.parse::<f64>() else {
+                if points.is_empty() {
+                    continue;
+                }
+                return Err(format!("line {}: invalid azimuth `{}`", i + 1, az).into());
+            };
+            let el: f64 = el.parse().map_err(|_| format!("line {}: invalid elevation `{}`", i + 1, el))?;
+            points.push((az.rem_euclid(360.0), el));
+        }
+        if points.is_empty() {
+            return Err("horizon profile contains no points".into());
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(HorizonProfile { points, obstacles: Vec::new() })
+    }
+
+    /// Level terrain at 0°, for obstacles alone.
+    pub fn flat() -> HorizonProfile {
+        HorizonProfile { points: vec![(0.0, 0.0)], obstacles: Vec::new() }
+    }
+
+    pub fn with_obstacles(mut self, obstacles: &[Obstacle]) -> HorizonProfile {
+        self.obstacles.extend_from_slice(obstacles);
+        self
+    }
+
+    /// Height of the horizon at `azimuth`: the terrain or the highest
+    /// obstacle in front of it.
+    pub fn elevation_at(&self, azimuth: f64) -> f64 {
+        let terrain = self.terrain_at(azimuth);
+        self.obstacles.iter().filter(|o| o.covers(azimuth)).map(|o| o.height).fold(terrain, f64::max)
+    }
+
+    fn terrain_at(&self, azimuth: f64) -> f64 {
+        let azimuth = azimuth.rem_euclid(360.0);
+        let next = self.points.iter().position(|p| p.0 >= azimuth);
+        let (a, b) = match next {
+            Some(0) | None => (self.points[self.points.len() - 1], self.points[0]),
+            Some(i) => (self.points[i - 1], self.points[i]),
+        };
+        let span = (b.0 - a.0).rem_euclid(360.0);
+        if span == 0.0 {
+            return a.1;
+        }
+        let t = (azimuth - a.0).rem_euclid(360.0) / span;
+        a.1 + t * (b.1 - a.1)
+    }
+
+    fn is_visible(&self, lat: f64, long: f64, j: f64) -> bool {
+        let (elevation, azimuth) = sun_position(lat, long, j);
+        elevation > self.elevation_at(azimuth) + STANDARD_ALTITUDE
+    }
+
+    /// First instant in `[from, to]` (true Julian dates) at which the sun
+    /// clears the terrain.
+    pub fn visible_rise(&self, lat: f64, long: f64, from: f64, to: f64) -> Option<f64> {
+        self.find_crossing(lat, long, from, to, true)
+    }
+
+    /// Last instant in `[from, to]` at which the sun drops behind the terrain.
+    pub fn visible_set(&self, lat: f64, long: f64, from: f64, to: f64) -> Option<f64> {
+        self.find_crossing(lat, long, from, to, false)
+    }
+
+    /// The spans of `[from, to]` in which the sun is in view, in time order.
+    /// Gaps between them are times it is behind an obstacle or a ridge.
+    pub fn visible_spans(&self, lat: f64, long: f64, from: f64, to: f64) -> Vec<(f64, f64)> {
+        const STEP: f64 = 1.0 / 1440.0;
+        let steps = ((to - from) / STEP).ceil() as usize;
+        let mut spans = Vec::new();
+        let mut start = self.is_visible(lat, long, from).then_some(from);
+        let mut prev = (from, start.is_some());
+        for i in 1..=steps {
+            let j = (from + i as f64 * STEP).min(to);
+            let cur = self.is_visible(lat, long, j);
+            if cur != prev.1 {
+                let edge = self.bisect(lat, long, prev.0, j, cur);
+                match start.take() {
+                    Some(start) => spans.push((start, edge)),
+                    None => start = Some(edge),
+                }
+            }
+            prev = (j, cur);
+        }
+        spans.extend(start.map(|start| (start, to)));
+        spans
+    }
+
+    /// Instant between `lo` and `hi` at which visibility changes to `to`.
+    fn bisect(&self, lat: f64, long: f64, mut lo: f64, mut hi: f64, to: bool) -> f64 {
+        while hi - lo > 0.1 / 86400.0 {
+            let mid = (lo + hi) / 2.0;
+            if self.is_visible(lat, long, mid) == to {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        hi
+    }
+
+    fn find_crossing(&self, lat: f64, long: f64, from: f64, to: f64, rising: bool) -> Option<f64> {
+        const STEP: f64 = 1.0 / 1440.0;
+        let steps = ((to - from) / STEP).ceil() as usize;
+        let mut found = None;
+        let mut prev = self.is_visible(lat, long, from);
+        for i in 1..=steps {
+            let j = (from + i as f64 * STEP).min(to);
+            let cur = self.is_visible(lat, long, j);
+            if cur == rising && prev != rising {
+                found = Some((j - STEP, j));
+                if rising {
+                    break;
+                }
+            }
+            prev = cur;
+        }
+        let (lo, hi) = found?;
+        Some(self.bisect(lat, long, lo, hi, rising))
+    }
+}
@@ -0,0 +1,312 @@
+//! A small HTTP/1.1 server answering the JSON-RPC methods as `GET /v1/METHOD`
+//! with query parameters, and describing them at `/openapi.json`. Each
+//! connection carries one request. `/healthz` and `/readyz` serve liveness
+//! and readiness probes; on SIGTERM the server stops accepting connections,
+//! answers the ones it has and exits.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::format::{json_object, json_string};
+use crate::json::Json;
+use crate::rpc::{RpcError, RpcServer, INVALID_PARAMS, METHOD_NOT_FOUND};
+use crate::signal;
+
+/// Version in the paths of the API; changes to it only add fields.
+pub const API_VERSION: &str = "v1";
+
+/// How often the accept loop checks for a termination signal.
+const POLL: Duration = Duration::from_millis(100);
+
+/// A query parameter: name, JSON type, format and description.
+type Param = (&'static str, &'static str, Option<&'static str>, &'static str);
+
+/// A response field: name, JSON type, format and presence.
+type Field = (&'static str, &'static str, Option<&'static str>, Presence);
+
+#[derive(Clone, Copy, PartialEq)]
+enum Presence {
+    Always,
+    Nullable,
+    /// Left out when there is no value.
+    Optional,
+}
+
+struct Endpoint {
+    method: &'static str,
+    summary: &'static str,
+    params: &'static [Param],
+    fields: &'static [Field],
+}
+
+const LAT: Param = ("lat", "number", Some("double"), "Latitude in degrees, negative south of the equator (default: the server's)");
+const LONG: Param = ("long", "number", Some("double"), "Longitude in degrees, negative west of Greenwich (default: the server's)");
+const TIME: Param = ("time", "string", Some("date-time"), "RFC 3339 instant (default: now)");
+const DATE: Param = ("date", "string", Some("date"), "Day as YYYY-MM-DD (default: the day of time)");
+const TIME_FIELD: Field = ("time", "string", Some("date-time"), Presence::Always);
+
+const ENDPOINTS: [Endpoint; 4] = [
+    Endpoint {
+        method: "events",
+        summary: "Sunrise, sunset and day length",
+        params: &[LAT, LONG, DATE, TIME],
+        fields: &[
+            ("date", "string", Some("date"), Presence::Always),
+            ("sunrise", "string", Some("date-time"), Presence::Nullable),
+            ("sunset", "string", Some("date-time"), Presence::Nullable),
+            ("day_length_seconds", "integer", Some("int64"), Presence::Always),
+        ],
+    },
+    Endpoint {
+        method: "position",
+        summary: "Elevation and azimuth of the sun and the moon in degrees",
+        params: &[LAT, LONG, TIME],
+        fields: &[
+            TIME_FIELD,
+            ("elevation", "number", Some("double"), Presence::Always),
+            ("azimuth", "number", Some("double"), Presence::Always),
+            ("moon_elevation", "number", Some("double"), Presence::Always),
+            ("moon_azimuth", "number", Some("double"), Presence::Always),
+        ],
+    },
+    Endpoint {
+        method: "phase",
+        summary: "Phase of the day and when it changes next",
+        params: &[LAT, LONG, TIME],
+        fields: &[
+            TIME_FIELD,
+            ("phase", "string", None, Presence::Always),
+            ("next_phase", "string", None, Presence::Optional),
+            ("next_change", "string", Some("date-time"), Presence::Optional),
+        ],
+    },
+    Endpoint {
+        method: "subsolar",
+        summary: "Where the sun is in the zenith",
+        params: &[TIME],
+        fields: &[TIME_FIELD, ("latitude", "number", Some("double"), Presence::Always), ("longitude", "number", Some("double"), Presence::Always)],
+    },
+];
+
+/// Serves the methods of `rpc` over HTTP.
+pub struct HttpServer {
+    pub rpc: RpcServer,
+}
+
+impl HttpServer {
+    /// Answers connections on `listener`, each on its own thread, until
+    /// SIGINT or SIGTERM, then returns once the open connections are answered.
+    pub fn serve(&self, listener: TcpListener) -> io::Result<()> {
+        signal::catch_termination();
+        listener.set_nonblocking(true)?;
+        info!("Listening on http://{}", listener.local_addr()?);
+        thread::scope(|s| {
+            while !signal::terminated() {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        s.spawn(move || {
+                            if let Err(e) = self.connection(stream) {
+                                warn!("HTTP connection failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL),
+                    Err(e) => warn!("HTTP accept failed: {}", e),
+                }
+            }
+            // Closing the listener refuses new connections while the open ones finish
+            drop(listener);
+            info!("Shutting down after the open connections");
+        });
+        Ok(())
+    }
+
+    fn connection(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Headers are not needed, and no method takes a body
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && !header.trim().is_empty() {
+            header.clear();
+        }
+        let (status, body) = self.respond(&request_line);
+        info!("HTTP {} {}", request_line.trim(), status);
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        (&stream).write_all(response.as_bytes())
+    }
+
+    /// Status line and JSON body answering a request line like
+    /// `GET /v1/position?lat=48.4&long=9.9 HTTP/1.1`.
+    pub fn respond(&self, request_line: &str) -> (&'static str, String) {
+        let mut parts = request_line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return error("400 Bad Request", "malformed request line");
+        };
+        if method != "GET" {
+            return error("405 Method Not Allowed", &format!("{} is not supported, only GET", method));
+        }
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        match path {
+            "/openapi.json" => return ("200 OK", openapi()),
+            "/healthz" => return ("200 OK", status("ok")),
+            "/readyz" if signal::terminated() => return ("503 Service Unavailable", status("shutting down")),
+            "/readyz" => return ("200 OK", status("ready")),
+            _ => {}
+        }
+        let Some(endpoint) = path
+            .strip_prefix('/')
+            .and_then(|p| p.strip_prefix(API_VERSION))
+            .and_then(|p| p.strip_prefix('/'))
+            .and_then(|name| ENDPOINTS.iter().find(|e| e.method == name))
+        else {
+            return error("404 Not Found", &format!("no endpoint {} (see /openapi.json)", path));
+        };
+        let params = match query_params(endpoint, query) {
+            Ok(params) => params,
+            Err(e) => return error("400 Bad Request", &e),
+        };
+        match self.rpc.call(endpoint.method, &params) {
+            Ok(result) => ("200 OK", result),
+            Err(RpcError(INVALID_PARAMS, message)) => error("400 Bad Request", &message),
+            Err(RpcError(METHOD_NOT_FOUND, message)) => error("404 Not Found", &message),
+            Err(RpcError(_, message)) => error("500 Internal Server Error", &message),
+        }
+    }
+}
+
+fn status(status: &str) -> String {
+    json_object(&[("status", json_string(status))])
+}
+
+fn error(status: &'static str, message: &str) -> (&'static str, String) {
+    (status, json_object(&[("error", json_string(message))]))
+}
+
+/// The query string as the JSON parameters of the method, typed as the
+/// endpoint declares them.
+fn query_params(endpoint: &Endpoint, query: &str) -> Result<Json, String> {
+    let mut params = Vec::new();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let (key, value) = (percent_decode(key)?, percent_decode(value)?);
+        let Some(&(_, kind, _, _)) = endpoint.params.iter().find(|(name, ..)| *name == key) else {
+            return Err(format!("unknown parameter `{}` for {}", key, endpoint.method));
+        };
+        let value = match kind {
+            "number" => Json::Number(value.parse().map_err(|_| format!("{} must be a number, not `{}`", key, value))?),
+            _ => Json::String(value),
+        };
+        params.push((key, value));
+    }
+    Ok(Json::Object(params))
+}
+
+/// Decodes `%XX` escapes. A `+` stays a plus sign, as in time zone offsets.
+fn percent_decode(s: &str) -> Result<String, String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = tail.get(..2).and_then(|h| std::str::from_utf8(h).ok()).and_then(|h| u8::from_str_radix(h, 16).ok());
+            bytes.push(hex.ok_or_else(|| format!("invalid escape in `{}`", s))?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| format!("`{}` is not UTF-8", s))
+}
+
+fn schema(kind: &str, format: Option<&str>, nullable: bool) -> String {
+    let mut fields = vec![("type", json_string(kind))];
+    if let Some(format) = format {
+        fields.push(("format", json_string(format)));
+    }
+    if nullable {
+        fields.push(("nullable", "true".to_string()));
+    }
+    json_object(&fields)
+}
+
+fn json_response(description: &str, schema: String) -> String {
+    json_object(&[("description", json_string(description)), ("content", json_object(&[("application/json", json_object(&[("schema", schema)]))]))])
+}
+
+/// OpenAPI 3.0 description of the endpoints.
+pub fn openapi() -> String {
+    let error_ref = || json_object(&[("$ref", json_string("#/components/schemas/Error"))]);
+    let mut paths: Vec<(String, String)> = ENDPOINTS
+        .iter()
+        .map(|e| {
+            let params: Vec<String> = e
+                .params
+                .iter()
+                .map(|&(name, kind, format, description)| {
+                    json_object(&[
+                        ("name", json_string(name)),
+                        ("in", json_string("query")),
+                        ("required", "false".to_string()),
+                        ("description", json_string(description)),
+                        ("schema", schema(kind, format, false)),
+                    ])
+                })
+                .collect();
+            let properties: Vec<(&str, String)> = e.fields.iter().map(|&(name, kind, format, presence)| (name, schema(kind, format, presence == Presence::Nullable))).collect();
+            let required: Vec<String> = e.fields.iter().filter(|f| f.3 != Presence::Optional).map(|f| json_string(f.0)).collect();
+            let result = json_object(&[
+                ("type", json_string("object")),
+                ("required", format!("[{}]", required.join(","))),
+                ("properties", json_object(&properties)),
+            ]);
+            let responses = json_object(&[("200", json_response(e.summary, result)), ("400", json_response("Invalid parameters", error_ref()))]);
+            let get = json_object(&[
+                ("operationId", json_string(e.method)),
+                ("summary", json_string(e.summary)),
+                ("parameters", format!("[{}]", params.join(","))),
+                ("responses", responses),
+            ]);
+            (format!("/{}/{}", API_VERSION, e.method), json_object(&[("get", get)]))
+        })
+        .collect();
+    let status_ref = || json_object(&[("$ref", json_string("#/components/schemas/Status"))]);
+    let probe = |operation: &str, summary: &str, responses: String| {
+        json_object(&[("get", json_object(&[("operationId", json_string(operation)), ("summary", json_string(summary)), ("responses", responses)]))])
+    };
+    paths.push(("/healthz".to_string(), probe("healthz", "Liveness probe", json_object(&[("200", json_response("Alive", status_ref()))]))));
+    paths.push((
+        "/readyz".to_string(),
+        probe(
+            "readyz",
+            "Readiness probe, failing once the server is shutting down",
+            json_object(&[("200", json_response("Ready", status_ref())), ("503", json_response("Shutting down", status_ref()))]),
+        ),
+    ));
+    let paths: Vec<(&str, String)> = paths.iter().map(|(path, item)| (path.as_str(), item.clone())).collect();
+    let error_schema = json_object(&[
+        ("type", json_string("object")),
+        ("required", "[\"error\"]".to_string()),
+        ("properties", json_object(&[("error", schema("string", None, false))])),
+    ]);
+    let status_schema = json_object(&[
+        ("type", json_string("object")),
+        ("required", "[\"status\"]".to_string()),
+        ("properties", json_object(&[("status", schema("string", None, false))])),
+    ]);
+    json_object(&[
+        ("openapi", json_string("3.0.3")),
+        ("info", json_object(&[("title", json_string("so2lar")), ("version", json_string(env!("CARGO_PKG_VERSION")))])),
+        ("paths", json_object(&paths)),
+        ("components", json_object(&[("schemas", json_object(&[("Error", error_schema), ("Status", status_schema)]))])),
+    ])
+}
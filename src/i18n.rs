@@ -0,0 +1,304 @@
+use std::env;
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset};
+
+use crate::format::Precision;
+
+use crate::phase::Phase;
+
+/// Language of human-readable output. Machine-readable formats stay English.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Lang {
+    #[default]
+    En,
+    De,
+}
+
+const EN: &[(&str, &str)] = &[
+    ("sunrise", "Sunrise: {time} at {bearing}"),
+    ("sunset", "Sunset: {time} at {bearing}"),
+    ("rise-set-direction", "The sun rises in the {rise} and sets in the {set}"),
+    ("clouds-sunrise", "Clouds at sunrise: {percent}%, {outlook}"),
+    ("clouds-sunset", "Clouds at sunset: {percent}%, {outlook}"),
+    ("clouds-none", "Clouds: no forecast for this day"),
+    ("likely visible", "likely visible"),
+    ("possibly obscured", "possibly obscured"),
+    ("likely obscured", "likely obscured"),
+    ("polar-day", "Sunrise and sunset: none, the sun stays above the horizon all day (polar day)"),
+    ("polar-night", "Sunrise and sunset: none, the sun stays below the horizon all day (polar night)"),
+    ("polar-fallback-day", "In their place: lowest sun at {time}"),
+    ("polar-fallback-night", "In their place: highest sun at {time}"),
+    ("solar-noon", "Solar noon: {time}, the sun stands {altitude}° high in the {direction}"),
+    ("calendar-date", "{calendar} date: {date}"),
+    ("accuracy", "Sunrise and sunset are good to about {uncertainty}"),
+    ("calendar-julian", "Julian"),
+    ("calendar-hebrew", "Hebrew"),
+    ("calendar-islamic", "Islamic"),
+    ("north", "north"),
+    ("south", "south"),
+    ("hms", "{h}h, {m}m, {s}s"),
+    ("sun-length", "Sun length: {length}"),
+    ("night-length", "Night length: {length}"),
+    ("darkness", "Darkness: {from} - {to} ({length})"),
+    ("darkness-all-night", "Darkness: all night"),
+    ("darkness-none", "Darkness: none, the sun stays within 18° of the horizon"),
+    ("twilight", "{phase} twilight: {length}"),
+    ("twilight-civil", "Civil"),
+    ("twilight-nautical", "Nautical"),
+    ("twilight-astronomical", "Astronomical"),
+    ("twilight-timed", "{morning} morning, {evening} evening"),
+    ("twilight-all-night", "all night"),
+    ("twilight-midday", "around noon only"),
+    ("twilight-all-day", "all day"),
+    ("twilight-never", "none"),
+    ("since-marker", "You have {change} {amount} of daylight since the {marker} ({date})"),
+    ("until-marker", "{marker} in {days} days ({date}, {time})"),
+    ("until-marker-tomorrow", "{marker} tomorrow ({date}, {time})"),
+    ("until-marker-today", "{marker} today ({date}, {time})"),
+    ("gained", "gained"),
+    ("lost", "lost"),
+    ("spring equinox", "spring equinox"),
+    ("summer solstice", "summer solstice"),
+    ("autumn equinox", "autumn equinox"),
+    ("winter solstice", "winter solstice"),
+    ("day-length-change", "Day length change: {day} per day, {week} per week"),
+    ("compared-with", "Compared with {date}: sunrise {rise}, sunset {set}, day length {length}"),
+    ("visible-sunrise", "Visible sunrise: {time}"),
+    ("visible-sunset", "Visible sunset: {time}"),
+    ("visible-sun-length", "Visible sun length: {length}"),
+    ("shaded", "Shaded: {from} to {to}"),
+    ("none", "none"),
+    ("magnetic", "{bearing} magnetic"),
+    ("grid", "{bearing} grid"),
+    ("above", "Sun above {altitude}° on {date}: {length}"),
+    ("drone-window", "Drone window ({window}) on {date}: {start} to {end}"),
+    ("drone-none", "No drone window ({window}) on {date}"),
+    ("drone-inside", "{time} is inside the window"),
+    ("drone-outside", "{time} is outside the window"),
+    ("find-daylength", "Day length {condition} from {first} to {last}"),
+    ("find-daylength-open", "Day length {condition} from {first} onwards"),
+    ("find-daylength-never", "Day length is never {condition} in the next {days} days"),
+    ("find-sunrise", "Sunrise {condition} from {first} to {last}"),
+    ("find-sunrise-open", "Sunrise {condition} from {first} onwards"),
+    ("find-sunrise-never", "Sunrise is never {condition} in the next {days} days"),
+    ("clock-before", "before {time}"),
+    ("clock-after", "after {time}"),
+    ("subsolar", "{time}  Subsolar point: {point}"),
+    ("now", "{time}  {phase}, elevation {elevation}° at {bearing}, moon {moon_elevation}° at {moon_bearing}, {next}"),
+    ("next-phase", "{phase} in {length}"),
+    ("no-next-phase", "no change within two days"),
+    ("until", "{event} in {length} ({time})"),
+    ("since", "{event} was {length} ago ({time})"),
+    ("summary-now", "Now:       {time}  {phase}, sun {elevation}° at {bearing}"),
+    ("summary-today", "Today:     sunrise {sunrise}, solar noon {noon}, sunset {sunset}"),
+    ("summary-length", "Daylight:  {length}, {change} per day"),
+    ("summary-next", "Next:      {next}"),
+    ("day", "day"),
+    ("night", "night"),
+    ("dawn", "{phase} dawn"),
+    ("dusk", "{phase} dusk"),
+    ("civil", "civil"),
+    ("nautical", "nautical"),
+    ("astronomical", "astronomical"),
+    ("perihelion", "Perihelion: {time}, {distance} AU"),
+    ("aphelion", "Aphelion: {time}, {distance} AU"),
+    ("sun-distance", "Earth–Sun distance: {au} AU ({km} km)"),
+    ("moon-distance", "Earth–Moon distance: {km} km"),
+    ("perigee", "Perigee: {time}, {km} km"),
+    ("apogee", "Apogee: {time}, {km} km"),
+    ("full-moon", "Full moon: {time}, {km} km"),
+    ("full-moon-super", "Full moon: {time}, {km} km, a supermoon"),
+    ("season", "{season}, {percent}% over: since the {start} ({from}), until the {end} ({to}, in {left})"),
+    ("spring", "Spring"),
+    ("summer", "Summer"),
+    ("autumn", "Autumn"),
+    ("winter", "Winter"),
+    ("irradiance", "Irradiance above the atmosphere: {normal} W/m² facing the sun, {horizontal} W/m² on the ground plane"),
+    ("widget-two-line", "Sunrise {rise}  Sunset {set}\nDay {day}  Night {night}"),
+    ("widget-sunrise-in", "sunrise in {length}"),
+    ("widget-sunset-in", "sunset in {length}"),
+    ("widget-no-next", "no sunrise or sunset soon"),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("sunrise", "Sonnenaufgang: {time} bei {bearing}"),
+    ("sunset", "Sonnenuntergang: {time} bei {bearing}"),
+    ("rise-set-direction", "Die Sonne geht im {rise} auf und im {set} unter"),
+    ("clouds-sunrise", "Bewölkung bei Sonnenaufgang: {percent} %, {outlook}"),
+    ("clouds-sunset", "Bewölkung bei Sonnenuntergang: {percent} %, {outlook}"),
+    ("clouds-none", "Bewölkung: keine Vorhersage für diesen Tag"),
+    ("likely visible", "wahrscheinlich sichtbar"),
+    ("possibly obscured", "vielleicht verdeckt"),
+    ("likely obscured", "wahrscheinlich verdeckt"),
+    ("polar-day", "Sonnenauf- und -untergang: keiner, die Sonne bleibt den ganzen Tag über dem Horizont (Polartag)"),
+    ("polar-night", "Sonnenauf- und -untergang: keiner, die Sonne bleibt den ganzen Tag unter dem Horizont (Polarnacht)"),
+    ("polar-fallback-day", "Stattdessen: tiefster Sonnenstand um {time}"),
+    ("polar-fallback-night", "Stattdessen: höchster Sonnenstand um {time}"),
+    ("solar-noon", "Sonnenhöchststand: {time}, die Sonne steht {altitude}° hoch im {direction}"),
+    ("calendar-date", "Datum im {calendar} Kalender: {date}"),
+    ("accuracy", "Sonnenauf- und -untergang stimmen auf etwa {uncertainty}"),
+    ("calendar-julian", "julianischen"),
+    ("calendar-hebrew", "jüdischen"),
+    ("calendar-islamic", "islamischen"),
+    ("north", "Norden"),
+    ("south", "Süden"),
+    ("hms", "{h} h, {m} min, {s} s"),
+    ("sun-length", "Tageslänge: {length}"),
+    ("night-length", "Nachtlänge: {length}"),
+    ("darkness", "Dunkelheit: {from} - {to} ({length})"),
+    ("darkness-all-night", "Dunkelheit: die ganze Nacht"),
+    ("darkness-none", "Dunkelheit: keine, die Sonne sinkt nicht tiefer als 18° unter den Horizont"),
+    ("twilight", "{phase} Dämmerung: {length}"),
+    ("twilight-civil", "Bürgerliche"),
+    ("twilight-nautical", "Nautische"),
+    ("twilight-astronomical", "Astronomische"),
+    ("twilight-timed", "{morning} morgens, {evening} abends"),
+    ("twilight-all-night", "die ganze Nacht"),
+    ("twilight-midday", "nur um die Mittagszeit"),
+    ("twilight-all-day", "den ganzen Tag"),
+    ("twilight-never", "keine"),
+    ("since-marker", "Seit der {marker} ({date}) hast du {amount} Tageslicht {change}"),
+    ("until-marker", "{marker} in {days} Tagen ({date}, {time})"),
+    ("until-marker-tomorrow", "{marker} morgen ({date}, {time})"),
+    ("until-marker-today", "{marker} heute ({date}, {time})"),
+    ("gained", "gewonnen"),
+    ("lost", "verloren"),
+    ("spring equinox", "Frühlings-Tagundnachtgleiche"),
+    ("summer solstice", "Sommersonnenwende"),
+    ("autumn equinox", "Herbst-Tagundnachtgleiche"),
+    ("winter solstice", "Wintersonnenwende"),
+    ("day-length-change", "Änderung der Tageslänge: {day} pro Tag, {week} pro Woche"),
+    ("compared-with", "Im Vergleich zum {date}: Sonnenaufgang {rise}, Sonnenuntergang {set}, Tageslänge {length}"),
+    ("visible-sunrise", "Sichtbarer Sonnenaufgang: {time}"),
+    ("visible-sunset", "Sichtbarer Sonnenuntergang: {time}"),
+    ("visible-sun-length", "Sichtbare Tageslänge: {length}"),
+    ("shaded", "Im Schatten: {from} bis {to}"),
+    ("none", "keiner"),
+    ("magnetic", "{bearing} magnetisch"),
+    ("grid", "{bearing} Gitter"),
+    ("above", "Sonne über {altitude}° am {date}: {length}"),
+    ("drone-window", "Drohnen-Flugfenster ({window}) am {date}: {start} bis {end}"),
+    ("drone-none", "Kein Drohnen-Flugfenster ({window}) am {date}"),
+    ("drone-inside", "{time} liegt im Flugfenster"),
+    ("drone-outside", "{time} liegt außerhalb des Flugfensters"),
+    ("find-daylength", "Tageslänge {condition} vom {first} bis {last}"),
+    ("find-daylength-open", "Tageslänge {condition} ab {first}"),
+    ("find-daylength-never", "Tageslänge in den nächsten {days} Tagen nie {condition}"),
+    ("find-sunrise", "Sonnenaufgang {condition} vom {first} bis {last}"),
+    ("find-sunrise-open", "Sonnenaufgang {condition} ab {first}"),
+    ("find-sunrise-never", "Sonnenaufgang in den nächsten {days} Tagen nie {condition}"),
+    ("clock-before", "vor {time}"),
+    ("clock-after", "nach {time}"),
+    ("subsolar", "{time}  Subsolarer Punkt: {point}"),
+    ("now", "{time}  {phase}, Höhe {elevation}° bei {bearing}, Mond {moon_elevation}° bei {moon_bearing}, {next}"),
+    ("next-phase", "{phase} in {length}"),
+    ("no-next-phase", "keine Änderung in den nächsten zwei Tagen"),
+    ("until", "{event} in {length} ({time})"),
+    ("since", "{event} war vor {length} ({time})"),
+    ("summary-now", "Jetzt:     {time}  {phase}, Sonne {elevation}° bei {bearing}"),
+    ("summary-today", "Heute:     Aufgang {sunrise}, Höchststand {noon}, Untergang {sunset}"),
+    ("summary-length", "Tag:       {length}, {change} pro Tag"),
+    ("summary-next", "Demnächst: {next}"),
+    ("day", "Tag"),
+    ("night", "Nacht"),
+    ("dawn", "{phase} Morgendämmerung"),
+    ("dusk", "{phase} Abenddämmerung"),
+    ("civil", "bürgerliche"),
+    ("nautical", "nautische"),
+    ("astronomical", "astronomische"),
+    ("perihelion", "Perihel: {time}, {distance} AE"),
+    ("aphelion", "Aphel: {time}, {distance} AE"),
+    ("sun-distance", "Abstand Erde–Sonne: {au} AE ({km} km)"),
+    ("moon-distance", "Abstand Erde–Mond: {km} km"),
+    ("perigee", "Perigäum: {time}, {km} km"),
+    ("apogee", "Apogäum: {time}, {km} km"),
+    ("full-moon", "Vollmond: {time}, {km} km"),
+    ("full-moon-super", "Vollmond: {time}, {km} km, ein Supermond"),
+    ("season", "{season}, zu {percent}% vorbei: seit der {start} ({from}), bis zur {end} ({to}, in {left})"),
+    ("spring", "Frühling"),
+    ("summer", "Sommer"),
+    ("autumn", "Herbst"),
+    ("winter", "Winter"),
+    ("irradiance", "Bestrahlungsstärke über der Atmosphäre: {normal} W/m² zur Sonne gerichtet, {horizontal} W/m² waagerecht"),
+    ("widget-two-line", "Aufgang {rise}  Untergang {set}\nTag {day}  Nacht {night}"),
+    ("widget-sunrise-in", "Aufgang in {length}"),
+    ("widget-sunset-in", "Untergang in {length}"),
+    ("widget-no-next", "kein Auf- oder Untergang in Sicht"),
+];
+
+impl Lang {
+    /// Language from `LC_ALL`, `LC_MESSAGES` or `LANG`, in that order.
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|v| !v.is_empty())
+            .and_then(|v| v.split(['_', '.', '@']).next()?.parse().ok())
+            .unwrap_or_default()
+    }
+
+    fn messages(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::En => EN,
+            Lang::De => DE,
+        }
+    }
+
+    /// Message `key`, falling back to English and then to the key itself.
+    pub fn text(&self, key: &'static str) -> &'static str {
+        let find = |messages: &'static [(&str, &'static str)]| messages.iter().find(|(k, _)| *k == key).map(|(_, v)| *v);
+        find(self.messages()).or_else(|| find(EN)).unwrap_or(key)
+    }
+
+    /// Message `key` with `{name}` placeholders replaced by `args`.
+    pub fn format(&self, key: &'static str, args: &[(&str, &str)]) -> String {
+        args.iter().fold(self.text(key).to_string(), |s, (name, value)| s.replace(&format!("{{{}}}", name), value))
+    }
+
+    /// Date and time of `t` rounded to `precision`, in the style of RFC 2822 for English.
+    pub fn datetime(&self, t: &DateTime<FixedOffset>, precision: Precision) -> String {
+        let pattern = match self {
+            Lang::En => format!("%a, %d %b %Y {} %z", precision.time_pattern()),
+            Lang::De => format!("%d.%m.%Y {} %:z", precision.time_pattern()),
+        };
+        precision.round(t).format(&pattern).to_string()
+    }
+
+    pub fn short_date(&self, t: &DateTime<FixedOffset>) -> String {
+        match self {
+            Lang::En => t.format("%b %-d").to_string(),
+            Lang::De => t.format("%-d.%-m.").to_string(),
+        }
+    }
+
+    /// Compass point abbreviation; German uses O for east and z for by.
+    pub fn compass(&self, point: &str) -> String {
+        match self {
+            Lang::En => point.to_string(),
+            Lang::De => point.replace('E', "O").replace('b', "z"),
+        }
+    }
+
+    pub fn phase(&self, phase: &Phase) -> String {
+        match phase {
+            Phase::Day => self.text("day").to_string(),
+            Phase::Night => self.text("night").to_string(),
+            Phase::Twilight { phase, rising } => {
+                self.format(if *rising { "dawn" } else { "dusk" }, &[("phase", self.text(phase.name()))])
+            }
+        }
+    }
+}
+
+impl FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Lang, String> {
+        match s.to_lowercase().as_str() {
+            "en" | "c" | "posix" => Ok(Lang::En),
+            "de" => Ok(Lang::De),
+            _ => Err(format!("unsupported language `{}` (expected en or de)", s)),
+        }
+    }
+}
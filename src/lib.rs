@@ -0,0 +1,135 @@
+// Library logging goes through the `log` crate with the `log` feature and
+// compiles away without it.
+macro_rules! info {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::info!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[cfg_attr(not(feature = "chrono"), allow(unused_macros))]
+macro_rules! warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::warn!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[cfg_attr(not(feature = "chrono"), allow(unused_macros))]
+macro_rules! error {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::error!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+#[cfg(feature = "chrono")]
+pub mod algorithm;
+#[cfg(feature = "chrono")]
+pub mod almanac;
+pub mod angle;
+#[cfg(feature = "chrono")]
+pub mod aviation;
+#[cfg(feature = "chrono")]
+pub mod batch;
+pub mod bearing;
+#[cfg(feature = "chrono")]
+pub mod cache;
+#[cfg(feature = "chrono")]
+pub mod calendar;
+#[cfg(feature = "chrono")]
+pub mod color;
+#[cfg(feature = "chrono")]
+pub mod config;
+pub mod constants;
+#[cfg(feature = "chrono")]
+pub mod daemon;
+#[cfg(feature = "chrono")]
+pub mod dial;
+#[cfg(feature = "chrono")]
+pub mod duration;
+#[cfg(feature = "chrono")]
+pub mod eclipse;
+#[cfg(feature = "chrono")]
+pub mod exif;
+#[cfg(feature = "chrono")]
+pub mod export;
+#[cfg(feature = "chrono")]
+pub mod fetch;
+#[cfg(feature = "chrono")]
+pub mod find;
+#[cfg(feature = "chrono")]
+pub mod format;
+#[cfg(feature = "chrono")]
+pub mod glare;
+#[cfg(feature = "chrono")]
+mod hmac;
+pub mod horizon;
+#[cfg(feature = "chrono")]
+pub mod http;
+#[cfg(feature = "chrono")]
+pub mod i18n;
+#[cfg(feature = "chrono")]
+pub mod json;
+#[cfg(feature = "chrono")]
+pub mod location;
+pub mod magnetic;
+#[cfg(feature = "chrono")]
+pub mod moon;
+#[cfg(feature = "chrono")]
+pub mod nightless;
+#[cfg(feature = "chrono")]
+pub mod notify;
+#[cfg(feature = "chrono")]
+pub mod orbit;
+#[cfg(feature = "chrono")]
+pub mod overlap;
+pub mod parallel;
+#[cfg(feature = "chrono")]
+pub mod paths;
+pub mod phase;
+#[cfg(feature = "planets")]
+pub mod planets;
+#[cfg(feature = "chrono")]
+pub mod photo;
+#[cfg(feature = "chrono")]
+pub mod pipeline;
+#[cfg(feature = "chrono")]
+pub mod platform;
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod progress;
+pub mod refraction;
+#[cfg(feature = "chrono")]
+pub mod repl;
+#[cfg(feature = "chrono")]
+pub mod report;
+#[cfg(feature = "chrono")]
+pub mod rpc;
+#[cfg(feature = "chrono")]
+pub mod sample;
+pub mod seasons;
+#[cfg(feature = "chrono")]
+mod signal;
+pub mod solar;
+#[cfg(feature = "chrono")]
+pub mod stream;
+pub mod timescale;
+#[cfg(feature = "chrono")]
+pub mod timeshift;
+pub mod timing;
+#[cfg(feature = "chrono")]
+pub mod tzif;
+pub mod validate;
+#[cfg(feature = "weather")]
+pub mod weather;
+#[cfg(feature = "chrono")]
+pub mod widget;
+#[cfg(feature = "chrono")]
+pub mod window;
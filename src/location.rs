@@ -0,0 +1,27 @@
+use std::io;
+
+use crate::paths;
+use crate::platform::Files;
+
+/// Coordinates saved by `--remember`, reused when no location is given.
+pub fn load_last(files: &dyn Files) -> Option<(f64, f64)> {
+    let s = files.read_to_string(&paths::last_location_file()?).ok()?;
+    let mut fields = s.split_whitespace().map(str::parse::<f64>);
+    match (fields.next(), fields.next()) {
+        (Some(Ok(lat)), Some(Ok(long))) => Some((lat, long)),
+        _ => None,
+    }
+}
+
+pub fn save_last(files: &dyn Files, lat: f64, long: f64) -> io::Result<()> {
+    let path = paths::last_location_file().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory, set HOME"))?;
+    paths::ensure_parent(files, &path)?;
+    files.write(&path, &format!("{} {}\n", lat, long))
+}
+
+pub fn forget_last(files: &dyn Files) -> io::Result<()> {
+    match paths::last_location_file().map(|path| files.remove(&path)) {
+        Some(Err(e)) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
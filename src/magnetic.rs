@@ -0,0 +1,128 @@
+//! Magnetic declination from the World Magnetic Model. The coefficients
+//! are read from NOAA's `WMM.COF` file, which is published with each
+//! five-year model release.
+
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+const WGS84_A: f64 = 6378.137;
+const WGS84_F: f64 = 1.0 / 298.257223563;
+const GEOMAGNETIC_RADIUS: f64 = 6371.2;
+
+struct Coefficient {
+    n: usize,
+    m: usize,
+    g: f64,
+    h: f64,
+    g_dot: f64,
+    h_dot: f64,
+}
+
+pub struct MagneticModel {
+    epoch: f64,
+    max_degree: usize,
+    coefficients: Vec<Coefficient>,
+}
+
+impl MagneticModel {
+    pub fn from_cof(path: &Path) -> Result<MagneticModel, Box<dyn Error>> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(s: &str) -> Result<MagneticModel, Box<dyn Error>> {
+        let mut lines = s.lines().filter(|l| !l.trim().is_empty());
+        let header = lines.next().ok_or("empty coefficient file")?;
+        let epoch: f64 = header.split_whitespace().next().ok_or("missing epoch")?.parse()?;
+        let mut coefficients = Vec::new();
+        for line in lines {
+            if line.trim_start().starts_with("9999") {
+                break;
+            }
+            let f: Vec<&str> = line.split_whitespace().collect();
+            if f.len() < 6 {
+                return Err(format!("invalid coefficient line `{}`", line).into());
+            }
+            coefficients.push(Coefficient {
+                n: f[0].parse()?,
+                m: f[1].parse()?,
+                g: f[2].parse()?,
+                h: f[3].parse()?,
+                g_dot: f[4].parse()?,
+                h_dot: f[5].parse()?,
+            });
+        }
+        let max_degree = coefficients.iter().map(|c| c.n).max().ok_or("no coefficients")?;
+        if coefficients.iter().any(|c| c.m > c.n) {
+            return Err("coefficient with order above degree".into());
+        }
+        Ok(MagneticModel { epoch, max_degree, coefficients })
+    }
+
+    /// Declination (east of true north positive) in degrees at geodetic
+    /// `lat`/`long`, `height` km above the ellipsoid and decimal `year`.
+    pub fn declination(&self, lat: f64, long: f64, height: f64, year: f64) -> f64 {
+        let (x, y, _) = self.field(lat, long, height, year);
+        y.atan2(x).to_degrees()
+    }
+
+    /// North, east and down components of the field in nT.
+    pub fn field(&self, lat: f64, long: f64, height: f64, year: f64) -> (f64, f64, f64) {
+        // Geodetic to geocentric spherical coordinates
+        let (rlat, rlong) = (lat.to_radians(), long.to_radians());
+        let e2 = WGS84_F * (2.0 - WGS84_F);
+        let rc = WGS84_A / (1.0 - e2 * rlat.sin().powi(2)).sqrt();
+        let p = (rc + height) * rlat.cos();
+        let z = (rc * (1.0 - e2) + height) * rlat.sin();
+        let r = p.hypot(z);
+        let geocentric = (z / r).asin();
+
+        // Schmidt semi-normalized associated Legendre functions of the
+        // colatitude and their derivatives with respect to it
+        let size = self.max_degree + 1;
+        let (c, s) = (geocentric.sin(), geocentric.cos().max(1e-12));
+        let mut pnm = vec![vec![0.0; size]; size];
+        let mut dp = vec![vec![0.0; size]; size];
+        pnm[0][0] = 1.0;
+        for n in 1..size {
+            for m in 0..=n {
+                if n == m {
+                    pnm[n][m] = s * pnm[n - 1][m - 1];
+                    dp[n][m] = s * dp[n - 1][m - 1] + c * pnm[n - 1][m - 1];
+                } else if n == 1 {
+                    pnm[n][m] = c * pnm[n - 1][m];
+                    dp[n][m] = c * dp[n - 1][m] - s * pnm[n - 1][m];
+                } else {
+                    let k = ((n - 1).pow(2) as f64 - (m * m) as f64) / (((2 * n - 1) * (2 * n - 3)) as f64);
+                    pnm[n][m] = c * pnm[n - 1][m] - k * pnm[n - 2][m];
+                    dp[n][m] = c * dp[n - 1][m] - s * pnm[n - 1][m] - k * dp[n - 2][m];
+                }
+            }
+        }
+        let mut schmidt = vec![vec![1.0; size]; size];
+        for n in 1..size {
+            schmidt[n][0] = schmidt[n - 1][0] * (2 * n - 1) as f64 / n as f64;
+            for m in 1..=n {
+                let delta = if m == 1 { 2.0 } else { 1.0 };
+                schmidt[n][m] = schmidt[n][m - 1] * (((n - m + 1) as f64 * delta) / (n + m) as f64).sqrt();
+            }
+        }
+
+        let dt = year - self.epoch;
+        let (mut north, mut east, mut down) = (0.0, 0.0, 0.0);
+        for coef in &self.coefficients {
+            let (n, m) = (coef.n, coef.m);
+            let g = (coef.g + dt * coef.g_dot) * schmidt[n][m];
+            let h = (coef.h + dt * coef.h_dot) * schmidt[n][m];
+            let ratio = (GEOMAGNETIC_RADIUS / r).powi(n as i32 + 2);
+            let (sin_ml, cos_ml) = (m as f64 * rlong).sin_cos();
+            north += ratio * (g * cos_ml + h * sin_ml) * dp[n][m];
+            east += ratio * m as f64 * (g * sin_ml - h * cos_ml) * pnm[n][m] / s;
+            down -= ratio * (n + 1) as f64 * (g * cos_ml + h * sin_ml) * pnm[n][m];
+        }
+
+        // Rotate from geocentric to geodetic axes
+        let psi = geocentric - rlat;
+        (north * psi.cos() - down * psi.sin(), east, north * psi.sin() + down * psi.cos())
+    }
+}
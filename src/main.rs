@@ -1,102 +1,2394 @@
+use std::cell::LazyCell;
+use std::env;
 use std::error::Error;
-use chrono::{DateTime, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc};
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+use chrono::{DateTime, Datelike, Days, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
 use log::info;
 
-use julian::Calendar;
+use so2lar::algorithm::{Algorithm, AlgorithmDiff};
+use so2lar::almanac::AlmanacPage;
+use so2lar::aviation::Rules;
+use so2lar::bearing::{grid_convergence, to_magnetic, BearingFormat, BearingReference, CompassRose};
+use so2lar::cache::ResponseCache;
+use so2lar::calendar::Calendar;
+use so2lar::color::{self, ColorChoice, Theme, Tone};
+use so2lar::config::{Config, LocationProfile};
+use so2lar::export::ics::{Alarm, Event};
+use so2lar::duration::DurationStyle;
+use so2lar::fetch::HttpCache;
+use so2lar::horizon::{HorizonProfile, Obstacle};
+use so2lar::i18n::Lang;
+use so2lar::magnetic::MagneticModel;
+use so2lar::notify::desktop::Desktop;
+use so2lar::notify::exec::Exec;
+use so2lar::notify::webhook::Webhook;
+use so2lar::notify::Subscription;
+use so2lar::phase::Phase;
+use so2lar::platform::{Clock, FixedClock, HostFiles, ShiftedClock, SystemClock};
+use so2lar::seasons::{Hemisphere, SeasonMarker};
+use so2lar::solar::{azimuth_at, darkness, datetime2julian, day_length, day_length_rate, get_crossings, jdn, julian2datetime, julian2utc, night_length, sun_position, sunrise_altitude_with, sunrise_sunset, SolarEvent, Twilight, TwilightLength};
+use so2lar::format::{DisplayZone, Format, OutputFormatter, Precision, Table, Value};
+use so2lar::refraction::RefractionModel;
+use so2lar::report::{format_coords, MonthReport, YearReport};
+use so2lar::sample::Sample;
+#[cfg(feature = "plot")]
+use so2lar::plot;
+use so2lar::orbit::{self, Apsis};
+use so2lar::widget::{Snapshot, Widget};
+use so2lar::find::{self, ClockCondition, DayLengthCondition};
+use so2lar::glare::{self, GlareCone};
+use so2lar::timing::{self, Stage, Timed};
+use so2lar::validate::{self, InvalidInput};
+use so2lar::exif::{self, Exif};
+use so2lar::angle::Coordinates;
+use so2lar::{daemon, duration, eclipse, export, location, paths, photo, progress, solar, window};
 
-fn julian2datetime(j: f64) -> DateTime<Local> {
-    let date = Calendar::GREGORIAN.at_jdn(j.floor() as i32);
-    let rem = (j - j.floor()) * 24.0;
-    let date = NaiveDate::try_from(date).unwrap();
-    let h = rem.floor();
-    let rem = (rem - h) * 60.0;
-    let m = rem.floor();
-    let rem = (rem - m) * 60.0;
-    let s = rem.floor();
-    let time = NaiveTime::from_hms_opt(h as u32, m as u32, s as u32).unwrap(); 
-    let date = NaiveDateTime::new(date, time); 
-    let date: DateTime<Utc> = Utc.from_utc_datetime(&date);
-    let date: DateTime<Local> = date.into();
-    let date = date + TimeDelta::hours(12);
-    let date = date.checked_sub_days(Days::new(1)).unwrap();
-    date
+const USAGE: &str = "Usage: so2lar [--lat DEG] [--long DEG] [--location NAME] [--elevation M|KMkm|FTft] [--refraction none|bennett|saemundsson|34'] [--date YYYY-MM-DD] [--now TIME] [--utc] [--lang en|de] [--remember] [--forget] [--compare-year] [--bearings true|magnetic|grid]
+              [--bearing-format both|degrees|compass|mils] [--compass 8|16|32] [--hemisphere north|south] [--wmm WMM.COF] [--declination DEG] [--horizon FILE] [--obstacle SPEC]... [--clouds] [--offline] [--explain] [--polar-fallback]
+              [--calendar julian|hebrew|islamic] [--xmp] [--show-accuracy] [--day-boundary solar|civil] [--daylength-definition DEF] [--format FORMAT] [--precision minutes|seconds|millis] [--durations STYLE] [--widget NAME] [--color auto|always|never]
+              [--theme default|basic|mono] [--timing] [-q|--quiet] [-o|--output FILE] [COMMAND]
+
+Commands:
+  kml         Export the sun path and sunrise/sunset rays as KML
+  gpx FILE    Annotate GPX points with sun elevation and daylight (--format gpx|csv)
+  ics         Export sunrise/sunset as iCalendar events (--days N, --sample weekly, --alarm -30m@sunset)
+  diff        Compare sunrise and sunset of two --algorithms (simple,position by default) day by day over
+              --range YEAR|FROM..TO or --year, with the largest and mean differences
+  week        List the next seven days from --date with sunrise, sunset and day length and how each
+              changed from the day before (--format table|csv|json)
+  events      List dawn, sunrise, sunset and dusk for --days N from --date (--sample, --format table|csv|json|sql);
+              -o sqlite:FILE stores them in the solar_events table of an SQLite database (needs sqlite3),
+              replacing rows for the same location, date and event
+  config path Show where the configuration, bookmarks and caches are stored
+  cron        Print crontab lines (--format at: at commands) running --command CMD at events (--days N, --sample,
+              --offset)
+  table       Write a --year of sunrise/sunset minutes for firmware (--format bin|c|rust)
+  generate almanac  Write a --year of local dawn, sunrise, sunset and dusk minutes with lookup code
+              for devices without floating point or time zones (--format rust|c|json)
+  daemon      Run until killed, notifying at each event (--webhook URL, --webhook-secret, --webhook-retries,
+              --hook CMD to run a command at --event, or sunrise and sunset, shifted by --offset -30m,
+              --notify with --notify-lead 15m and --notify-event EVENT for desktop notifications, and
+              the chats of [notify.matrix] and [notify.telegram] in the configuration file)
+  eclipse [solar|lunar]  List the next --count eclipses from --date, or those in a --year or up to --until;
+              solar eclipses only where visible, lunar ones with the Moon's elevation at maximum (--format)
+  aviation    List the nights of --days N from --date under --rules faa|easa|OFFSET (e.g. 30m after
+              sunset to 30m before sunrise) in UTC (Zulu) and local time
+  drone       Print the drone operating window of --date for --window faa|faa-2016|daylight or a rule
+              like \"sunrise-30m to sunset+30m\", and whether --at TIME (default now) falls inside it
+  find        Search from --date for the days whose length meets --daylength, e.g. \">=16h\" or \"<8h 30m\",
+              or whose local sunrise is --sunrise-before or --sunrise-after HH:MM
+  above DEG   Print how long the sun stays above DEG degrees on --date (--format json; `above -- -6` for negative)
+  batch FILE  Compute sunrise, sunset, solar noon and day length for every place of FILE (`name,lat,long`
+              or `lat,long` lines, - for stdin) on every date from --date for --days N or up to --until,
+              one row per place and date (--format table|csv|json)
+  clock       Draw a 24-hour dial of --date's night, twilight and day with the current time marked,
+              redrawn every minute on a terminal until interrupted
+  glare [FILE] List when the sun is low ahead travelling on --heading DEG between --from and --to HH:MM on
+              --date, or along a timestamped GPX route (--cone DEG half-angle, default 20;
+              --max-elevation DEG, default 25)
+  hours [NAME...]  Print the start and end on --date of the named [hours] rules of the configuration
+              file (all without NAME) and whether each is open now
+  nautical    Print the Sun's hourly GHA and declination, equation of time and meridian passage on
+              --date like a nautical almanac page (--format)
+  moon        Print the Earth–Moon distance now or at midnight of --date, the next perigee and apogee,
+              and the next full moon, flagged as a supermoon within 90% of the way from apogee to perigee
+              (--format)
+  moon next|previous  List the next or previous --count principal phases (default 1) from now or
+              from midnight of --date, all four or only --phase new|first-quarter|full|last-quarter,
+              with the Earth–Moon distance and whether a full moon is a supermoon (--format)
+  nightlight wlsunset|hyprsunset  Print the wlsunset command line for --date, fading over civil
+              twilight to --temperature K at night (default 4000), or run it with --exec (arguments after
+              -- are passed on); or write hyprsunset.conf profiles switching at sunrise and sunset (-o FILE)
+  nightless   List the periods of --year in which night never falls: the sun stays above each
+              twilight's lower limit, or only --twilight civil|nautical|astronomical's (--format)
+  now         Print the current twilight phase, sun and moon position and the next phase change (--format json)
+  orbit       Print the perihelion and aphelion of --year, and the Earth–Sun distance and
+              top-of-atmosphere irradiance now or at noon on --date
+  overlap     List the windows of --date when every --location (two or more, by name) has daylight,
+              within working hours --from HH:MM --to HH:MM of each place's time zone if given, with
+              each window in the places' local times (--format)
+  photo       Lay out --date as blue hour, golden hour, harsh light and night with the sun's direction
+              at each change (--bearing-format, --format)
+  photo FILE...  Read when and where each JPEG or raw photo was taken from its EXIF and give the sun's
+              position, the phase and light and the time from the nearest golden hour (--xmp to
+              write them as keywords to a FILE.xmp sidecar that does not exist yet, --format)
+  parquet     Write --days N from --date of sunrise, sunset, solar noon, civil dawn and dusk (UTC
+              timestamps) and day length as a Parquet file (-o FILE) for DuckDB, pandas and the like
+  planets [NAME]  List rise, transit and set of Venus, Mars, Jupiter and Saturn, or one of them, for
+              --days N from --date (--format)
+  plot heatmap  Render a --year of day, twilight and night by date and hour (--png FILE)
+  plot year   Chart sunrise, solar noon and sunset through a --year (--svg FILE and/or --png FILE)
+  report      Summarize daylight over --year YYYY, or per day over --month YYYY-MM
+              (--format table|csv|json|markdown, --no-dst adds standard-time columns,
+              --sample weekly or \"1st of each month\" thins the daily rows)
+  relay       List when a relay is on for --window RULE, e.g. \"sunset-15m to sunrise+15m\" (overnight when
+              the end comes first), for --days N from --date (--format table|csv|json, or esphome for a
+              time: block switching `relay` on and off at each time in UTC)
+  repl        Query interactively, keeping location and date between commands (`help` lists them)
+  rpc         Answer JSON-RPC requests on stdin, one per line (events, position, phase, subsolar)
+  schtasks    Write and, on Windows, register a scheduled task running --command CMD at the next --event
+              (--task NAME, --dir DIR), refreshed nightly like the systemd timer
+  serve       Answer the rpc methods over HTTP as GET /v1/METHOD?lat=48.4&long=9.9 on --listen ADDR
+              (default 127.0.0.1:8080), described by the OpenAPI document at /openapi.json, with /healthz
+              and /readyz probes; SIGTERM stops it after the requests it has accepted. Answers of events
+              are kept per location rounded to 0.0001° and day for --cache-ttl (default 1h), at most
+              --cache-size N of them (default 10000, 0 keeps none)
+  season      Print the astronomical season and how much of it has passed, now or on --date
+  shadow      List for the 21st of each month of --year the solar noon, the sun's noon altitude and
+              declination, and the noon shadow length per metre of height and its direction (--format)
+  solar-time  List --days N from --date of civil dawn, sunrise, sunset and civil dusk in local apparent
+              solar time, by which the sun transits at 12:00 whatever the time zone, with the offset of
+              that clock from UTC (--sample, --format table|csv|json)
+  stream      Answer one request per stdin line as it arrives, a JSON object like {\"lat\": 48.4, \"long\": 9.9,
+              \"date\": \"2024-06-21\"} or a CSV record lat,long,date[,time] (a header line may reorder the
+              columns), with sunrise, sunset, day length and, for a time, the sun's position
+  summary     Print the current phase and sun position, sunrise, solar noon and sunset of --date, the day
+              length and its change, and the time to the next phase change in one block (--format json)
+  subsolar    Print where the sun is in the zenith (--watch to keep updating every --interval)
+  sunline [sunrise|sunset]  Write as GeoJSON the line of all places seeing sunrise (the default) or
+              sunset at --at TIME, by default now
+  systemd     Write a user timer starting --unit UNIT at the next --event (--dir DIR)
+  timeshift   Count the days of --year with the sun up before --from (default 07:00) and after --to
+              (default 18:00) under the current clock rules, permanent standard time and permanent
+              daylight saving time (--format)
+  until EVENT  Print the time left until EVENT (sunrise, sunset, dawn or dusk with an optional offset
+              like sunset-30m) on --date, by default the local today; --seconds prints only the signed
+              number of seconds, negative once it has passed, for `sleep $(so2lar until sunset --seconds)`
+  wait EVENT  Sleep until the next EVENT (as for until) shifted by --offset, e.g. `so2lar wait sunrise
+              --offset -30m && wake-light on`; on --date only if given
+  upcoming    List the next --count events (default 10) from now: each twilight's dawn and dusk, sunrise,
+              solar noon and sunset, with the time left until each (--format)
+
+Environment:
+  SO2LAR_LAT, SO2LAR_LON  Default location in degrees
+  SO2LAR_TZ               Time zone for local times (UTC or a name like Europe/Berlin)
+  SO2LAR_FORMAT           Default --format
+  SO2LAR_THREADS          Threads for year-long computations (default: one per core)
+  SO2LAR_OFFLINE          Set to 1 for --offline
+
+Named locations come from [locations.NAME] tables in the configuration file, each with lat, long and
+optionally elevation (metres) and timezone; `default = \"NAME\"` picks one when no location is given.
+An [hours] table names legal hours such as `hunting = \"sunrise-30m to sunset+30m\"` for `so2lar hours`.
+[notify.telegram] (bot_token, chat_id) and [notify.matrix] (homeserver, room, access_token) tables send
+daemon messages like \"Sunset in 10 minutes\", optionally for `events = \"sunset\"` and `lead = \"10m, 0m\"`.
+When its location comes from the configuration file, the daemon rereads it whenever the file changes
+or on SIGHUP, and finds the upcoming events for the new coordinates without a restart.
+Settings apply in the order configuration file, environment, command line.
+
+JSON objects carry a schema_version. Later versions only add fields and events; --format json-v1
+pins the output to those of version 1.
+
+Tables and the `now` line are coloured by the light they describe when stdout is a terminal and
+NO_COLOR is unset; --color always|never overrides that, --theme basic suits 16-colour terminals.
+Computations over many days draw a progress bar on stderr after a second, except with --quiet or
+--format json.
+
+Without a command, prints today's summary, or with --widget line|two-line|countdown|tmux a compact
+layout for conky, lemonbar or tmux's status-right (--format tmux is the same as --widget tmux).
+
+--now TIME (RFC 3339 or YYYY-MM-DD HH:MM in the display time zone) computes everything as of that
+instant instead of the system clock: the default --date, countdowns, `now`, `upcoming` and the next
+events of the exports. The daemon's clock starts there and runs on in real time.
+
+A TIME, as taken by --now and --at, can also be an event with an offset such as sunset-45m or
+civil_dawn+1h on --date or today, or after a date as in \"2026-06-21 sunrise\". --event takes only
+sunrise or sunset, with such an offset as in sunset-45m, which adds to --offset for hooks, wait and cron.
+
+--show-accuracy adds how far off sunrise and sunset may be to the summary, and each event's to the
+events: the error of the sunrise equation and the spread of the refraction, over how fast the sun
+climbs. It is a minute or two at moderate latitudes, and grows to hours where the sun skims the horizon.
+
+--calendar julian|hebrew|islamic adds the date in that calendar to the summary and to the events: the
+Julian calendar, the Hebrew calendar, or the tabular Islamic calendar, which observed dates may
+precede or follow by a day or two. Days are civil days from midnight.
+
+--polar-fallback gives polar days and nights times in place of sunrise and sunset, marked in the
+summary, its polar_fallback field and with ~ in widgets: the lowest sun after noon when the sun
+stays up, and the highest sun at noon when it stays down.
+
+--remember saves the given location for later runs that name none; --forget drops it.
+
+--explain prints every intermediate value of the sunrise equation for --date with its unit instead of
+the summary, to check against references.
+
+--day-boundary civil gives the summary and ics the sunrise and sunset that fall on the date in the
+display time zone, which may come from the solar day before or after; solar (the default) keeps the
+pair around one solar noon.
+
+--hemisphere names the seasons in the summary, season and report for that hemisphere, where the
+June solstice begins summer or winter; by default that of --lat, the equator counting as north.
+
+--daylength-definition sets where the summary's sun length and day_length_seconds begin and end:
+upper-limb at the refracted horizon like sunrise (the default), center, upper-limb-geometric and
+geometric without refraction, or civil from civil dawn to dusk.
+
+--horizon FILE reads the terrain's height by azimuth from `azimuth,elevation` lines, and each
+--obstacle BEARING,HEIGHT,WIDTH (degrees, optionally named as in building:220,15,30) blocks the sun
+behind a building or tree; the summary then gives the sunrise, sunset and sun length in direct view
+and the times the sun is shaded in between.
+
+--clouds adds Open-Meteo's cloud cover forecast at sunrise and sunset to the summary (needs curl and
+the weather feature). Responses of network services are cached for a while (forecasts an hour) under
+the cache directory of `so2lar config path`, and reused when a request fails; --offline answers from
+that cache alone.
+
+--durations writes lengths of time compact (15h42m), verbose (15 hours 42 minutes), on a clock
+(15:42:12) or in ISO 8601 (PT15H42M12S) instead of the default 15h 42m, or 15h, 42m, 12s in the summary.
+
+Compass directions name the nearest of 16 points (ENE), or of 8 (NE) or 32 (NEbE) with --compass.
+
+--timing reports on stderr the time spent parsing the command line and configuration, in network
+requests and writing the output, the rest being the math, and how many maps ran in parallel.
+
+Exit status: 0 on success, 1 on other failures, 2 on unknown commands, options or malformed values,
+3 on values out of range (latitude -90 to 90, longitude -180 to 180, elevation -500 m to 100 km,
+years 1 to 9999).";
+
+/// A command line that does not fit the usage, reported with it.
+#[derive(Debug)]
+struct Usage(String);
+
+impl std::fmt::Display for Usage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}\n{}", self.0, USAGE)
+    }
 }
 
-fn mean_solar_time(n: f64, long: f64) -> f64 {
-    n - long / 360.0
+impl Error for Usage {}
+
+fn usage(msg: impl Into<String>) -> Box<dyn Error> {
+    Box::new(Usage(msg.into()))
 }
 
-fn solar_mean_anomaly(j_star: f64) -> f64 {
-    (357.5291 + 0.98560028 * j_star) % 360.0
+/// Exit status for `e` as listed in the usage.
+fn exit_code(e: &(dyn Error + 'static)) -> u8 {
+    if std::iter::successors(Some(e), |&e| e.source()).any(|e| e.is::<InvalidInput>()) {
+        3
+    } else if e.is::<Usage>() || e.is::<lexopt::Error>() {
+        2
+    } else {
+        1
+    }
 }
 
-fn normalized_date(j_date: f64) -> f64 {
-    (j_date - 2451545.0 + 0.0008).ceil()
+struct Args {
+    command: Option<String>,
+    inputs: Vec<String>,
+    lat: f64,
+    long: f64,
+    /// Observer height above the terrain in metres, lowering the horizon.
+    elevation: f64,
+    /// Whether the location came from the environment or flags rather than the defaults.
+    location_given: bool,
+    /// The configured location given by --location, unless --lat or --long override it.
+    location_name: Option<String>,
+    /// Every --location, in order; the last one sets the location.
+    locations: Vec<String>,
+    remember: bool,
+    forget: bool,
+    date: Option<NaiveDate>,
+    zone: DisplayZone,
+    compare_year: bool,
+    lang: Option<Lang>,
+    bearings: BearingReference,
+    bearing_format: BearingFormat,
+    compass: CompassRose,
+    hemisphere: Option<Hemisphere>,
+    clouds: bool,
+    offline: bool,
+    durations: DurationStyle,
+    timing: bool,
+    precision: Precision,
+    wmm: Option<PathBuf>,
+    declination: Option<f64>,
+    watch: bool,
+    interval: std::time::Duration,
+    horizon: Option<PathBuf>,
+    obstacles: Vec<Obstacle>,
+    format: Option<String>,
+    widget: Option<Widget>,
+    days: u32,
+    daylength: Option<DayLengthCondition>,
+    sunrise_clock: Option<ClockCondition>,
+    rules: Rules,
+    window: Option<String>,
+    at: Option<String>,
+    now: Option<String>,
+    heading: Option<f64>,
+    refraction: Option<RefractionModel>,
+    from: Option<NaiveTime>,
+    to: Option<NaiveTime>,
+    glare: GlareCone,
+    sample: Sample,
+    count: Option<usize>,
+    moon_phase: Option<so2lar::moon::MoonPhase>,
+    temperature: Option<u32>,
+    exec: bool,
+    seconds: bool,
+    explain: bool,
+    day_boundary: solar::DayBoundary,
+    daylength_definition: solar::DayLengthDefinition,
+    until: Option<NaiveDate>,
+    year: Option<i32>,
+    month: Option<NaiveDate>,
+    no_dst: bool,
+    polar_fallback: bool,
+    xmp: bool,
+    show_accuracy: bool,
+    algorithms: [Algorithm; 2],
+    range: Option<(NaiveDate, NaiveDate)>,
+    calendar: Option<Calendar>,
+    alarms: Vec<Alarm>,
+    event: Option<SolarEvent>,
+    unit: Option<String>,
+    task: Option<String>,
+    command_line: Option<String>,
+    dir: Option<PathBuf>,
+    webhooks: Vec<String>,
+    hooks: Vec<String>,
+    twilight: Option<Twilight>,
+    offset: TimeDelta,
+    webhook_secret: Option<String>,
+    webhook_retries: u32,
+    /// Address `serve` listens on.
+    listen: String,
+    cache_ttl: std::time::Duration,
+    cache_size: usize,
+    notify: bool,
+    notify_leads: Vec<TimeDelta>,
+    notify_events: Vec<SolarEvent>,
+    color: ColorChoice,
+    theme: Theme,
+    quiet: bool,
+    output: Option<PathBuf>,
+    png: Option<PathBuf>,
+    svg: Option<PathBuf>,
+    config: Config,
 }
 
-fn equation_of_the_center(m: f64) -> f64 {
-    let m_rad = m.to_radians();
-    1.9148 * m_rad.sin() + 0.02 * (2.0 * m_rad).sin() + 0.0003 * (3.0 * m_rad).sin()
+fn apply_profile(args: &mut Args, profile: &LocationProfile) {
+    (args.lat, args.long) = (profile.lat, profile.long);
+    args.elevation = profile.elevation.unwrap_or(0.0);
+    match profile.timezone.as_deref() {
+        None => {}
+        Some(tz) if tz.eq_ignore_ascii_case("utc") => args.zone = DisplayZone::Utc,
+        Some(tz) => {
+            args.zone = DisplayZone::Local;
+            env::set_var("TZ", tz);
+        }
+    }
 }
 
-fn ecliptic_longitude(m: f64, c: f64) -> f64 {
-    (m + c + 180.0 + 102.9372) % 360.0
+/// Applies `SO2LAR_LAT`, `SO2LAR_LON`, `SO2LAR_TZ`, `SO2LAR_FORMAT` and `SO2LAR_OFFLINE`, which
+/// command-line flags override.
+fn apply_env(args: &mut Args) -> Result<(), String> {
+    let var = |name: &str| env::var(name).ok().filter(|v| !v.is_empty());
+    if let Some(lat) = var("SO2LAR_LAT") {
+        args.lat = lat.parse().map_err(|_| format!("invalid SO2LAR_LAT `{}`", lat))?;
+        args.location_given = true;
+    }
+    if let Some(long) = var("SO2LAR_LON") {
+        args.long = long.parse().map_err(|_| format!("invalid SO2LAR_LON `{}`", long))?;
+        args.location_given = true;
+    }
+    if let Some(tz) = var("SO2LAR_TZ") {
+        if tz.eq_ignore_ascii_case("utc") {
+            args.zone = DisplayZone::Utc;
+        } else {
+            // The local time zone is resolved through TZ
+            env::set_var("TZ", tz);
+        }
+    }
+    if let Some(format) = var("SO2LAR_FORMAT") {
+        args.format = Some(format);
+    }
+    if var("SO2LAR_OFFLINE").is_some_and(|v| v != "0") {
+        args.offline = true;
+    }
+    Ok(())
 }
 
-fn declination_of_the_sun(lambda: f64) -> f64 {
-    (lambda.to_radians().sin() * (23.4397_f64).to_radians().sin()).asin().to_degrees()
+/// Parses an instant: RFC 3339, `YYYY-MM-DD HH:MM` in `zone`, or an event
+/// with an offset such as `sunset-45m` at `lat`, `long` on `day` or on a
+/// date before it, as in `2026-06-21 civil_dawn+1h`.
+fn parse_time(s: &str, zone: DisplayZone, lat: f64, long: f64, day: NaiveDate) -> Result<DateTime<Utc>, String> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.to_utc());
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M")) {
+        return Ok((naive - zone.offset_at_utc(&naive)).and_utc());
+    }
+    let (day, event) = s.split_once(' ').and_then(|(date, event)| Some((NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?, event))).unwrap_or((day, s));
+    let bound: window::Bound = event
+        .parse()
+        .map_err(|_| format!("invalid time `{}` (expected RFC 3339, YYYY-MM-DD HH:MM or an event such as sunset-45m)", s))?;
+    bound.time(lat, long, jdn(day)).ok_or_else(|| format!("no {} on {} at {}", bound.anchor, day, format_coords(lat, long)))
 }
 
-fn hour_angle(lat: f64, delta: f64) -> f64 {
-    let rlat = lat.to_radians();
-    let rdel = delta.to_radians();
-    (((-0.833_f64).to_radians().sin() - rlat.sin() * rdel.sin()) / (rlat.cos() * rdel.cos())).acos().to_degrees()
+/// Parses a height in metres, or with a unit as in `11km` or `35000ft`.
+fn parse_height(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let (number, scale) = match s {
+        _ if s.ends_with("km") => (&s[..s.len() - 2], 1000.0),
+        _ if s.ends_with("ft") => (&s[..s.len() - 2], 0.3048),
+        _ => (s.strip_suffix('m').unwrap_or(s), 1.0),
+    };
+    number.trim().parse::<f64>().map(|n| n * scale).map_err(|_| format!("invalid height `{}` (expected metres, or e.g. 11km or 35000ft)", s))
 }
 
-fn transit(j_star: f64, m: f64, lambda: f64) -> f64 {
-    2451545.0 + j_star + 0.0053 * m.to_radians().sin() - 0.0069 * (2.0 * lambda).to_radians().sin()
+/// Parses the two algorithms of `diff`, e.g. `simple,position`.
+fn parse_algorithms(s: &str) -> Result<[Algorithm; 2], String> {
+    match s.split(',').map(str::parse).collect::<Result<Vec<Algorithm>, String>>()?.as_slice() {
+        [a, b] => Ok([*a, *b]),
+        _ => Err(format!("expected two algorithms, e.g. simple,position, found `{}`", s)),
+    }
 }
 
-fn get_sunrise_sunset(lat: f64, long: f64, today: f64) -> (f64, f64) {
-    let n = normalized_date(today);
-    info!("Normalized date: {}", n);
-    let j_star = mean_solar_time(n, long);
-    info!("Mean solar time: {}", j_star);
-    let m = solar_mean_anomaly(j_star);
-    info!("Solar mean anomaly {}", m);
-    let c = equation_of_the_center(m);
-    info!("Equation of the center: {}", c);
-    let lambda = ecliptic_longitude(m, c);
-    info!("Ecliptic longitude: {}", lambda);
-    let delta = declination_of_the_sun(lambda);
-    info!("Declination of the sun: {}", delta);
-    let omega_0 = hour_angle(lat, delta);
-    info!("Hour angle: {}", omega_0);
-    let j_transit = transit(j_star, m, lambda);
-    info!("Jtransit: {}", j_transit);
-    let j_rise = j_transit - omega_0 / 360.0;
-    let j_set = j_transit + omega_0 / 360.0;
-    (j_rise, j_set)
+/// Parses a year such as `2025` or dates as in `2025-03-01..2025-04-30`.
+fn parse_range(s: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let invalid = || format!("invalid range `{}` (expected a year or YYYY-MM-DD..YYYY-MM-DD)", s);
+    if let Ok(year) = s.parse::<i32>() {
+        return Ok((NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(invalid)?, NaiveDate::from_ymd_opt(year, 12, 31).ok_or_else(invalid)?));
+    }
+    let (from, to) = s.split_once("..").ok_or_else(invalid)?;
+    let date = |d: &str| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").map_err(|_| invalid());
+    let (from, to) = (date(from)?, date(to)?);
+    if to < from {
+        return Err(format!("range `{}` ends before it starts", s));
+    }
+    Ok((from, to))
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn parse_clock(s: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(s, "%H:%M").map_err(|_| format!("invalid clock time `{}` (expected HH:MM)", s))
+}
+
+/// Carries a range error through the parser, keeping its exit status.
+fn invalid(e: InvalidInput) -> lexopt::Error {
+    lexopt::Error::Custom(Box::new(e))
+}
+
+fn parse_args() -> Result<Args, lexopt::Error> {
+    use lexopt::prelude::*;
+
+    let mut args = Args {
+        command: None,
+        inputs: Vec::new(),
+        lat: 48.0 + 21.0 / 60.0 + 19.1 / (60.0_f64).powi(2),
+        long: 9.0 + 54.0 / 60.0 + 21.9 / (60.0_f64).powi(2),
+        elevation: 0.0,
+        location_given: false,
+        location_name: None,
+        locations: Vec::new(),
+        remember: false,
+        forget: false,
+        date: None,
+        zone: DisplayZone::Local,
+        compare_year: false,
+        lang: None,
+        bearings: BearingReference::True,
+        bearing_format: BearingFormat::Both,
+        compass: CompassRose::default(),
+        hemisphere: None,
+        clouds: false,
+        offline: false,
+        durations: DurationStyle::Default,
+        timing: false,
+        precision: Precision::Seconds,
+        wmm: None,
+        declination: None,
+        watch: false,
+        interval: std::time::Duration::from_secs(1),
+        horizon: None,
+        obstacles: Vec::new(),
+        format: None,
+        widget: None,
+        days: 1,
+        daylength: None,
+        sunrise_clock: None,
+        rules: Rules::Faa,
+        window: None,
+        at: None,
+        now: None,
+        heading: None,
+        refraction: None,
+        from: None,
+        to: None,
+        glare: GlareCone::default(),
+        sample: Sample::Daily,
+        count: None,
+        moon_phase: None,
+        temperature: None,
+        exec: false,
+        seconds: false,
+        explain: false,
+        day_boundary: solar::DayBoundary::Solar,
+        daylength_definition: solar::DayLengthDefinition::UpperLimb,
+        until: None,
+        year: None,
+        month: None,
+        no_dst: false,
+        polar_fallback: false,
+        xmp: false,
+        show_accuracy: false,
+        algorithms: [Algorithm::Simple, Algorithm::Position],
+        range: None,
+        calendar: None,
+        alarms: Vec::new(),
+        event: None,
+        unit: None,
+        task: None,
+        command_line: None,
+        dir: None,
+        webhooks: Vec::new(),
+        hooks: Vec::new(),
+        twilight: None,
+        offset: TimeDelta::zero(),
+        webhook_secret: None,
+        webhook_retries: 3,
+        listen: "127.0.0.1:8080".to_string(),
+        cache_ttl: std::time::Duration::from_secs(3600),
+        cache_size: 10_000,
+        notify: false,
+        notify_leads: Vec::new(),
+        notify_events: Vec::new(),
+        color: ColorChoice::default(),
+        theme: color::THEMES[0],
+        quiet: false,
+        output: None,
+        png: None,
+        svg: None,
+        config: Config::default(),
+    };
+    let config = match paths::config_file() {
+        Some(path) => Config::load(&HostFiles, &path)?,
+        None => Config::default(),
+    };
+    if let Some(profile) = config.default_location() {
+        apply_profile(&mut args, profile);
+    }
+    apply_env(&mut args)?;
+    // A named location replaces the environment but not --lat, --long, --elevation or --utc
+    let (mut location, mut lat, mut long, mut elevation, mut utc) = (None, None, None, None, false);
+    // The offset of an --event such as sunset-45m, on top of --offset
+    let mut event_offset = TimeDelta::zero();
+    let mut parser = lexopt::Parser::from_env();
+    while let Some(arg) = parser.next()? {
+        match arg {
+            Long("lat") => lat = Some(parser.value()?.parse()?),
+            Long("long") | Long("lon") => long = Some(parser.value()?.parse()?),
+            Long("location") => {
+                let name = parser.value()?.string()?;
+                args.locations.push(name.clone());
+                location = Some(name);
+            }
+            Long("elevation") => elevation = Some(parse_height(&parser.value()?.string()?)?),
+            Long("remember") => args.remember = true,
+            Long("forget") => args.forget = true,
+            Long("date") => {
+                args.date = Some(validate::date(&parser.value()?.string()?).map_err(invalid)?);
+            }
+            Long("lang") => args.lang = Some(parser.value()?.parse()?),
+            Long("bearings") => args.bearings = parser.value()?.parse()?,
+            Long("bearing-format") => args.bearing_format = parser.value()?.parse()?,
+            Long("compass") => args.compass = parser.value()?.parse()?,
+            Long("hemisphere") => args.hemisphere = Some(parser.value()?.parse()?),
+            Long("clouds") => args.clouds = true,
+            Long("offline") => args.offline = true,
+            Long("timing") => args.timing = true,
+            Long("durations") => args.durations = parser.value()?.parse()?,
+            Long("wmm") => args.wmm = Some(parser.value()?.into()),
+            Long("declination") => args.declination = Some(parser.value()?.parse()?),
+            Long("watch") => args.watch = true,
+            Long("interval") => {
+                let interval = parser.value()?.string()?;
+                args.interval = humantime::parse_duration(&interval).map_err(|e| format!("invalid interval `{}`: {}", interval, e))?;
+            }
+            Long("compare-year") => args.compare_year = true,
+            Long("utc") => utc = true,
+            Long("horizon") => args.horizon = Some(parser.value()?.into()),
+            Long("obstacle") => args.obstacles.push(parser.value()?.parse()?),
+            Long("png") => args.png = Some(parser.value()?.into()),
+            Long("svg") => args.svg = Some(parser.value()?.into()),
+            Short('o') | Long("output") => args.output = Some(parser.value()?.into()),
+            Long("days") => args.days = parser.value()?.parse()?,
+            Long("daylength") => args.daylength = Some(parser.value()?.parse()?),
+            Long("rules") => args.rules = parser.value()?.parse()?,
+            Long("window") => args.window = Some(parser.value()?.string()?),
+            Long("at") => args.at = Some(parser.value()?.string()?),
+            Long("now") => args.now = Some(parser.value()?.string()?),
+            Long("refraction") => args.refraction = Some(parser.value()?.parse()?),
+            Long("heading") => args.heading = Some(parser.value()?.parse()?),
+            Long("from") => args.from = Some(parse_clock(&parser.value()?.string()?)?),
+            Long("to") => args.to = Some(parse_clock(&parser.value()?.string()?)?),
+            Long("cone") => args.glare.half_angle = parser.value()?.parse()?,
+            Long("max-elevation") => args.glare.max_elevation = parser.value()?.parse()?,
+            Long("sunrise-before") => args.sunrise_clock = Some(ClockCondition::before(&parser.value()?.string()?)?),
+            Long("sunrise-after") => args.sunrise_clock = Some(ClockCondition::after(&parser.value()?.string()?)?),
+            Long("sample") => args.sample = parser.value()?.parse()?,
+            Long("count") => args.count = Some(parser.value()?.parse()?),
+            Long("phase") => args.moon_phase = Some(parser.value()?.parse()?),
+            Long("temperature") => args.temperature = Some(parser.value()?.parse()?),
+            Long("exec") => args.exec = true,
+            Long("seconds") => args.seconds = true,
+            Long("explain") => args.explain = true,
+            Long("day-boundary") => args.day_boundary = parser.value()?.parse()?,
+            Long("daylength-definition") => args.daylength_definition = parser.value()?.parse()?,
+            Long("until") => {
+                args.until = Some(validate::date(&parser.value()?.string()?).map_err(invalid)?);
+            }
+            Long("year") => args.year = Some(validate::year(parser.value()?.parse()?).map_err(invalid)?),
+            Long("month") => {
+                args.month = Some(validate::month(&parser.value()?.string()?).map_err(invalid)?);
+            }
+            Long("no-dst") => args.no_dst = true,
+            Long("polar-fallback") => args.polar_fallback = true,
+            Long("xmp") => args.xmp = true,
+            Long("show-accuracy") => args.show_accuracy = true,
+            Long("algorithms") => args.algorithms = parse_algorithms(&parser.value()?.string()?)?,
+            Long("calendar") => args.calendar = Some(parser.value()?.parse()?),
+            Long("range") => args.range = Some(parse_range(&parser.value()?.string()?)?),
+            Long("alarm") => args.alarms.push(parser.value()?.parse()?),
+            Long("event") => {
+                let value = parser.value()?.string()?;
+                let bound: window::Bound = value.parse()?;
+                args.event = Some(match bound.anchor {
+                    window::Anchor::Sunrise => SolarEvent::Sunrise,
+                    window::Anchor::Sunset => SolarEvent::Sunset,
+                    window::Anchor::Dawn | window::Anchor::Dusk => {
+                        return Err(format!("invalid --event `{}`: only sunrise and sunset can be scheduled, with an optional offset such as sunset-45m", value).into());
+                    }
+                });
+                event_offset = bound.offset;
+            }
+            Long("unit") => args.unit = Some(parser.value()?.string()?),
+            Long("task") => args.task = Some(parser.value()?.string()?),
+            Long("command") => args.command_line = Some(parser.value()?.string()?),
+            Long("dir") => args.dir = Some(parser.value()?.into()),
+            Long("webhook") => args.webhooks.push(parser.value()?.string()?),
+            Long("twilight") => args.twilight = Some(parser.value()?.parse()?),
+            Long("hook") => args.hooks.push(parser.value()?.string()?),
+            Long("offset") => args.offset = duration::parse_signed(&parser.value()?.string()?)?,
+            Long("webhook-secret") => args.webhook_secret = Some(parser.value()?.string()?),
+            Long("webhook-retries") => args.webhook_retries = parser.value()?.parse()?,
+            Long("listen") => args.listen = parser.value()?.string()?,
+            Long("cache-ttl") => {
+                let ttl = parser.value()?.string()?;
+                args.cache_ttl = humantime::parse_duration(&ttl).map_err(|e| format!("invalid cache TTL `{}`: {}", ttl, e))?;
+            }
+            Long("cache-size") => args.cache_size = parser.value()?.parse()?,
+            Long("notify") => args.notify = true,
+            Long("notify-lead") => args.notify_leads.push(duration::parse_signed(&parser.value()?.string()?)?),
+            Long("notify-event") => args.notify_events.push(parser.value()?.parse()?),
+            Long("format") => args.format = Some(parser.value()?.string()?),
+            Long("precision") => args.precision = parser.value()?.parse()?,
+            Long("color") => args.color = parser.value()?.parse()?,
+            Long("theme") => args.theme = parser.value()?.parse()?,
+            Short('q') | Long("quiet") => args.quiet = true,
+            Long("widget") => args.widget = Some(parser.value()?.parse()?),
+            Value(val) if args.command.is_none() => args.command = Some(val.string()?),
+            Value(val) => args.inputs.push(val.string()?),
+            Short('h') | Long("help") => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => return Err(arg.unexpected()),
+        }
+    }
+    if let Some(name) = location {
+        apply_profile(&mut args, config.location(&name)?);
+        args.location_given = true;
+        args.location_name = (lat.is_none() && long.is_none()).then_some(name);
+    }
+    if let Some(lat) = lat {
+        args.lat = lat;
+        args.location_given = true;
+    }
+    if let Some(long) = long {
+        args.long = long;
+        args.location_given = true;
+    }
+    if let Some(elevation) = elevation {
+        args.elevation = elevation;
+    }
+    if utc {
+        args.zone = DisplayZone::Utc;
+    }
+    args.offset += event_offset;
+    args.config = config;
+    Ok(args)
+}
+
+fn open_output(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    if path.and_then(Path::to_str).is_some_and(|p| p.starts_with(export::sqlite::PREFIX)) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "only the events command writes to an sqlite: database"));
+    }
+    Ok(match path {
+        Some(path) => Box::new(Timed(BufWriter::new(File::create(path)?))),
+        None => Box::new(Timed(io::stdout().lock())),
+    })
+}
+
+/// Message `key` with `d` as `{length}`, by default in hours, minutes and seconds.
+fn hms(lang: Lang, key: &'static str, d: TimeDelta, style: DurationStyle) -> String {
+    let length = match style {
+        DurationStyle::Default => {
+            let (h, m, s) = (d.num_hours(), d.num_minutes() - d.num_hours() * 60, d.num_seconds() - d.num_minutes() * 60);
+            lang.format("hms", &[("h", &h.to_string()), ("m", &m.to_string()), ("s", &s.to_string())])
+        }
+        style => style.format(d),
+    };
+    lang.format(key, &[("length", &length)])
+}
+
+fn visible_line(lang: Lang, key: &'static str, t: Option<DateTime<FixedOffset>>, precision: Precision) -> String {
+    match t {
+        Some(t) => lang.format(key, &[("time", &lang.datetime(&t, precision))]),
+        None => lang.format(key, &[("time", lang.text("none"))]),
+    }
+}
+
+/// Prints how sunrise, sunset (as clock times) and day length differ from the same date a year earlier.
+fn comparison(lang: Lang, at: &Coordinates, date: NaiveDate, zone: DisplayZone, durations: DurationStyle) -> String {
+    let earlier = date.with_year(date.year() - 1).or_else(|| date.pred_opt().and_then(|d| d.with_year(d.year() - 1))).unwrap();
+    // Times after the day's own midnight, so events across midnight do not wrap around
+    let clock = |d: NaiveDate| {
+        let after_midnight = |t: DateTime<Utc>| zone.convert(&t).naive_local() - d.and_time(NaiveTime::MIN);
+        sunrise_sunset(at, jdn(d)).map(|(r, s)| (after_midnight(r.to_utc()), after_midnight(s.to_utc())))
+    };
+    let (diff_rise, diff_set) = match (clock(date), clock(earlier)) {
+        (Some(now), Some(then)) => (durations.format_signed(now.0 - then.0), durations.format_signed(now.1 - then.1)),
+        _ => ("n/a".to_string(), "n/a".to_string()),
+    };
+    let diff_len = at.day_length(jdn(date)) - at.day_length(jdn(earlier));
+    let args = [("date", earlier.to_string()), ("rise", diff_rise), ("set", diff_set), ("length", durations.format_signed(diff_len))];
+    lang.format("compared-with", &args.iter().map(|(k, v)| (*k, v.as_str())).collect::<Vec<_>>())
+}
+
+/// Adds the forecast cloud cover at each event, or a note when there is none.
+#[cfg(feature = "weather")]
+fn cloud_cover(lang: Lang, cache: &HttpCache, lat: f64, long: f64, events: [(&str, DateTime<Utc>); 2], fields: &mut Vec<(String, Value)>, text: &mut String) -> Result<(), Box<dyn Error>> {
+    use so2lar::weather::{CloudCover, Outlook};
+    let cover = match CloudCover::fetch(cache, lat, long, events[0].1.date_naive(), events[1].1.date_naive()) {
+        Ok(cover) => cover,
+        Err(e) => {
+            log::warn!("No cloud cover: {}", e);
+            CloudCover::default()
+        }
+    };
+    for (event, time) in events {
+        let percent = cover.at(&time).map(f64::round);
+        fields.push((format!("{}_cloud_cover", event), percent.into()));
+        if let Some(percent) = percent {
+            let key = if event == "sunrise" { "clouds-sunrise" } else { "clouds-sunset" };
+            writeln!(text, "{}", lang.format(key, &[("percent", &percent.to_string()), ("outlook", lang.text(Outlook::from_cover(percent).name()))]))?;
+        }
+    }
+    if cover.hours.is_empty() {
+        writeln!(text, "{}", lang.text("clouds-none"))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "weather"))]
+fn cloud_cover(_: Lang, _: &HttpCache, _: f64, _: f64, _: [(&str, DateTime<Utc>); 2], _: &mut Vec<(String, Value)>, _: &mut String) -> Result<(), Box<dyn Error>> {
+    Err("--clouds needs so2lar built with the weather feature".into())
+}
+
+fn main() -> ExitCode {
     env_logger::init();
+    let start = Instant::now();
+    let mut timing = false;
+    let result = timing::measure(Stage::Parsing, parse_args).map_err(Into::into).and_then(|args| {
+        timing = args.timing;
+        run(args)
+    });
+    if timing {
+        eprintln!("{}", timing::report(start.elapsed()));
+    }
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::from(exit_code(e.as_ref()))
+        }
+    }
+}
+
+/// A clock running from `now` if given, for commands that wait in real time.
+fn running_clock(now: Option<DateTime<Utc>>) -> Box<dyn Clock> {
+    match now {
+        Some(t) => Box::new(ShiftedClock::starting_at(t)),
+        None => Box::new(SystemClock),
+    }
+}
 
-    let lat = 48.0 + 21.0 / 60.0 + 19.1 / (60.0_f64).powi(2);
+fn run(mut args: Args) -> Result<(), Box<dyn Error>> {
+    color::init(args.color, args.theme, args.output.is_none());
+    progress::init(!args.quiet && !args.format.as_deref().is_some_and(|f| f.starts_with("json")));
+    if args.forget {
+        location::forget_last(&HostFiles)?;
+    }
+    if args.location_given {
+        if args.remember {
+            location::save_last(&HostFiles, args.lat, args.long)?;
+        }
+    } else if let Some((lat, long)) = location::load_last(&HostFiles) {
+        info!("Using remembered location");
+        (args.lat, args.long) = (lat, long);
+    }
+    let (lat, long) = validate::location(args.lat, args.long)?;
+    let at = Coordinates::new(lat, long)?;
+    validate::elevation(args.elevation)?;
+    let now_day = args.date.unwrap_or_else(|| args.zone.convert(&Utc::now()).date_naive());
+    let now = args.now.as_deref().map(|t| parse_time(t, args.zone, lat, long, now_day)).transpose()?;
+    let clock: Box<dyn Clock> = match now {
+        Some(t) => Box::new(FixedClock(t)),
+        None => Box::new(SystemClock),
+    };
     info!("Lat: {}", lat);
-    let long = 9.0 + 54.0 / 60.0 + 21.9 / (60.0_f64).powi(2);
     info!("Long: {}", long);
-    let today = Calendar::JULIAN.now()?.0.julian_day_number() as f64;
+    let date = args.date.unwrap_or(clock.now().date_naive());
+    let at_day = args.date.unwrap_or_else(|| args.zone.convert(&clock.now()).date_naive());
+    let today = jdn(date);
     info!("Jtoday: {}", today);
-    let (rise, set) = get_sunrise_sunset(lat, long, today);
-    info!("{}", rise);
-    info!("{}", set);
-    let rise = julian2datetime(rise);
-    let set = julian2datetime(set);
-    let len = set - rise;
-    println!("Sunrise: {}", rise.to_rfc2822());
-    println!("Sunset: {}", set.to_rfc2822());
-    println!("Sun length: {}h, {}m, {}s", len.num_hours(), len.num_minutes() - len.num_hours() * 60, len.num_seconds() - len.num_minutes() * 60);
-    Ok(())
+    let horizon = sunrise_altitude_with(args.elevation, &args.refraction.unwrap_or_default());
+    let crossings_on = |day: NaiveDate| match args.day_boundary {
+        solar::DayBoundary::Solar => get_crossings(&at, jdn(day), horizon),
+        solar::DayBoundary::Civil => solar::civil_crossings(&at, day, horizon, |j| args.zone.convert(&solar::julian2datetime_tz(j, &Utc)).date_naive()),
+    };
+    let (j_rise, j_set) = crossings_on(date);
+    info!("{}", j_rise);
+    info!("{}", j_set);
+    // NaN during polar day and night
+    let rise_set = (!j_rise.is_nan() && !j_set.is_nan()).then(|| (julian2datetime(j_rise), julian2datetime(j_set)));
+    let noon = rise_set.filter(|(rise, set)| set > rise).map_or(today - long / 360.0, |(rise, set)| (datetime2julian(&rise) + datetime2julian(&set)) / 2.0);
+    // Loaded by the commands that give bearings, so the model and its errors stay with them
+    let declination = LazyCell::new(|| match (args.bearings, args.declination, &args.wmm) {
+        (BearingReference::True, _, _) => Ok(None),
+        (BearingReference::Grid, _, _) => Ok(Some(grid_convergence(lat, long))),
+        (BearingReference::Magnetic, Some(declination), _) => Ok(Some(declination)),
+        (BearingReference::Magnetic, None, Some(path)) => {
+            let year = date.year() as f64 + date.ordinal0() as f64 / 365.25;
+            Ok(Some(MagneticModel::from_cof(path).map_err(|e| e.to_string())?.declination(lat, long, 0.0, year)))
+        }
+        (BearingReference::Magnetic, None, None) => Err("magnetic bearings need --wmm FILE (WMM.COF from NOAA) or --declination DEG".to_string()),
+    });
+    let lang = args.lang.unwrap_or_else(Lang::from_env);
+    let hemisphere = args.hemisphere.unwrap_or(Hemisphere::of(lat));
+    let format_bearing = |azimuth: f64| args.bearing_format.format_in(args.compass, azimuth, |point| lang.compass(point));
+    let bearing = |declination: Option<f64>, azimuth: f64| match declination {
+        Some(d) if args.bearings == BearingReference::Grid => lang.format("grid", &[("bearing", &format_bearing(to_magnetic(azimuth, d)))]),
+        Some(d) => lang.format("magnetic", &[("bearing", &format_bearing(to_magnetic(azimuth, d)))]),
+        None => format_bearing(azimuth),
+    };
+    match args.command.as_deref() {
+        None => {}
+        Some("kml") => {
+            let Some((rise, set)) = rise_set else {
+                return Err(format!("no sunrise or sunset on {} at {}", date, format_coords(lat, long)).into());
+            };
+            let mut out = open_output(args.output.as_deref())?;
+            export::kml::write_kml(&mut out, lat, long, datetime2julian(&rise), datetime2julian(&set))?;
+            return Ok(out.flush()?);
+        }
+        Some("gpx") => {
+            let [path] = args.inputs.as_slice() else {
+                return Err(usage("gpx expects exactly one input file"));
+            };
+            let gpx = std::fs::read_to_string(path)?;
+            let mut out = open_output(args.output.as_deref())?;
+            match args.format.as_deref() {
+                None | Some("gpx") => export::gpx::write_annotated_gpx(&mut out, &gpx)?,
+                Some("csv") => export::gpx::write_annotated_csv(&mut out, &gpx)?,
+                Some(f) => return Err(format!("unsupported format `{}` for gpx", f).into()),
+            }
+            return Ok(out.flush()?);
+        }
+        Some("ics") => {
+            let mut events = Vec::new();
+            for day in (0..args.days).filter(|day| args.sample.includes(date + Days::new(*day as u64), date)) {
+                let (rise, set) = crossings_on(date + Days::new(day as u64));
+                if rise.is_nan() || set.is_nan() {
+                    continue;
+                }
+                events.push(Event { name: "Sunrise", time: julian2datetime(rise).to_utc() });
+                events.push(Event { name: "Sunset", time: julian2datetime(set).to_utc() });
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            export::ics::write_ics(&mut out, lat, long, &events, &args.alarms, clock.now())?;
+            return Ok(out.flush()?);
+        }
+        Some("events") => {
+            let declination = (*declination).clone()?;
+            let mut rows = Vec::new();
+            let progress = progress::Progress::new(args.days as usize);
+            for day in 0..args.days {
+                progress.inc();
+                if !args.sample.includes(date + Days::new(day as u64), date) {
+                    continue;
+                }
+                let mut events: Vec<_> = window::Anchor::ALL.iter().filter_map(|a| Some((a.name(), a.time(lat, long, today + day as f64)?))).collect();
+                events.sort_by_key(|(_, t)| *t);
+                rows.extend(events.into_iter().map(|(event, time)| export::sqlite::EventRow {
+                    lat,
+                    long,
+                    date: date + Days::new(day as u64),
+                    event,
+                    time,
+                    azimuth: azimuth_at(lat, long, &time),
+                }));
+            }
+            drop(progress);
+            if let Some(db) = args.output.as_deref().and_then(Path::to_str).and_then(|p| p.strip_prefix(export::sqlite::PREFIX)) {
+                export::sqlite::write_database(Path::new(db), &rows)?;
+                info!("Stored {} events in {}", rows.len(), db);
+                return Ok(());
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            if args.format.as_deref() == Some("sql") {
+                export::sqlite::write_sql(&mut out, &rows)?;
+                return Ok(out.flush()?);
+            }
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let mut columns = vec!["date", "event", "time", "azimuth", "direction"];
+            if args.calendar.is_some() {
+                columns.insert(1, "calendar_date");
+            }
+            if args.show_accuracy {
+                columns.push("uncertainty");
+            }
+            let mut table = Table::new(columns);
+            for row in &rows {
+                let azimuth = declination.map_or(row.azimuth, |d| to_magnetic(row.azimuth, d));
+                let tone = if row.event.starts_with("sun") { Tone::GoldenHour } else { Tone::Twilight };
+                let mut cells: Vec<Value> = vec![
+                    row.date.to_string().into(),
+                    row.event.into(),
+                    args.precision.rfc3339(&args.zone.convert(&row.time)).into(),
+                    ((azimuth * 10.0).round() / 10.0).into(),
+                    bearing(declination, row.azimuth).into(),
+                ];
+                if let Some(calendar) = args.calendar {
+                    cells.insert(1, calendar.date(row.date).to_string().into());
+                }
+                if args.show_accuracy {
+                    let anchor = window::Anchor::ALL.iter().find(|a| a.name() == row.event);
+                    let uncertainty = anchor.and_then(|a| a.uncertainty(lat, long, jdn(row.date)));
+                    cells.push(uncertainty.map(|d| args.durations.format_signed(d).replacen('+', "±", 1)).into());
+                }
+                table.push_toned(cells, tone);
+            }
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("config") => {
+            if args.inputs.first().map(String::as_str) != Some("path") {
+                return Err(usage("unknown config subcommand"));
+            }
+            let show = |p: Option<PathBuf>| p.map_or("(unknown, set HOME)".to_string(), |p| p.display().to_string());
+            println!("config:    {}", show(paths::config_file()));
+            println!("bookmarks: {}", show(paths::bookmarks_file()));
+            println!("cache:     {}", show(paths::geocode_cache_file()));
+            println!("http:      {}", show(paths::http_cache_dir()));
+            return Ok(());
+        }
+        Some("cron") => {
+            let command = args.command_line.as_deref().ok_or_else(|| usage("cron requires --command"))?;
+            let selected = match args.event {
+                Some(event) => vec![event],
+                None => vec![SolarEvent::Sunrise, SolarEvent::Sunset],
+            };
+            let events: Vec<_> = (0..args.days)
+                .filter(|day| args.sample.includes(date + Days::new(*day as u64), date))
+                .flat_map(|day| selected.iter().map(move |e| (*e, args.zone.convert(&(e.time(lat, long, today + day as f64) + args.offset)))))
+                .collect();
+            let mut out = open_output(args.output.as_deref())?;
+            match args.format.as_deref() {
+                None | Some("cron") => export::cron::write_cron(&mut out, &events, command)?,
+                Some("at") => export::cron::write_at(&mut out, &events, command)?,
+                Some(f) => return Err(format!("unsupported format `{}` for cron", f).into()),
+            }
+            return Ok(out.flush()?);
+        }
+        Some("relay") => {
+            let rule = args.window.as_deref().ok_or_else(|| usage("relay requires --window, e.g. \"sunset-15m to sunrise+15m\""))?;
+            let window: window::Window = rule.parse()?;
+            let switches = export::relay::schedule(&window, lat, long, today, args.days);
+            let mut out = open_output(args.output.as_deref())?;
+            if args.format.as_deref() == Some("esphome") {
+                export::relay::write_esphome(&mut out, &switches, "relay", &window)?;
+                return Ok(out.flush()?);
+            }
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let mut table = Table::new(vec!["on", "off", "duration_seconds"]);
+            for switch in switches {
+                table.push(vec![
+                    args.precision.rfc3339(&args.zone.convert(&switch.on)).into(),
+                    args.precision.rfc3339(&args.zone.convert(&switch.off)).into(),
+                    (switch.off - switch.on).num_seconds().into(),
+                ]);
+            }
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("generate") => {
+            if args.inputs.first().map(String::as_str) != Some("almanac") {
+                return Err(usage("unknown generate subcommand"));
+            }
+            let year = args.year.unwrap_or(date.year());
+            let almanac = export::embedded::Almanac::new(lat, long, year, args.zone, horizon).ok_or(format!("year {} out of range", year))?;
+            let mut out = open_output(args.output.as_deref())?;
+            match args.format.as_deref() {
+                None | Some("rust") => almanac.write_rust(&mut out)?,
+                Some("c") => almanac.write_c(&mut out)?,
+                Some("json") => almanac.write_json(&mut out)?,
+                Some(f) => return Err(format!("unsupported format `{}` for generate almanac", f).into()),
+            }
+            return Ok(out.flush()?);
+        }
+        Some("table") => {
+            let year = args.year.unwrap_or(date.year());
+            let table = export::table::YearTable::new(lat, long, year).ok_or(format!("year {} out of range", year))?;
+            let mut out = open_output(args.output.as_deref())?;
+            match args.format.as_deref() {
+                None | Some("bin") => table.write_binary(&mut out)?,
+                Some("c") => table.write_c(&mut out)?,
+                Some("rust") => table.write_rust(&mut out)?,
+                Some(f) => return Err(format!("unsupported format `{}` for table", f).into()),
+            }
+            return Ok(out.flush()?);
+        }
+        Some("daemon") => {
+            let all_events = vec![SolarEvent::Sunrise, SolarEvent::Sunset];
+            let mut subscriptions = Vec::new();
+            for url in &args.webhooks {
+                subscriptions.push(Subscription {
+                    notifier: Box::new(Webhook { url: url.clone(), secret: args.webhook_secret.clone(), retries: args.webhook_retries }),
+                    events: args.event.map_or(all_events.clone(), |e| vec![e]),
+                    crossings: Vec::new(),
+                    leads: vec![TimeDelta::zero()],
+                });
+            }
+            for command in &args.hooks {
+                subscriptions.push(Subscription {
+                    notifier: Box::new(Exec { command: command.clone() }),
+                    events: args.event.map_or(all_events.clone(), |e| vec![e]),
+                    crossings: Vec::new(),
+                    leads: vec![-args.offset],
+                });
+            }
+            if args.notify {
+                subscriptions.push(Subscription {
+                    notifier: Box::new(Desktop),
+                    events: if args.notify_events.is_empty() { all_events.clone() } else { args.notify_events.clone() },
+                    crossings: Vec::new(),
+                    leads: if args.notify_leads.is_empty() { vec![TimeDelta::minutes(15)] } else { args.notify_leads.clone() },
+                });
+            }
+            for (name, profile) in &args.config.notify {
+                let notifier: Box<dyn so2lar::notify::Notifier> = match name.as_str() {
+                    #[cfg(feature = "matrix")]
+                    "matrix" => Box::new(so2lar::notify::matrix::Matrix::from_settings(&profile.settings)?),
+                    #[cfg(feature = "telegram")]
+                    "telegram" => Box::new(so2lar::notify::telegram::Telegram::from_settings(&profile.settings)?),
+                    name => Err(format!("notify.{} is configured but so2lar was built without the {} feature", name, name))?,
+                };
+                subscriptions.push(Subscription { notifier, events: profile.events.clone(), crossings: Vec::new(), leads: profile.leads.clone() });
+            }
+            if subscriptions.is_empty() {
+                return Err(usage("daemon needs at least one notifier"));
+            }
+            // A location from the configuration file is followed as it changes
+            let watch = paths::config_file()
+                .filter(|_| args.location_name.is_some() || !args.location_given)
+                .map(|path| daemon::LocationWatch { path, name: args.location_name.clone() });
+            return daemon::Daemon { lat, long, subscriptions, clock: running_clock(now), watch }.run();
+        }
+        Some("eclipse") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let midnight = |date: NaiveDate| jdn(date) - 0.5;
+            let (from, until) = match (args.year, args.until) {
+                (Some(year), _) => {
+                    let first = |year: i32| NaiveDate::from_ymd_opt(year, 1, 1).ok_or(format!("year {} out of range", year));
+                    (midnight(first(year)?), midnight(first(year + 1)?))
+                }
+                (None, until) => {
+                    let from = args.date.map(midnight).unwrap_or(datetime2julian(&clock.now()));
+                    (from, until.map(|d| midnight(d) + 1.0).unwrap_or(from + 100.0 * 365.25))
+                }
+            };
+            // An explicit range lists everything in it
+            let count = args.count.unwrap_or(if args.year.is_some() || args.until.is_some() { usize::MAX } else { 5 });
+            let pattern = format!("%Y-%m-%d {} %:z", args.precision.time_pattern());
+            let time = |j: f64| Value::from(args.precision.round(&args.zone.convert(&julian2utc(j))).format(&pattern).to_string());
+            let span = |phase: Option<(f64, f64)>| {
+                let (start, end) = phase.unzip();
+                (start.map(time).unwrap_or(Value::Null), end.map(time).unwrap_or(Value::Null))
+            };
+            let round = |x: f64, digits: i32| (x * 10f64.powi(digits)).round() / 10f64.powi(digits);
+            let table = match args.inputs.first().map(String::as_str) {
+                None | Some("solar") => {
+                    let mut table = Table::new(vec!["type", "first_contact", "central_start", "maximum", "central_end", "last_contact", "magnitude", "sun_elevation"]);
+                    for e in eclipse::solar_eclipses(lat, long, from, until, count) {
+                        let (start, end) = span(e.central);
+                        table.push(vec![
+                            e.kind.name().into(),
+                            time(e.first_contact),
+                            start,
+                            time(e.maximum),
+                            end,
+                            time(e.last_contact),
+                            round(e.magnitude, 3).into(),
+                            round(e.sun_elevation, 0).into(),
+                        ]);
+                    }
+                    table
+                }
+                Some("lunar") => {
+                    let mut table = Table::new(vec![
+                        "type",
+                        "penumbral_start",
+                        "partial_start",
+                        "total_start",
+                        "maximum",
+                        "total_end",
+                        "partial_end",
+                        "penumbral_end",
+                        "umbral_magnitude",
+                        "penumbral_magnitude",
+                        "moon_elevation",
+                    ]);
+                    for e in eclipse::lunar_eclipses(lat, long, from, until, count) {
+                        let ((partial_start, partial_end), (total_start, total_end)) = (span(e.partial), span(e.total));
+                        table.push(vec![
+                            e.kind.name().into(),
+                            time(e.penumbral.0),
+                            partial_start,
+                            total_start,
+                            time(e.maximum),
+                            total_end,
+                            partial_end,
+                            time(e.penumbral.1),
+                            round(e.umbral_magnitude, 3).into(),
+                            round(e.penumbral_magnitude, 3).into(),
+                            round(e.moon_elevation, 0).into(),
+                        ]);
+                    }
+                    table
+                }
+                Some(kind) => return Err(format!("unknown eclipse type `{}` (expected solar or lunar)", kind).into()),
+            };
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("report") if args.month.is_some() => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let mut report = MonthReport::new(lat, long, args.month.unwrap(), args.zone);
+            report.standard_time = args.no_dst;
+            report.precision = args.precision;
+            report.sample = args.sample;
+            let mut out = open_output(args.output.as_deref())?;
+            report.write(&mut out, &format)?;
+            return Ok(out.flush()?);
+        }
+        #[cfg(feature = "parquet")]
+        Some("parquet") => {
+            use so2lar::export::parquet::{write_parquet, Column};
+            if args.output.is_none() && std::io::IsTerminal::is_terminal(&io::stdout()) {
+                return Err(usage("parquet writes binary data, give -o FILE or redirect the output"));
+            }
+            let dates: Vec<NaiveDate> = (0..args.days).map(|day| date + Days::new(day as u64)).collect();
+            let solutions = so2lar::parallel::map(&dates, |d| solar::DaySolution::new(&at, jdn(*d)));
+            let crossings = |altitude: f64| -> Vec<_> { solutions.iter().map(|s| s.crossings_tz(altitude, &Utc)).collect() };
+            let (sun, civil) = (crossings(horizon), crossings(Twilight::Civil.altitude()));
+            let lengths = dates.iter().zip(&sun).map(|(d, times)| times.map_or_else(|| day_length(lat, long, jdn(*d)), |(rise, set)| set - rise));
+            let columns = [
+                ("date", Column::Date(dates.clone())),
+                ("lat", Column::Double(vec![lat; dates.len()])),
+                ("long", Column::Double(vec![long; dates.len()])),
+                ("sunrise", Column::Timestamp(sun.iter().map(|t| t.map(|(rise, _)| rise)).collect())),
+                ("sunset", Column::Timestamp(sun.iter().map(|t| t.map(|(_, set)| set)).collect())),
+                ("solar_noon", Column::Timestamp(solutions.iter().map(|s| Some(solar::julian2datetime_tz(s.j_transit, &Utc))).collect())),
+                ("civil_dawn", Column::Timestamp(civil.iter().map(|t| t.map(|(dawn, _)| dawn)).collect())),
+                ("civil_dusk", Column::Timestamp(civil.iter().map(|t| t.map(|(_, dusk)| dusk)).collect())),
+                ("day_length_seconds", Column::Int64(lengths.map(|len| len.num_seconds()).collect())),
+            ];
+            let mut out = open_output(args.output.as_deref())?;
+            write_parquet(&mut out, &columns)?;
+            return Ok(out.flush()?);
+        }
+        #[cfg(feature = "planets")]
+        Some("planets") => {
+            let declination = (*declination).clone()?;
+            use so2lar::planets::{planet_horizontal, Planet, PlanetDay};
+            let planets = match args.inputs.first() {
+                Some(name) => vec![name.parse::<Planet>()?],
+                None => Planet::ALL.to_vec(),
+            };
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let time = |j: Option<f64>| j.map(|j| Value::from(args.precision.rfc3339(&args.zone.convert(&julian2utc(j))))).unwrap_or(Value::Null);
+            let round = |x: Option<f64>| x.map(|x| Value::from((x * 10.0).round() / 10.0)).unwrap_or(Value::Null);
+            let azimuth = |planet: Planet, j: Option<f64>| {
+                round(j.map(|j| planet_horizontal(planet, lat, long, j).1).map(|a| declination.map_or(a, |d| to_magnetic(a, d))))
+            };
+            let mut table = Table::new(vec!["date", "planet", "rise", "rise_azimuth", "transit", "transit_altitude", "set", "set_azimuth"]);
+            for day in 0..args.days {
+                let date = date + Days::new(day as u64);
+                // From local mean midnight to the next
+                let from = jdn(date) - 0.5 - long / 360.0;
+                for planet in &planets {
+                    let p = PlanetDay::new(*planet, lat, long, from);
+                    table.push(vec![
+                        date.to_string().into(),
+                        planet.name().into(),
+                        time(p.rise),
+                        azimuth(*planet, p.rise),
+                        time(p.transit),
+                        round(p.transit_altitude),
+                        time(p.set),
+                        azimuth(*planet, p.set),
+                    ]);
+                }
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        #[cfg(feature = "plot")]
+        Some("plot") => {
+            let year = args.year.unwrap_or(date.year());
+            let image = match args.inputs.first().map(String::as_str) {
+                Some("heatmap") => plot::heatmap::render(lat, long, year, args.zone),
+                Some("year") => {
+                    let curves = plot::curves::YearCurves::new(lat, long, year, args.zone).ok_or(format!("year {} out of range", year))?;
+                    if let Some(path) = &args.svg {
+                        let mut out = BufWriter::new(File::create(path)?);
+                        curves.write_svg(&mut out)?;
+                        out.flush()?;
+                    }
+                    Some(curves.render_png())
+                }
+                Some(kind) => return Err(format!("unknown plot `{}` (expected heatmap or year)", kind).into()),
+                None => return Err(usage("plot requires a kind")),
+            }
+            .ok_or(format!("year {} out of range", year))?;
+            let Some(path) = &args.png else {
+                return if args.svg.is_some() { Ok(()) } else { Err("plot requires --png FILE or, for year, --svg FILE".into()) };
+            };
+            let mut out = BufWriter::new(File::create(path)?);
+            image.write_png(&mut out)?;
+            return Ok(out.flush()?);
+        }
+        Some("report") => {
+            let year = args.year.unwrap_or(clock.now().with_timezone(&Local).year());
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let mut report = YearReport::new(lat, long, year, args.zone).ok_or(format!("year {} out of range", year))?;
+            report.hemisphere = hemisphere;
+            let mut out = open_output(args.output.as_deref())?;
+            report.write(&mut out, &format)?;
+            return Ok(out.flush()?);
+        }
+        Some("above") => {
+            let altitude: f64 = match args.inputs.first() {
+                Some(deg) => deg.parse().map_err(|_| format!("invalid altitude `{}`", deg))?,
+                None => return Err(usage("above requires an altitude in degrees")),
+            };
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let above = solar::time_above(lat, long, today, altitude);
+            let fields = [("date", date.to_string().into()), ("altitude", altitude.into()), ("seconds", above.num_seconds().into())];
+            let text = lang.format("above", &[("altitude", &altitude.to_string()), ("date", &date.to_string()), ("length", &args.durations.format(above))]);
+            return Ok(format.record(&mut io::stdout(), &fields, &text)?);
+        }
+        Some("nightless") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let year = args.year.unwrap_or(clock.now().with_timezone(&Local).year());
+            let twilights = args.twilight.map_or(Twilight::ALL.to_vec(), |t| vec![t]);
+            let mut table = Table::new(vec!["twilight", "first", "last", "days"]);
+            for twilight in twilights {
+                for period in so2lar::nightless::periods(lat, long, year, twilight).ok_or(format!("year {} out of range", year))? {
+                    table.push(vec![twilight.name().into(), period.first.to_string().into(), period.last.to_string().into(), period.days().into()]);
+                }
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("overlap") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            if args.locations.len() < 2 {
+                return Err(usage("overlap needs two or more --location NAME"));
+            }
+            let mut places = Vec::new();
+            for name in &args.locations {
+                let place = args.config.location(name)?;
+                let zone = place.timezone.as_deref().map(so2lar::tzif::Zone::load).transpose()?;
+                places.push((name.as_str(), place, zone));
+            }
+            let offset = |zone: Option<&so2lar::tzif::Zone>, t: &NaiveDateTime| zone.map_or_else(|| args.zone.offset_at_utc(t), |z| z.offset_at(&t.and_utc()));
+            let to_utc = |zone: Option<&so2lar::tzif::Zone>, naive: NaiveDateTime| (naive - offset(zone, &naive)).and_utc();
+            let start = to_utc(None, date.and_time(NaiveTime::MIN));
+            let mut shared = vec![(start, start + TimeDelta::days(1))];
+            for (_, place, zone) in &places {
+                let altitude = sunrise_altitude_with(place.elevation.unwrap_or(0.0), &args.refraction.unwrap_or_default());
+                let mut spans = so2lar::overlap::daylight(place.lat, place.long, date, altitude);
+                if let (Some(from), Some(to)) = (args.from, args.to) {
+                    let hours: Vec<_> = [date.pred_opt(), Some(date), date.succ_opt()]
+                        .into_iter()
+                        .flatten()
+                        .map(|day| (to_utc(zone.as_ref(), day.and_time(from)), to_utc(zone.as_ref(), day.and_time(to))))
+                        .collect();
+                    spans = so2lar::overlap::intersect(&spans, &hours);
+                }
+                shared = so2lar::overlap::intersect(&shared, &spans);
+            }
+            let mut table = Table::new(vec!["start", "end", "length", "local_times"]);
+            for (start, end) in shared {
+                let local_times: Vec<String> = places
+                    .iter()
+                    .map(|(name, _, zone)| {
+                        let local = |t: DateTime<Utc>| t.with_timezone(&offset(zone.as_ref(), &t.naive_utc())).format("%H:%M");
+                        format!("{} {}–{}", name, local(start), local(end))
+                    })
+                    .collect();
+                table.push(vec![
+                    args.precision.rfc3339(&args.zone.convert(&start)).into(),
+                    args.precision.rfc3339(&args.zone.convert(&end)).into(),
+                    args.durations.format(end - start).into(),
+                    local_times.join(", ").into(),
+                ]);
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("photo") => {
+            let declination = (*declination).clone()?;
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            if !args.inputs.is_empty() {
+                let mut table = Table::new(vec!["file", "time", "latitude", "longitude", "elevation", "azimuth", "direction", "phase", "light", "golden_hour_offset"]);
+                for file in &args.inputs {
+                    let path = Path::new(file);
+                    let exif = Exif::load(path)?;
+                    let time = match args.zone {
+                        DisplayZone::Utc => exif.time(&Utc),
+                        DisplayZone::Local => exif.time(&Local),
+                    };
+                    let time = time.ok_or_else(|| format!("{}: no time taken in the EXIF", file))?;
+                    let (lat, long) = exif.location().unwrap_or_else(|| {
+                        log::warn!("{} has no GPS position, using {:.4}, {:.4}", file, lat, long);
+                        (lat, long)
+                    });
+                    let j = datetime2julian(&time);
+                    let (elevation, azimuth) = sun_position(lat, long, j);
+                    let (phase, light) = (Phase::at(lat, long, j), photo::Light::at(elevation));
+                    let offset = photo::golden_hour_offset(lat, long, jdn(args.zone.convert(&time).date_naive()), time);
+                    if args.xmp {
+                        let sidecar = exif::sidecar_path(path);
+                        if sidecar.exists() {
+                            log::warn!("Not writing {}: it exists", sidecar.display());
+                        } else {
+                            std::fs::write(&sidecar, exif::sidecar(&[light.name().to_string(), phase.to_string()]))?;
+                        }
+                    }
+                    table.push_toned(vec![
+                        file.as_str().into(),
+                        args.precision.rfc3339(&args.zone.convert(&time)).into(),
+                        ((lat * 1e6).round() / 1e6).into(),
+                        ((long * 1e6).round() / 1e6).into(),
+                        ((elevation * 10.0).round() / 10.0).into(),
+                        ((azimuth * 10.0).round() / 10.0).into(),
+                        bearing(declination, azimuth).into(),
+                        phase.to_string().into(),
+                        light.name().into(),
+                        offset.map(|d| args.durations.format_signed(d)).into(),
+                    ], Tone::from(light));
+                }
+                let mut out = open_output(args.output.as_deref())?;
+                table.write(&mut out, format)?;
+                return Ok(out.flush()?);
+            }
+            let timeline = photo::timeline(lat, long, today);
+            let clock = |t: &DateTime<Utc>| Value::from(args.precision.time(&args.zone.convert(t)));
+            let mut table = Table::new(vec!["start", "end", "light", "length", "azimuth", "direction"]);
+            for (i, transition) in timeline.iter().enumerate() {
+                let end = timeline.get(i + 1).map(|next| next.time);
+                table.push_toned(vec![
+                    clock(&transition.time),
+                    end.as_ref().map(clock).unwrap_or(Value::Null),
+                    transition.light.name().into(),
+                    end.map(|end| args.durations.format(end - transition.time)).into(),
+                    ((transition.azimuth * 10.0).round() / 10.0).into(),
+                    bearing(declination, transition.azimuth).into(),
+                ], Tone::from(transition.light));
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("nautical") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let mut out = open_output(args.output.as_deref())?;
+            AlmanacPage::new(date).write(&mut out, &format)?;
+            return Ok(out.flush()?);
+        }
+        Some("aviation") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let pattern = args.precision.time_pattern();
+            let zulu = |t: DateTime<Utc>| Value::from(args.precision.round(&t).format(&format!("%Y-%m-%d {}Z", pattern)).to_string());
+            let local = |t: DateTime<Utc>| Value::from(args.precision.round(&args.zone.convert(&t)).format(&format!("%Y-%m-%d {} %:z", pattern)).to_string());
+            let mut table = Table::new(vec!["date", "rule", "definition", "start_utc", "start_local", "end_utc", "end_local"]);
+            for day in 0..args.days {
+                for rule in args.rules.nights() {
+                    let (start, end) = rule.night(lat, long, today + day as f64).unzip();
+                    table.push(vec![
+                        (date + Days::new(day as u64)).to_string().into(),
+                        rule.name.into(),
+                        rule.description.into(),
+                        start.map(zulu).unwrap_or(Value::Null),
+                        start.map(local).unwrap_or(Value::Null),
+                        end.map(zulu).unwrap_or(Value::Null),
+                        end.map(local).unwrap_or(Value::Null),
+                    ]);
+                }
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("drone") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let window = window::drone_window(args.window.as_deref().unwrap_or("faa"))?;
+            let at = args.at.as_deref().map(|at| parse_time(at, args.zone, lat, long, at_day)).transpose()?.unwrap_or_else(|| clock.now());
+            // The window of the day of --at, or of --date
+            let day = if args.at.is_some() { args.zone.convert(&at).date_naive() } else { date };
+            let (start, end) = window.on(lat, long, jdn(day)).unzip();
+            let inside = window.contains(lat, long, &at);
+            let time = |t: DateTime<Utc>| args.precision.rfc3339(&args.zone.convert(&t));
+            let fields = [
+                ("window", window.to_string().into()),
+                ("date", day.to_string().into()),
+                ("start", start.map(time).into()),
+                ("end", end.map(time).into()),
+                ("time", time(at).into()),
+                ("inside", inside.into()),
+            ];
+            let (window, day) = (window.to_string(), day.to_string());
+            let clock = |t: DateTime<Utc>| lang.datetime(&args.zone.convert(&t), args.precision);
+            let mut text = match (start, end) {
+                (Some(start), Some(end)) => lang.format("drone-window", &[("window", &window), ("date", &day), ("start", &clock(start)), ("end", &clock(end))]),
+                _ => lang.format("drone-none", &[("window", &window), ("date", &day)]),
+            };
+            text.push('\n');
+            text.push_str(&lang.format(if inside { "drone-inside" } else { "drone-outside" }, &[("time", &clock(at))]));
+            return Ok(format.record(&mut io::stdout(), &fields, &text)?);
+        }
+        Some("batch") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let places = match args.inputs.as_slice() {
+                [path] if path == "-" => so2lar::batch::parse_places(&io::read_to_string(io::stdin())?)?,
+                [path] => so2lar::batch::parse_places(&std::fs::read_to_string(path)?)?,
+                _ => return Err(usage("batch needs a file of places")),
+            };
+            let days = match args.until {
+                Some(until) if until < date => return Err(usage("--until lies before --date")),
+                Some(until) => until.signed_duration_since(date).num_days() as u64 + 1,
+                None => args.days as u64,
+            };
+            let dates: Vec<NaiveDate> = (0..days).map(|day| date + Days::new(day)).collect();
+            let rfc3339 = |t: Option<DateTime<Utc>>| Value::from(t.map(|t| args.precision.rfc3339(&args.zone.convert(&t))));
+            let mut table = Table::new(vec!["location", "lat", "long", "date", "sunrise", "sunset", "solar_noon", "day_length_seconds"]);
+            for row in so2lar::batch::cross_product(&places, &dates, horizon) {
+                let place = &places[row.place];
+                table.push(vec![
+                    place.name.as_str().into(),
+                    place.lat.into(),
+                    place.long.into(),
+                    row.date.to_string().into(),
+                    rfc3339(row.sunrise),
+                    rfc3339(row.sunset),
+                    rfc3339(Some(row.solar_noon)),
+                    row.day_length.num_seconds().into(),
+                ]);
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("clock") => {
+            let live = args.output.is_none() && std::io::IsTerminal::is_terminal(&io::stdout());
+            let mut out = open_output(args.output.as_deref())?;
+            loop {
+                let now = args.zone.convert(&clock.now());
+                let day = args.date.unwrap_or(now.date_naive()).and_time(NaiveTime::MIN);
+                let midnight = (day - args.zone.offset_at_utc(&day)).and_utc();
+                let dial = so2lar::dial::Dial { lat, long, midnight, now };
+                if !live {
+                    writeln!(out, "{}", dial.render())?;
+                    return Ok(out.flush()?);
+                }
+                write!(out, "\x1b[H\x1b[2J{}", dial.render())?;
+                out.flush()?;
+                std::thread::sleep(dial.refresh_in().to_std()?);
+            }
+        }
+        Some("glare") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let samples = match (args.inputs.as_slice(), args.heading) {
+                ([path], None) => glare::route(&export::gpx::timed_points(&std::fs::read_to_string(path)?)?),
+                ([], Some(heading)) => {
+                    let at = |t: Option<NaiveTime>, default: NaiveTime| {
+                        let naive = date.and_time(t.unwrap_or(default));
+                        (naive - args.zone.offset_at_utc(&naive)).and_utc()
+                    };
+                    let (from, to) = (at(args.from, NaiveTime::MIN), at(args.to, NaiveTime::from_hms_opt(23, 59, 0).unwrap()));
+                    glare::fixed_heading(lat, long, heading, from, to)
+                }
+                _ => return Err(usage("glare needs --heading DEG or a GPX file")),
+            };
+            let clock = |t: &DateTime<Utc>| Value::from(args.precision.rfc3339(&args.zone.convert(t)));
+            let round = |x: f64| ((x * 10.0).round() / 10.0).into();
+            let mut table = Table::new(vec!["start", "end", "length", "heading", "sun_azimuth", "sun_elevation"]);
+            for period in args.glare.periods(&samples) {
+                table.push(vec![
+                    clock(&period.start.time),
+                    clock(&period.end),
+                    args.durations.format(period.end - period.start.time).into(),
+                    round(period.start.heading),
+                    round(period.start.azimuth),
+                    round(period.start.elevation),
+                ]);
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("hours") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let names: Vec<&String> = if args.inputs.is_empty() { args.config.hours.keys().collect() } else { args.inputs.iter().collect() };
+            if names.is_empty() {
+                return Err(format!("no [hours] rules in {}", paths::config_file().map_or("the configuration file".to_string(), |p| p.display().to_string())).into());
+            }
+            let now = clock.now();
+            let time = |t: DateTime<Utc>| Value::from(args.precision.rfc3339(&args.zone.convert(&t)));
+            let mut table = Table::new(vec!["name", "rule", "date", "start", "end", "now"]);
+            for name in names {
+                let window = args.config.window(name)?;
+                let (start, end) = window.on(lat, long, today).unzip();
+                table.push(vec![
+                    name.as_str().into(),
+                    window.to_string().into(),
+                    date.to_string().into(),
+                    start.map(time).unwrap_or(Value::Null),
+                    end.map(time).unwrap_or(Value::Null),
+                    window.contains(lat, long, &now).into(),
+                ]);
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("find") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let local_rise = |d: NaiveDate| sunrise_sunset(&at, jdn(d)).map(|(rise, _)| args.zone.convert(&rise).time());
+            let (run, condition, label, keys) = match (args.daylength, args.sunrise_clock) {
+                (Some(condition), None) => (
+                    find::find_run(date, |d| condition.holds(day_length(lat, long, jdn(d)))),
+                    condition.to_string(),
+                    condition.to_string(),
+                    ["find-daylength", "find-daylength-open", "find-daylength-never"],
+                ),
+                (None, Some(condition)) => {
+                    let key = if condition.comparison == find::Comparison::Less { "clock-before" } else { "clock-after" };
+                    (
+                        find::find_run(date, |d| local_rise(d).is_some_and(|t| condition.holds(t))),
+                        condition.to_string(),
+                        lang.format(key, &[("time", &condition.time.format("%H:%M").to_string())]),
+                        ["find-sunrise", "find-sunrise-open", "find-sunrise-never"],
+                    )
+                }
+                _ => return Err(usage("find requires one of --daylength, --sunrise-before or --sunrise-after")),
+            };
+            let (first, last) = run.unzip();
+            let fields = [
+                ("condition", condition.into()),
+                ("first", first.map(|d| d.to_string()).into()),
+                ("last", last.flatten().map(|d| d.to_string()).into()),
+                ("first_sunrise", first.and_then(local_rise).map(|t| t.format("%H:%M:%S").to_string()).into()),
+            ];
+            let text = match run {
+                Some((first, Some(last))) => lang.format(keys[0], &[("condition", &label), ("first", &first.to_string()), ("last", &last.to_string())]),
+                Some((first, None)) => lang.format(keys[1], &[("condition", &label), ("first", &first.to_string())]),
+                None => lang.format(keys[2], &[("condition", &label), ("days", &find::SEARCH_DAYS.to_string())]),
+            };
+            return Ok(format.record(&mut io::stdout(), &fields, &text)?);
+        }
+        Some("nightlight") => {
+            use so2lar::export::nightlight::{NightLight, NIGHT_TEMPERATURE};
+            let Some((rise, set)) = rise_set else {
+                return Err(format!("no sunrise or sunset on {} at {}", date, format_coords(lat, long)).into());
+            };
+            let civil = solar::DaySolution::new(&at, today).crossings_tz(Twilight::Civil.altitude(), &Utc);
+            // Civil twilight lasts all night in the summer of high latitudes
+            let transition = civil.map_or(TimeDelta::hours(1), |(dawn, _)| rise.to_utc() - dawn);
+            let light = NightLight {
+                sunrise: args.zone.convert(&rise),
+                sunset: args.zone.convert(&set),
+                transition,
+                temperature: args.temperature.unwrap_or(NIGHT_TEMPERATURE),
+            };
+            match args.inputs.first().map(String::as_str) {
+                Some("wlsunset") => {
+                    let mut command = std::process::Command::new("wlsunset");
+                    command.args(light.wlsunset_args()).args(&args.inputs[1..]);
+                    if !args.exec {
+                        let words: Vec<_> = command.get_args().map(|a| a.to_string_lossy()).collect();
+                        println!("wlsunset {}", words.join(" "));
+                        return Ok(());
+                    }
+                    #[cfg(unix)]
+                    {
+                        use std::os::unix::process::CommandExt;
+                        return Err(format!("cannot run wlsunset: {}", command.exec()).into());
+                    }
+                    #[cfg(not(unix))]
+                    {
+                        let status = command.status().map_err(|e| format!("cannot run wlsunset: {}", e))?;
+                        return if status.success() { Ok(()) } else { Err(format!("wlsunset exited with {}", status).into()) };
+                    }
+                }
+                Some("hyprsunset") => {
+                    let mut out = open_output(args.output.as_deref())?;
+                    light.write_hyprsunset(&mut out)?;
+                    return Ok(out.flush()?);
+                }
+                _ => return Err(usage("nightlight expects wlsunset or hyprsunset")),
+            }
+        }
+        Some("now") => {
+            let declination = (*declination).clone()?;
+            let now = clock.now();
+            let j = datetime2julian(&now);
+            let phase = Phase::at(lat, long, j);
+            let (elevation, azimuth) = sun_position(lat, long, j);
+            let (moon_elevation, moon_azimuth) = so2lar::moon::moon_horizontal(lat, long, j);
+            // Geometric unless a model is asked for
+            let elevation = args.refraction.map_or(elevation, |model| model.apparent(elevation));
+            let moon_elevation = args.refraction.map_or(moon_elevation, |model| model.apparent(moon_elevation));
+            let next = Phase::next_change(lat, long, j);
+            let time = args.precision.rfc3339(&args.zone.convert(&now));
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let mut fields: Vec<(&str, Value)> = vec![
+                ("time", time.clone().into()),
+                ("phase", phase.to_string().into()),
+                ("elevation", ((elevation * 100.0).round() / 100.0).into()),
+                ("azimuth", ((azimuth * 100.0).round() / 100.0).into()),
+                ("moon_elevation", ((moon_elevation * 100.0).round() / 100.0).into()),
+                ("moon_azimuth", ((moon_azimuth * 100.0).round() / 100.0).into()),
+            ];
+            let next = match next {
+                Some((j, next)) => {
+                    let at = julian2utc(j);
+                    fields.push(("next_phase", next.to_string().into()));
+                    fields.push(("next_change", args.precision.rfc3339(&args.zone.convert(&at)).into()));
+                    fields.push(("seconds_to_next", (at - now).num_seconds().into()));
+                    lang.format("next-phase", &[("phase", &lang.phase(&next)), ("length", &args.durations.format(at - now))])
+                }
+                None => lang.text("no-next-phase").to_string(),
+            };
+            let text = lang.format(
+                "now",
+                &[
+                    ("time", &time),
+                    ("phase", &lang.phase(&phase)),
+                    ("elevation", &format!("{:.1}", elevation)),
+                    ("bearing", &bearing(declination, azimuth)),
+                    ("moon_elevation", &format!("{:.1}", moon_elevation)),
+                    ("moon_bearing", &bearing(declination, moon_azimuth)),
+                    ("next", &next),
+                ],
+            );
+            let text = if matches!(format, Format::Table) { color::paint(Tone::from(phase), &text) } else { text };
+            return Ok(format.record(&mut io::stdout(), &fields, &text)?);
+        }
+        Some("summary") => {
+            let declination = (*declination).clone()?;
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let now = clock.now();
+            let j = datetime2julian(&now);
+            let phase = Phase::at(lat, long, j);
+            let (elevation, azimuth) = sun_position(lat, long, j);
+            let elevation = args.refraction.map_or(elevation, |model| model.apparent(elevation));
+            let solution = solar::DaySolution::new(&at, today);
+            let noon = solution.culmination().time;
+            let rise_set = solution.crossings_tz(horizon, &Utc);
+            let length = rise_set.map_or_else(|| day_length(lat, long, today), |(rise, set)| set - rise);
+            let rate = day_length_rate(lat, long, today);
+            let (rise, set) = rise_set.unzip();
+            let time = |t: Option<DateTime<Utc>>| t.map(|t| Value::from(args.precision.rfc3339(&args.zone.convert(&t)))).unwrap_or(Value::Null);
+            let clock_time = |t: Option<DateTime<Utc>>| t.map_or_else(|| lang.text("none").to_string(), |t| args.precision.time(&args.zone.convert(&t)));
+            let mut fields: Vec<(&str, Value)> = vec![
+                ("time", time(Some(now))),
+                ("phase", phase.to_string().into()),
+                ("elevation", ((elevation * 100.0).round() / 100.0).into()),
+                ("azimuth", ((azimuth * 100.0).round() / 100.0).into()),
+                ("sunrise", time(rise)),
+                ("solar_noon", time(Some(noon))),
+                ("sunset", time(set)),
+                ("day_length_seconds", length.num_seconds().into()),
+                ("day_length_change_seconds", rate.num_seconds().into()),
+            ];
+            let next = match Phase::next_change(lat, long, j) {
+                Some((j, next)) => {
+                    let at = julian2utc(j);
+                    fields.push(("next_phase", next.to_string().into()));
+                    fields.push(("next_change", time(Some(at))));
+                    fields.push(("seconds_to_next", (at - now).num_seconds().into()));
+                    lang.format("next-phase", &[("phase", &lang.phase(&next)), ("length", &args.durations.format(at - now))])
+                }
+                None => lang.text("no-next-phase").to_string(),
+            };
+            let lines = [
+                lang.format(
+                    "summary-now",
+                    &[("time", &clock_time(Some(now))), ("phase", &lang.phase(&phase)), ("elevation", &format!("{:.1}", elevation)), ("bearing", &bearing(declination, azimuth))],
+                ),
+                lang.format("summary-today", &[("sunrise", &clock_time(rise)), ("noon", &clock_time(Some(noon))), ("sunset", &clock_time(set))]),
+                lang.format("summary-length", &[("length", &args.durations.format(length)), ("change", &args.durations.format_signed(rate))]),
+                lang.format("summary-next", &[("next", &next)]),
+            ];
+            return Ok(format.record(&mut io::stdout(), &fields, &lines.join("\n"))?);
+        }
+        Some("until") => {
+            let [event] = args.inputs.as_slice() else {
+                return Err(usage("until expects one event, e.g. sunset or sunset-30m"));
+            };
+            let bound: window::Bound = event.parse()?;
+            let now = clock.now();
+            let day = args.date.unwrap_or_else(|| args.zone.convert(&now).date_naive());
+            let time = bound.time(lat, long, jdn(day)).ok_or(format!("no {} on {} at {}", bound.anchor, day, format_coords(lat, long)))?;
+            let left = time - now;
+            if args.seconds {
+                println!("{}", left.num_seconds());
+                return Ok(());
+            }
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let fields: Vec<(&str, Value)> = vec![
+                ("event", bound.to_string().into()),
+                ("time", args.precision.rfc3339(&args.zone.convert(&time)).into()),
+                ("seconds", left.num_seconds().into()),
+            ];
+            let clock_time = args.precision.time(&args.zone.convert(&time));
+            let mut name = bound.to_string();
+            name[..1].make_ascii_uppercase();
+            let (key, length) = if left >= TimeDelta::zero() { ("until", left) } else { ("since", -left) };
+            let text = lang.format(key, &[("event", &name), ("length", &args.durations.format(length)), ("time", &clock_time)]);
+            return Ok(format.record(&mut io::stdout(), &fields, &text)?);
+        }
+        Some("wait") => {
+            let [event] = args.inputs.as_slice() else {
+                return Err(usage("wait expects one event, e.g. sunrise or sunrise-30m"));
+            };
+            let mut bound: window::Bound = event.parse()?;
+            bound.offset += args.offset;
+            let clock = running_clock(now);
+            let now = clock.now();
+            let first = args.date.unwrap_or_else(|| args.zone.convert(&now).date_naive());
+            let time = (0..366)
+                .filter_map(|day| bound.time(lat, long, jdn(first + Days::new(day))))
+                .find(|t| *t > now || args.date.is_some())
+                .ok_or(format!("no {} within the next year at {}", bound.anchor, format_coords(lat, long)))?;
+            info!("Waiting for {} at {}", bound, time);
+            daemon::sleep_until(clock.as_ref(), time.with_timezone(&Local));
+            return Ok(());
+        }
+        Some("upcoming") => {
+            let declination = (*declination).clone()?;
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let now = clock.now();
+            let count = args.count.unwrap_or(10);
+            // From the day before, whose late dusk can still lie ahead
+            let first = jdn(now.date_naive()) - 1.0;
+            let mut upcoming: Vec<(String, DateTime<Utc>, Tone)> = Vec::new();
+            let mut filled = None;
+            for day in 0..368 {
+                let solution = solar::DaySolution::new(&at, first + day as f64);
+                let crossings = Twilight::ALL
+                    .iter()
+                    .map(|t| (format!("{} dawn", t.name()), format!("{} dusk", t.name()), t.altitude(), Tone::Twilight))
+                    .chain([("sunrise".to_string(), "sunset".to_string(), horizon, Tone::GoldenHour)]);
+                for (dawn, dusk, altitude, tone) in crossings {
+                    if let Some((rise, set)) = solution.crossings_tz(altitude, &Utc) {
+                        upcoming.extend([(dawn, rise, tone), (dusk, set, tone)]);
+                    }
+                }
+                upcoming.push(("solar noon".to_string(), solution.culmination().time, Tone::Day));
+                upcoming.retain(|(_, t, _)| *t > now);
+                // One day more once there are enough, as events of consecutive days can interleave
+                if upcoming.len() >= count && *filled.get_or_insert(day) < day {
+                    break;
+                }
+            }
+            upcoming.sort_by_key(|(_, t, _)| *t);
+            upcoming.truncate(count);
+            let mut table = Table::new(vec!["event", "time", "in", "azimuth", "direction"]);
+            for (event, time, tone) in upcoming {
+                let azimuth = azimuth_at(lat, long, &time);
+                table.push_toned(vec![
+                    event.into(),
+                    args.precision.rfc3339(&args.zone.convert(&time)).into(),
+                    args.durations.format(time - now).into(),
+                    ((declination.map_or(azimuth, |d| to_magnetic(azimuth, d)) * 10.0).round() / 10.0).into(),
+                    bearing(declination, azimuth).into(),
+                ], tone);
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("moon") => {
+            use so2lar::moon::{is_supermoon, moon_distance, LunarApsis, MoonPhase};
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let j = if args.date.is_some() { today - long / 360.0 } else { datetime2julian(&clock.now()) };
+            let time = |j: f64| args.zone.convert(&julian2utc(j));
+            let km = |distance: f64| distance.round();
+            if let Some(direction) = args.inputs.first() {
+                let forward = match direction.as_str() {
+                    "next" => true,
+                    "previous" => false,
+                    _ => return Err(usage(format!("unknown moon subcommand `{}` (expected next or previous)", direction))),
+                };
+                let phases = args.moon_phase.map_or(MoonPhase::ALL.to_vec(), |phase| vec![phase]);
+                let mut table = Table::new(vec!["phase", "time", "distance_km", "supermoon"]);
+                let mut at = j;
+                for _ in 0..args.count.unwrap_or(1) {
+                    let found = phases.iter().map(|phase| (*phase, if forward { phase.next_after(at) } else { phase.previous_before(at) }));
+                    let (phase, t) = if forward { found.min_by(|a, b| a.1.total_cmp(&b.1)) } else { found.max_by(|a, b| a.1.total_cmp(&b.1)) }.unwrap();
+                    // Principal phases lie at least six days apart
+                    at = if forward { t + 1.0 } else { t - 1.0 };
+                    table.push(vec![
+                        phase.name().into(),
+                        args.precision.rfc3339(&time(t)).into(),
+                        km(moon_distance(t)).into(),
+                        if phase == MoonPhase::Full { Value::Bool(is_supermoon(t)) } else { Value::Null },
+                    ]);
+                }
+                let mut out = open_output(args.output.as_deref())?;
+                table.write(&mut out, format)?;
+                return Ok(out.flush()?);
+            }
+            let mut fields: Vec<(&str, Value)> = vec![("time", args.precision.rfc3339(&time(j)).into()), ("distance_km", km(moon_distance(j)).into())];
+            let mut lines = vec![lang.format("moon-distance", &[("km", &km(moon_distance(j)).to_string())])];
+            for apsis in LunarApsis::ALL {
+                let (t, distance) = apsis.next_after(j);
+                let (key, distance_key) = match apsis {
+                    LunarApsis::Perigee => ("next_perigee", "perigee_distance_km"),
+                    LunarApsis::Apogee => ("next_apogee", "apogee_distance_km"),
+                };
+                fields.push((key, args.precision.rfc3339(&time(t)).into()));
+                fields.push((distance_key, km(distance).into()));
+                lines.push(lang.format(apsis.name(), &[("time", &lang.datetime(&time(t), args.precision)), ("km", &km(distance).to_string())]));
+            }
+            let (full, _) = eclipse::next_full_moon(j);
+            let supermoon = is_supermoon(full);
+            fields.push(("next_full_moon", args.precision.rfc3339(&time(full)).into()));
+            fields.push(("full_moon_distance_km", km(moon_distance(full)).into()));
+            fields.push(("supermoon", Value::Bool(supermoon)));
+            let key = if supermoon { "full-moon-super" } else { "full-moon" };
+            lines.push(lang.format(key, &[("time", &lang.datetime(&time(full), args.precision)), ("km", &km(moon_distance(full)).to_string())]));
+            return Ok(format.record(&mut io::stdout(), &fields, &lines.join("\n"))?);
+        }
+        Some("orbit") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let year = args.year.unwrap_or(date.year());
+            let j = if args.date.is_some() { today - long / 360.0 } else { datetime2julian(&clock.now()) };
+            let time = |j: f64| args.zone.convert(&julian2utc(j));
+            let au = orbit::distance(j);
+            let mut fields: Vec<(&str, Value)> = vec![
+                ("time", args.precision.rfc3339(&time(j)).into()),
+                ("distance_au", ((au * 1e6).round() / 1e6).into()),
+                ("distance_km", (au * orbit::AU).round().into()),
+                ("irradiance", ((orbit::irradiance(j) * 10.0).round() / 10.0).into()),
+                ("horizontal_irradiance", ((orbit::horizontal_irradiance(lat, long, j) * 10.0).round() / 10.0).into()),
+            ];
+            let mut lines = Vec::new();
+            for apsis in Apsis::ALL {
+                let t = apsis.in_year(year).ok_or(format!("year {} out of range", year))?;
+                let distance = format!("{:.6}", orbit::distance(t));
+                fields.push((apsis.name(), args.precision.rfc3339(&time(t)).into()));
+                fields.push((if apsis == Apsis::Perihelion { "perihelion_distance_au" } else { "aphelion_distance_au" }, distance.parse::<f64>()?.into()));
+                lines.push(lang.format(apsis.name(), &[("time", &lang.datetime(&time(t), args.precision)), ("distance", &distance)]));
+            }
+            lines.push(lang.format("sun-distance", &[("au", &format!("{:.6}", au)), ("km", &format!("{:.0}", au * orbit::AU))]));
+            lines.push(lang.format(
+                "irradiance",
+                &[("normal", &format!("{:.1}", orbit::irradiance(j))), ("horizontal", &format!("{:.1}", orbit::horizontal_irradiance(lat, long, j)))],
+            ));
+            return Ok(format.record(&mut io::stdout(), &fields, &lines.join("\n"))?);
+        }
+        Some("shadow") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let year = args.year.unwrap_or(clock.now().with_timezone(&Local).year());
+            let round = |x: f64, places: i32| (x * 10f64.powi(places)).round() / 10f64.powi(places);
+            let mut table = Table::new(vec!["date", "solar_noon", "altitude", "declination", "shadow_per_metre", "shadow_points"]);
+            // The 21st comes close to each solstice and equinox
+            for date in (1..=12).filter_map(|month| NaiveDate::from_ymd_opt(year, month, 21)) {
+                let solution = solar::DaySolution::new(&at, jdn(date));
+                let noon = solution.culmination();
+                let up = noon.altitude > 0.0;
+                table.push(vec![
+                    date.to_string().into(),
+                    args.precision.rfc3339(&args.zone.convert(&noon.time)).into(),
+                    round(noon.altitude, 2).into(),
+                    round(solution.delta, 2).into(),
+                    up.then(|| round(1.0 / noon.altitude.to_radians().tan(), 3)).into(),
+                    up.then(|| if noon.north { "south" } else { "north" }.to_string()).into(),
+                ]);
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("diff") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let year = args.year.unwrap_or(date.year());
+            let (from, to) = args.range.map_or_else(|| parse_range(&year.to_string()), Ok)?;
+            let diff = AlgorithmDiff::new(args.algorithms, lat, long, from, to, horizon);
+            let mut out = open_output(args.output.as_deref())?;
+            diff.write(&mut out, &format)?;
+            return Ok(out.flush()?);
+        }
+        Some("week") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            // Clock times of sunrise and sunset after midnight, and the day length
+            let day = |date: NaiveDate| {
+                let (rise, set) = crossings_on(date);
+                let time = |j: f64| (!j.is_nan()).then(|| args.zone.convert(&solar::julian2datetime_tz(j, &Utc)));
+                let length = match (time(rise), time(set)) {
+                    (Some(rise), Some(set)) => set - rise,
+                    _ => solar::day_length_at(lat, long, jdn(date), horizon),
+                };
+                ([time(rise), time(set)], length)
+            };
+            let change = |now: Option<DateTime<FixedOffset>>, then: Option<DateTime<FixedOffset>>| {
+                Value::from(now.zip(then).map(|(now, then)| args.durations.format_signed(now.time() - then.time())))
+            };
+            let mut table = Table::new(vec!["date", "sunrise", "sunrise_change", "sunset", "sunset_change", "day_length", "day_length_change"]);
+            let mut previous = day(date.pred_opt().ok_or("date out of range")?);
+            for date in date.iter_days().take(7) {
+                let ([rise, set], length) = day(date);
+                table.push(vec![
+                    date.format("%a %Y-%m-%d").to_string().into(),
+                    rise.map(|t| args.precision.time(&t)).into(),
+                    change(rise, previous.0[0]),
+                    set.map(|t| args.precision.time(&t)).into(),
+                    change(set, previous.0[1]),
+                    args.durations.format(length).into(),
+                    args.durations.format_signed(length - previous.1).into(),
+                ]);
+                previous = ([rise, set], length);
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("solar-time") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let mut table = Table::new(vec!["date", "civil_dawn", "sunrise", "solar_noon", "sunset", "civil_dusk", "offset_seconds"]);
+            for day in (0..args.days).filter(|day| args.sample.includes(date + Days::new(*day as u64), date)) {
+                let solution = solar::DaySolution::new(&at, today + day as f64);
+                let clock = |j: f64| if j.is_nan() { Value::Null } else { solution.apparent_solar_time(j).format("%H:%M:%S").to_string().into() };
+                let (rise, set) = solution.crossings(horizon);
+                let (dawn, dusk) = solution.crossings(Twilight::Civil.altitude());
+                // Apparent noon on the UTC clock of the sunrise equation's Julian dates
+                let noon = solar::julian2datetime_tz(solution.j_transit, &Utc);
+                let offset = TimeDelta::hours(12) - noon.signed_duration_since(noon.date_naive().and_time(NaiveTime::MIN).and_utc());
+                table.push(vec![
+                    (date + Days::new(day as u64)).to_string().into(),
+                    clock(dawn),
+                    clock(rise),
+                    clock(solution.j_transit),
+                    clock(set),
+                    clock(dusk),
+                    offset.num_seconds().into(),
+                ]);
+            }
+            let mut out = open_output(args.output.as_deref())?;
+            table.write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("season") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let j = if args.date.is_some() { today - long / 360.0 } else { datetime2julian(&clock.now()) };
+            let year = args.zone.convert(&julian2utc(j)).year();
+            let (Some((start, from)), Some((end, to))) = (SeasonMarker::previous(j, year, |_| true), SeasonMarker::next(j, year)) else {
+                return Err(format!("year {} out of range", year).into());
+            };
+            let season = start.local_season(hemisphere);
+            let percent = (j - from) / (to - from) * 100.0;
+            let time = |j: f64| args.zone.convert(&julian2utc(j));
+            let fields: Vec<(&str, Value)> = vec![
+                ("season", season.into()),
+                ("hemisphere", hemisphere.name().into()),
+                ("percent", ((percent * 10.0).round() / 10.0).into()),
+                ("start", start.name().into()),
+                ("start_time", args.precision.rfc3339(&time(from)).into()),
+                ("end", end.name().into()),
+                ("end_time", args.precision.rfc3339(&time(to)).into()),
+            ];
+            let text = lang.format(
+                "season",
+                &[
+                    ("season", lang.text(season)),
+                    ("percent", &format!("{:.1}", percent)),
+                    ("start", lang.text(start.local_name(hemisphere))),
+                    ("from", &lang.short_date(&time(from))),
+                    ("end", lang.text(end.local_name(hemisphere))),
+                    ("to", &lang.short_date(&time(to))),
+                    ("left", &duration::format_days(TimeDelta::seconds(((to - j) * 86400.0) as i64))),
+                ],
+            );
+            return Ok(format.record(&mut io::stdout(), &fields, &text)?);
+        }
+        Some("repl") => {
+            let mut repl = so2lar::repl::Repl { lat, long, date, zone: args.zone, config: args.config };
+            return Ok(repl.run(io::stdin().lock(), &mut io::stdout().lock())?);
+        }
+        Some("rpc") => {
+            let server = so2lar::rpc::RpcServer { lat, long, zone: args.zone, cache: None };
+            return Ok(server.serve(io::stdin().lock(), &mut io::stdout().lock())?);
+        }
+        Some("serve") => {
+            if cfg!(target_os = "wasi") {
+                return Err("serve needs threads, which wasm32-wasi does not have".into());
+            }
+            let listener = std::net::TcpListener::bind(&args.listen).map_err(|e| format!("cannot listen on {}: {}", args.listen, e))?;
+            let cache = ResponseCache::new(args.cache_ttl, args.cache_size);
+            let server = so2lar::http::HttpServer { rpc: so2lar::rpc::RpcServer { lat, long, zone: args.zone, cache: Some(cache) } };
+            return Ok(server.serve(listener)?);
+        }
+        Some("stream") => {
+            let mut pipeline = so2lar::pipeline::Pipeline::new(lat, long, date, args.zone, args.precision);
+            return Ok(pipeline.run(io::stdin().lock(), &mut io::stdout().lock())?);
+        }
+        Some("subsolar") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            loop {
+                let now = clock.now();
+                let (sub_lat, sub_long) = solar::subsolar_point(datetime2julian(&now));
+                let time = args.precision.rfc3339(&args.zone.convert(&now));
+                let text = lang.format("subsolar", &[("time", &time), ("point", &format_coords(sub_lat, sub_long))]);
+                let round = |x: f64| (x * 1e4).round() / 1e4;
+                let fields = [("time", time.into()), ("latitude", round(sub_lat).into()), ("longitude", round(sub_long).into())];
+                format.record(&mut io::stdout(), &fields, &text)?;
+                if !args.watch {
+                    return Ok(());
+                }
+                std::thread::sleep(args.interval);
+            }
+        }
+        Some("schtasks") => {
+            let (Some(event), Some(command)) = (args.event, args.command_line.as_deref()) else {
+                return Err(usage("schtasks requires --event and --command"));
+            };
+            if !args.offset.is_zero() {
+                return Err(usage("schtasks cannot shift the event; use cron for offsets"));
+            }
+            let name = args.task.clone().unwrap_or_else(|| event.name().to_string());
+            let dir = match args.dir.clone() {
+                Some(dir) => dir,
+                None => paths::data_dir().ok_or("cannot determine the data directory, pass --dir")?.join("tasks"),
+            };
+            let next = event.next_after(lat, long, today, clock.now().with_timezone(&Local)).ok_or(format!("no {} within the next year", event.name()))?;
+            let exe = std::env::current_exe()?;
+            let refresh_arguments = format!(
+                "--lat {} --long {} schtasks --event {} --command {} --task {} --dir {}",
+                lat,
+                long,
+                event.name(),
+                export::schtasks::quote_argument(command),
+                export::schtasks::quote_argument(&name),
+                export::schtasks::quote_argument(&dir.display().to_string())
+            );
+            let spec = export::schtasks::TaskSpec { event, name: &name, command, next, refresh_command: (exe.display().to_string(), refresh_arguments) };
+            let paths = spec.write(&dir)?;
+            for path in &paths {
+                println!("Wrote {}", path.display());
+            }
+            for mut schtasks in spec.commands(&paths) {
+                if cfg!(windows) {
+                    let status = schtasks.status()?;
+                    if !status.success() {
+                        return Err(format!("schtasks failed: {}", status).into());
+                    }
+                } else {
+                    let args: Vec<_> = schtasks.get_args().map(|a| a.to_string_lossy()).collect();
+                    println!("Register on Windows with: schtasks {}", args.join(" "));
+                }
+            }
+            if cfg!(windows) {
+                let (task, refresh) = spec.task_names();
+                println!("Registered {} and {}", task, refresh);
+            }
+            return Ok(());
+        }
+        Some("timeshift") => {
+            let format: Format = args.format.as_deref().unwrap_or("table").parse()?;
+            let year = args.year.unwrap_or(clock.now().with_timezone(&Local).year());
+            let (morning, evening) = (args.from.unwrap_or(NaiveTime::from_hms_opt(7, 0, 0).unwrap()), args.to.unwrap_or(NaiveTime::from_hms_opt(18, 0, 0).unwrap()));
+            let report = so2lar::timeshift::TimeShiftReport::new(lat, long, year, args.zone, horizon, morning, evening).ok_or(format!("year {} out of range", year))?;
+            let mut out = open_output(args.output.as_deref())?;
+            report.table().write(&mut out, format)?;
+            return Ok(out.flush()?);
+        }
+        Some("sunline") => {
+            let event = match args.inputs.as_slice() {
+                [] => SolarEvent::Sunrise,
+                [event] => event.parse()?,
+                _ => return Err(usage("sunline expects sunrise or sunset")),
+            };
+            let at = args.at.as_deref().map(|at| parse_time(at, args.zone, lat, long, at_day)).transpose()?.unwrap_or_else(|| clock.now());
+            let mut out = open_output(args.output.as_deref())?;
+            export::geojson::write_event_line(&mut out, event, &at)?;
+            return Ok(out.flush()?);
+        }
+        Some("systemd") => {
+            let (Some(event), Some(unit)) = (args.event, args.unit.as_deref()) else {
+                return Err(usage("systemd requires --event and --unit"));
+            };
+            if !args.offset.is_zero() {
+                return Err(usage("systemd cannot shift the event; use cron for offsets"));
+            }
+            let dir = match args.dir.clone() {
+                Some(dir) => dir,
+                None => export::systemd::user_unit_dir().ok_or("cannot determine the user unit directory, pass --dir")?,
+            };
+            let next = event.next_after(lat, long, today, clock.now().with_timezone(&Local)).ok_or(format!("no {} within the next year", event.name()))?;
+            let exe = std::env::current_exe()?;
+            let refresh_command = format!(
+                "{} --lat {} --long {} systemd --event {} --unit {} --dir {}",
+                exe.display(), lat, long, event.name(), unit, dir.display()
+            );
+            let spec = export::systemd::TimerSpec { event, unit, next, refresh_command };
+            let paths = spec.write(&dir)?;
+            for path in &paths {
+                println!("Wrote {}", path.display());
+            }
+            let timers: Vec<_> = paths.iter().filter(|p| p.extension().is_some_and(|e| e == "timer")).filter_map(|p| p.file_name()).collect();
+            println!("Enable with: systemctl --user daemon-reload && systemctl --user enable --now {}", timers.iter().map(|t| t.to_string_lossy()).collect::<Vec<_>>().join(" "));
+            return Ok(());
+        }
+        Some(cmd) => return Err(usage(format!("unknown command `{}`", cmd))),
+    }
+    // Opt-in stand-ins for sunrise and sunset during polar day and night
+    let polar_fallback = if args.polar_fallback && rise_set.is_none() { solar::DaySolution::new(&at, today).polar_fallback(horizon) } else { None };
+    let widget = match args.format.as_deref() {
+        Some("tmux") => Some(Widget::Tmux),
+        _ => args.widget,
+    };
+    if let Some(widget) = widget {
+        let now = clock.now();
+        let times = |today: f64| sunrise_sunset(&at, today).map(|(rise, set)| (args.zone.convert(&rise), args.zone.convert(&set)));
+        let next = [times(today), times(today + 1.0)]
+            .into_iter()
+            .flatten()
+            .flat_map(|(rise, set)| [(rise, true), (set, false)])
+            .find(|(t, _)| *t > now);
+        let snapshot = Snapshot {
+            now: args.zone.convert(&now),
+            phase: Phase::at(lat, long, datetime2julian(&now)),
+            times: times(today).or_else(|| polar_fallback.map(|f| (args.zone.convert(&f.time), args.zone.convert(&f.time)))),
+            fallback: polar_fallback.is_some(),
+            day_length: day_length(lat, long, today),
+            night_length: night_length(lat, long, today),
+            next,
+        };
+        let mut out = open_output(args.output.as_deref())?;
+        writeln!(out, "{}", widget.render(&snapshot, lang))?;
+        return Ok(out.flush()?);
+    }
+    let formatter: Format = args.format.as_deref().unwrap_or("table").parse()?;
+    if args.explain {
+        let debug = solar::SolarDebug::new(solar::DaySolution::new(&at, today), horizon);
+        let mut table = Table::new(vec!["quantity", "symbol", "value", "unit"]);
+        for (name, symbol, value, unit) in debug.quantities() {
+            table.push(vec![name.into(), symbol.into(), if value.is_nan() { Value::Null } else { value.into() }, unit.into()]);
+        }
+        let mut out = open_output(args.output.as_deref())?;
+        table.write(&mut out, formatter)?;
+        return Ok(out.flush()?);
+    }
+    let declination = (*declination).clone()?;
+    let len = rise_set.filter(|(rise, set)| set > rise).map_or_else(|| day_length(lat, long, today), |(rise, set)| set - rise);
+    let night = night_length(lat, long, today);
+    let rfc3339 = |t: DateTime<FixedOffset>| Value::from(args.precision.rfc3339(&t));
+    let mut text = String::new();
+    let mut fields: Vec<(String, Value)> = vec![("date".into(), date.to_string().into())];
+    if let Some(calendar) = args.calendar {
+        let day = calendar.date(date).to_string();
+        let name = match calendar {
+            Calendar::Julian => lang.text("calendar-julian"),
+            Calendar::Hebrew => lang.text("calendar-hebrew"),
+            Calendar::Islamic => lang.text("calendar-islamic"),
+        };
+        fields.push(("calendar_date".into(), day.clone().into()));
+        writeln!(text, "{}", lang.format("calendar-date", &[("calendar", name), ("date", &day)]))?;
+    }
+    match rise_set {
+        Some((rise, set)) => {
+            let (rise_azimuth, set_azimuth) = (azimuth_at(lat, long, &rise), azimuth_at(lat, long, &set));
+            fields.push(("sunrise".into(), rfc3339(args.zone.convert(&rise))));
+            fields.push(("sunset".into(), rfc3339(args.zone.convert(&set))));
+            fields.push(("sunrise_azimuth".into(), declination.map_or(rise_azimuth, |d| to_magnetic(rise_azimuth, d)).into()));
+            fields.push(("sunset_azimuth".into(), declination.map_or(set_azimuth, |d| to_magnetic(set_azimuth, d)).into()));
+            writeln!(text, "{}", lang.format("sunrise", &[("time", &lang.datetime(&args.zone.convert(&rise), args.precision)), ("bearing", &bearing(declination, rise_azimuth))]))?;
+            writeln!(text, "{}", lang.format("sunset", &[("time", &lang.datetime(&args.zone.convert(&set), args.precision)), ("bearing", &bearing(declination, set_azimuth))]))?;
+            let point = |azimuth: f64| lang.compass(args.compass.point(declination.map_or(azimuth, |d| to_magnetic(azimuth, d))));
+            writeln!(text, "{}", lang.format("rise-set-direction", &[("rise", &point(rise_azimuth)), ("set", &point(set_azimuth))]))?;
+            if let Some(uncertainty) = solar::DaySolution::new(&at, today).uncertainty(horizon).filter(|_| args.show_accuracy) {
+                fields.push(("uncertainty_seconds".into(), uncertainty.num_seconds().into()));
+                writeln!(text, "{}", lang.format("accuracy", &[("uncertainty", &args.durations.format_signed(uncertainty).replacen('+', "±", 1))]))?;
+            }
+            if args.clouds {
+                cloud_cover(lang, &HttpCache::new(args.offline), lat, long, [("sunrise", rise.to_utc()), ("sunset", set.to_utc())], &mut fields, &mut text)?;
+            }
+        }
+        None => {
+            writeln!(text, "{}", lang.text(if len > TimeDelta::zero() { "polar-day" } else { "polar-night" }))?;
+            match polar_fallback {
+                Some(fallback) => {
+                    let time = args.zone.convert(&fallback.time);
+                    fields.extend(["sunrise", "sunset"].map(|key| (key.into(), rfc3339(time))));
+                    let key = if fallback.polar_day { "polar-fallback-day" } else { "polar-fallback-night" };
+                    writeln!(text, "{}", lang.format(key, &[("time", &args.precision.time(&time))]))?;
+                }
+                None => fields.extend(["sunrise", "sunset"].map(|key| (key.into(), Value::Null))),
+            }
+            fields.extend(["sunrise_azimuth", "sunset_azimuth"].map(|key| (key.into(), Value::Null)));
+            if let Some(fallback) = polar_fallback {
+                fields.push(("polar_fallback".into(), fallback.name().into()));
+            }
+        }
+    }
+    let culmination = solar::DaySolution::new(&at, today).culmination();
+    let direction = if culmination.north { "north" } else { "south" };
+    fields.push(("solar_noon".into(), rfc3339(args.zone.convert(&culmination.time))));
+    fields.push(("noon_altitude".into(), ((culmination.altitude * 100.0).round() / 100.0).into()));
+    fields.push(("culmination".into(), direction.into()));
+    let noon_args = [
+        ("time", args.precision.time(&args.zone.convert(&culmination.time))),
+        ("altitude", format!("{:.1}", culmination.altitude)),
+        ("direction", lang.text(direction).to_string()),
+    ];
+    writeln!(text, "{}", lang.format("solar-noon", &noon_args.iter().map(|(k, v)| (*k, v.as_str())).collect::<Vec<_>>()))?;
+    let sun_length = match args.daylength_definition {
+        solar::DayLengthDefinition::UpperLimb => len,
+        definition => solar::day_length_at(lat, long, today, definition.altitude(horizon)),
+    };
+    fields.push(("day_length_seconds".into(), sun_length.num_seconds().into()));
+    fields.push(("night_length_seconds".into(), night.num_seconds().into()));
+    writeln!(text, "{}", hms(lang, "sun-length", sun_length, args.durations))?;
+    writeln!(text, "{}", hms(lang, "night-length", night, args.durations))?;
+    let dark = darkness(lat, long, today);
+    fields.push(("darkness_start".into(), dark.map(|(dusk, _)| rfc3339(args.zone.convert(&dusk))).unwrap_or(Value::Null)));
+    fields.push(("darkness_end".into(), dark.map(|(_, dawn)| rfc3339(args.zone.convert(&dawn))).unwrap_or(Value::Null)));
+    match dark {
+        Some((dusk, dawn)) => {
+            let (from, to) = (args.zone.convert(&dusk).format("%H:%M").to_string(), args.zone.convert(&dawn).format("%H:%M").to_string());
+            writeln!(text, "{}", lang.format("darkness", &[("from", &from), ("to", &to), ("length", &args.durations.format(dawn - dusk))]))?
+        }
+        // Sun position at local midnight tells whether it stays below or above -18°
+        None if sun_position(lat, long, today - long / 360.0 + 0.5).0 < -18.0 => writeln!(text, "{}", lang.text("darkness-all-night"))?,
+        None => writeln!(text, "{}", lang.text("darkness-none"))?,
+    }
+    for phase in Twilight::ALL {
+        let twilight = phase.length(lat, long, today);
+        let length = match twilight {
+            TwilightLength::Timed { morning, evening } => {
+                lang.format("twilight-timed", &[("morning", &args.durations.format(morning)), ("evening", &args.durations.format(evening))])
+            }
+            TwilightLength::AllNight => lang.text("twilight-all-night").to_string(),
+            TwilightLength::Midday => lang.text("twilight-midday").to_string(),
+            TwilightLength::AllDay => lang.text("twilight-all-day").to_string(),
+            TwilightLength::Never => lang.text("twilight-never").to_string(),
+        };
+        let (morning, evening) = match twilight {
+            TwilightLength::Timed { morning, evening } => (morning.num_seconds().into(), evening.num_seconds().into()),
+            _ => (Value::Null, Value::Null),
+        };
+        let name = phase.name();
+        fields.push((format!("{}_twilight_morning_seconds", name), morning));
+        fields.push((format!("{}_twilight_evening_seconds", name), evening));
+        let label = lang.text(match phase {
+            Twilight::Civil => "twilight-civil",
+            Twilight::Nautical => "twilight-nautical",
+            Twilight::Astronomical => "twilight-astronomical",
+        });
+        writeln!(text, "{}", lang.format("twilight", &[("phase", label), ("length", &length)]))?;
+    }
+    if let Some((solstice, j)) = SeasonMarker::previous(today, date.year(), SeasonMarker::is_solstice) {
+        let then = day_length(lat, long, (j + 0.5).floor());
+        let change = lang.text(if len >= then { "gained" } else { "lost" });
+        let date = lang.short_date(&args.zone.convert(&julian2utc(j)));
+        fields.push(("change_since_solstice_seconds".into(), (len - then).num_seconds().into()));
+        let args = [("change", change), ("amount", &args.durations.format(len - then)), ("marker", lang.text(solstice.local_name(hemisphere))), ("date", &date)];
+        writeln!(text, "{}", lang.format("since-marker", &args))?;
+    }
+    let reference = if args.date.is_some() { today - long / 360.0 } else { datetime2julian(&clock.now()) };
+    if let Some((marker, j)) = SeasonMarker::next(reference, date.year()) {
+        let at = args.zone.convert(&julian2utc(j));
+        let days = (at.date_naive() - date).num_days();
+        fields.push(("next_marker".into(), marker.name().into()));
+        fields.push(("next_marker_time".into(), rfc3339(at)));
+        fields.push(("days_to_next_marker".into(), days.into()));
+        let name = lang.text(marker.local_name(hemisphere));
+        let name: String = name.chars().take(1).flat_map(char::to_uppercase).chain(name.chars().skip(1)).collect();
+        let key = match days {
+            0 => "until-marker-today",
+            1 => "until-marker-tomorrow",
+            _ => "until-marker",
+        };
+        let args = [("marker", name.as_str()), ("days", &days.to_string()), ("date", &lang.short_date(&at)), ("time", &at.format("%H:%M").to_string())];
+        writeln!(text, "{}", lang.format(key, &args))?;
+    }
+    let rate = day_length_rate(lat, long, today);
+    fields.push(("day_length_change_seconds".into(), rate.num_seconds().into()));
+    writeln!(text, "{}", lang.format("day-length-change", &[("day", &args.durations.format_signed(rate)), ("week", &args.durations.format_signed(rate * 7))]))?;
+    if args.compare_year {
+        writeln!(text, "{}", comparison(lang, &at, date, args.zone, args.durations))?;
+    }
+    if args.horizon.is_some() || !args.obstacles.is_empty() {
+        let profile = match &args.horizon {
+            Some(path) => HorizonProfile::from_csv(path)?,
+            None => HorizonProfile::flat(),
+        };
+        let profile = profile.with_obstacles(&args.obstacles);
+        let visible_rise = profile.visible_rise(lat, long, noon - 0.5, noon).map(|j| args.zone.convert(&julian2utc(j)));
+        let visible_set = profile.visible_set(lat, long, noon, noon + 0.5).map(|j| args.zone.convert(&julian2utc(j)));
+        fields.push(("visible_sunrise".into(), visible_rise.map(rfc3339).unwrap_or(Value::Null)));
+        fields.push(("visible_sunset".into(), visible_set.map(rfc3339).unwrap_or(Value::Null)));
+        writeln!(text, "{}", visible_line(lang, "visible-sunrise", visible_rise, args.precision))?;
+        writeln!(text, "{}", visible_line(lang, "visible-sunset", visible_set, args.precision))?;
+        let spans = profile.visible_spans(lat, long, noon - 0.5, noon + 0.5);
+        for gap in spans.windows(2) {
+            let (from, to) = (args.zone.convert(&julian2utc(gap[0].1)), args.zone.convert(&julian2utc(gap[1].0)));
+            writeln!(text, "{}", lang.format("shaded", &[("from", &args.precision.time(&from)), ("to", &args.precision.time(&to))]))?;
+        }
+        if visible_rise.is_some() && visible_set.is_some() {
+            let len: f64 = spans.iter().map(|(start, end)| end - start).sum();
+            let len = TimeDelta::milliseconds((len * 86400000.0).round() as i64);
+            fields.push(("visible_sun_length_seconds".into(), len.num_seconds().into()));
+            writeln!(text, "{}", hms(lang, "visible-sun-length", len, args.durations))?;
+        }
+    }
+    let fields: Vec<(&str, Value)> = fields.iter().map(|(k, v)| (k.as_str(), v.clone())).collect();
+    let mut out = open_output(args.output.as_deref())?;
+    formatter.record(&mut out, &fields, &text)?;
+    Ok(out.flush()?)
 }
-
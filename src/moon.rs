@@ -0,0 +1,341 @@
+use std::str::FromStr;
+
+use crate::eclipse::next_elongation;
+use crate::solar::{nutation_in_longitude, obliquity, sidereal_time};
+use crate::timescale::TimeScales;
+
+/// Multiples of D, M, M' and F with the coefficients of the longitude
+/// (1e-6 degrees) and distance (1e-3 km), the main terms of ELP-2000/82 as
+/// given by Meeus, Astronomical Algorithms, table 47.A.
+const LONGITUDE_DISTANCE: [(i8, i8, i8, i8, f64, f64); 60] = [
+    (0, 0, 1, 0, 6288774.0, -20905355.0),
+    (2, 0, -1, 0, 1274027.0, -3699111.0),
+    (2, 0, 0, 0, 658314.0, -2955968.0),
+    (0, 0, 2, 0, 213618.0, -569925.0),
+    (0, 1, 0, 0, -185116.0, 48888.0),
+    (0, 0, 0, 2, -114332.0, -3149.0),
+    (2, 0, -2, 0, 58793.0, 246158.0),
+    (2, -1, -1, 0, 57066.0, -152138.0),
+    (2, 0, 1, 0, 53322.0, -170733.0),
+    (2, -1, 0, 0, 45758.0, -204586.0),
+    (0, 1, -1, 0, -40923.0, -129620.0),
+    (1, 0, 0, 0, -34720.0, 108743.0),
+    (0, 1, 1, 0, -30383.0, 104755.0),
+    (2, 0, 0, -2, 15327.0, 10321.0),
+    (0, 0, 1, 2, -12528.0, 0.0),
+    (0, 0, 1, -2, 10980.0, 79661.0),
+    (4, 0, -1, 0, 10675.0, -34782.0),
+    (0, 0, 3, 0, 10034.0, -23210.0),
+    (4, 0, -2, 0, 8548.0, -21636.0),
+    (2, 1, -1, 0, -7888.0, 24208.0),
+    (2, 1, 0, 0, -6766.0, 30824.0),
+    (1, 0, -1, 0, -5163.0, -8379.0),
+    (1, 1, 0, 0, 4987.0, -16675.0),
+    (2, -1, 1, 0, 4036.0, -12831.0),
+    (2, 0, 2, 0, 3994.0, -10445.0),
+    (4, 0, 0, 0, 3861.0, -11650.0),
+    (2, 0, -3, 0, 3665.0, 14403.0),
+    (0, 1, -2, 0, -2689.0, -7003.0),
+    (2, 0, -1, 2, -2602.0, 0.0),
+    (2, -1, -2, 0, 2390.0, 10056.0),
+    (1, 0, 1, 0, -2348.0, 6322.0),
+    (2, -2, 0, 0, 2236.0, -9884.0),
+    (0, 1, 2, 0, -2120.0, 5751.0),
+    (0, 2, 0, 0, -2069.0, 0.0),
+    (2, -2, -1, 0, 2048.0, -4950.0),
+    (2, 0, 1, -2, -1773.0, 4130.0),
+    (2, 0, 0, 2, -1595.0, 0.0),
+    (4, -1, -1, 0, 1215.0, -3958.0),
+    (0, 0, 2, 2, -1110.0, 0.0),
+    (3, 0, -1, 0, -892.0, 3258.0),
+    (2, 1, 1, 0, -810.0, 2616.0),
+    (4, -1, -2, 0, 759.0, -1897.0),
+    (0, 2, -1, 0, -713.0, -2117.0),
+    (2, 2, -1, 0, -700.0, 2354.0),
+    (2, 1, -2, 0, 691.0, 0.0),
+    (2, -1, 0, -2, 596.0, 0.0),
+    (4, 0, 1, 0, 549.0, -1423.0),
+    (0, 0, 4, 0, 537.0, -1117.0),
+    (4, -1, 0, 0, 520.0, -1571.0),
+    (1, 0, -2, 0, -487.0, -1739.0),
+    (2, 1, 0, -2, -399.0, 0.0),
+    (0, 0, 2, -2, -381.0, -4421.0),
+    (1, 1, 1, 0, 351.0, 0.0),
+    (3, 0, -2, 0, -340.0, 0.0),
+    (4, 0, -3, 0, 330.0, 0.0),
+    (2, -1, 2, 0, 327.0, 0.0),
+    (0, 2, 1, 0, -323.0, 1165.0),
+    (1, 1, -1, 0, 299.0, 0.0),
+    (2, 0, 3, 0, 294.0, 0.0),
+    (2, 0, -1, -2, 0.0, 8752.0),
+];
+
+/// Multiples of D, M, M' and F with the coefficients of the latitude
+/// (1e-6 degrees), Meeus table 47.B.
+const LATITUDE: [(i8, i8, i8, i8, f64); 60] = [
+    (0, 0, 0, 1, 5128122.0),
+    (0, 0, 1, 1, 280602.0),
+    (0, 0, 1, -1, 277693.0),
+    (2, 0, 0, -1, 173237.0),
+    (2, 0, -1, 1, 55413.0),
+    (2, 0, -1, -1, 46271.0),
+    (2, 0, 0, 1, 32573.0),
+    (0, 0, 2, 1, 17198.0),
+    (2, 0, 1, -1, 9266.0),
+    (0, 0, 2, -1, 8822.0),
+    (2, -1, 0, -1, 8216.0),
+    (2, 0, -2, -1, 4324.0),
+    (2, 0, 1, 1, 4200.0),
+    (2, 1, 0, -1, -3359.0),
+    (2, -1, -1, 1, 2463.0),
+    (2, -1, 0, 1, 2211.0),
+    (2, -1, -1, -1, 2065.0),
+    (0, 1, -1, -1, -1870.0),
+    (4, 0, -1, -1, 1828.0),
+    (0, 1, 0, 1, -1794.0),
+    (0, 0, 0, 3, -1749.0),
+    (0, 1, -1, 1, -1565.0),
+    (1, 0, 0, 1, -1491.0),
+    (0, 1, 1, 1, -1475.0),
+    (0, 1, 1, -1, -1410.0),
+    (0, 1, 0, -1, -1344.0),
+    (1, 0, 0, -1, -1335.0),
+    (0, 0, 3, 1, 1107.0),
+    (4, 0, 0, -1, 1021.0),
+    (4, 0, -1, 1, 833.0),
+    (0, 0, 1, -3, 777.0),
+    (4, 0, -2, 1, 671.0),
+    (2, 0, 0, -3, 607.0),
+    (2, 0, 2, -1, 596.0),
+    (2, -1, 1, -1, 491.0),
+    (2, 0, -2, 1, -451.0),
+    (0, 0, 3, -1, 439.0),
+    (2, 0, 2, 1, 422.0),
+    (2, 0, -3, -1, 421.0),
+    (2, 1, -1, 1, -366.0),
+    (2, 1, 0, 1, -351.0),
+    (4, 0, 0, 1, 331.0),
+    (2, -1, 1, 1, 315.0),
+    (2, -2, 0, -1, 302.0),
+    (0, 0, 1, 3, -283.0),
+    (2, 1, 1, -1, -229.0),
+    (1, 1, 0, -1, 223.0),
+    (1, 1, 0, 1, 223.0),
+    (0, 1, -2, -1, -220.0),
+    (2, 1, -1, -1, -220.0),
+    (1, 0, 1, 1, -185.0),
+    (2, -1, -2, -1, 181.0),
+    (0, 1, 2, 1, -177.0),
+    (4, 0, -2, -1, 176.0),
+    (4, -1, -1, -1, 166.0),
+    (1, 0, 1, -1, -164.0),
+    (4, 0, 1, -1, 132.0),
+    (1, 0, -1, -1, -119.0),
+    (4, -1, 0, -1, 115.0),
+    (2, -2, 0, 1, 107.0),
+];
+
+/// Mean radius of the Moon in km.
+pub const MOON_RADIUS: f64 = 1737.4;
+
+/// Equatorial radius of the Earth in km.
+pub const EARTH_RADIUS: f64 = 6378.14;
+
+/// Geocentric ecliptic longitude and latitude in degrees (mean equinox of
+/// date) and distance in km of the Moon at the TT Julian date `jde`,
+/// accurate to about 10" and 10 km.
+pub fn moon_position(jde: f64) -> (f64, f64, f64) {
+    let t = (jde - 2451545.0) / 36525.0;
+    let l = 218.3164477 + 481267.88123421 * t - 0.0015786 * t * t + t.powi(3) / 538841.0 - t.powi(4) / 65194000.0;
+    let d = 297.8501921 + 445267.1114034 * t - 0.0018819 * t * t + t.powi(3) / 545868.0 - t.powi(4) / 113065000.0;
+    let m = 357.5291092 + 35999.0502909 * t - 0.0001536 * t * t + t.powi(3) / 24490000.0;
+    let mp = 134.9633964 + 477198.8675055 * t + 0.0087414 * t * t + t.powi(3) / 69699.0 - t.powi(4) / 14712000.0;
+    let f = 93.2720950 + 483202.0175233 * t - 0.0036539 * t * t - t.powi(3) / 3526000.0 + t.powi(4) / 863310000.0;
+    let a1 = 119.75 + 131.849 * t;
+    let a2 = 53.09 + 479264.290 * t;
+    let a3 = 313.45 + 481266.484 * t;
+    // Terms with the Sun's anomaly shrink with the eccentricity of the Earth's orbit
+    let e = 1.0 - 0.002516 * t - 0.0000074 * t * t;
+    let argument = |cd: i8, cm: i8, cmp: i8, cf: i8| {
+        let arg = (cd as f64 * d + cm as f64 * m + cmp as f64 * mp + cf as f64 * f).to_radians();
+        (arg, e.powi(cm.abs() as i32))
+    };
+    let (mut sl, mut sr, mut sb) = (0.0, 0.0, 0.0);
+    for &(cd, cm, cmp, cf, cl, cr) in &LONGITUDE_DISTANCE {
+        let (arg, scale) = argument(cd, cm, cmp, cf);
+        sl += cl * scale * arg.sin();
+        sr += cr * scale * arg.cos();
+    }
+    for &(cd, cm, cmp, cf, cb) in &LATITUDE {
+        let (arg, scale) = argument(cd, cm, cmp, cf);
+        sb += cb * scale * arg.sin();
+    }
+    let rad = f64::to_radians;
+    sl += 3958.0 * rad(a1).sin() + 1962.0 * rad(l - f).sin() + 318.0 * rad(a2).sin();
+    sb += -2235.0 * rad(l).sin() + 382.0 * rad(a3).sin() + 175.0 * rad(a1 - f).sin() + 175.0 * rad(a1 + f).sin()
+        + 127.0 * rad(l - mp).sin()
+        - 115.0 * rad(l + mp).sin();
+    ((l + sl / 1e6).rem_euclid(360.0), sb / 1e6, 385000.56 + sr / 1000.0)
+}
+
+/// Earth–Moon distance in km, centre to centre, at the UTC Julian date `j`.
+pub fn moon_distance(j: f64) -> f64 {
+    moon_position(TimeScales::default().utc_to_tt(j)).2
+}
+
+/// Mean time from new moon to new moon in days.
+pub const SYNODIC_MONTH: f64 = 29.530588861;
+
+/// The principal phases of the Moon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoonPhase {
+    New,
+    FirstQuarter,
+    Full,
+    LastQuarter,
+}
+
+impl MoonPhase {
+    pub const ALL: [MoonPhase; 4] = [MoonPhase::New, MoonPhase::FirstQuarter, MoonPhase::Full, MoonPhase::LastQuarter];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoonPhase::New => "new moon",
+            MoonPhase::FirstQuarter => "first quarter",
+            MoonPhase::Full => "full moon",
+            MoonPhase::LastQuarter => "last quarter",
+        }
+    }
+
+    /// Geocentric elongation of the Moon from the Sun in degrees.
+    fn elongation(&self) -> f64 {
+        match self {
+            MoonPhase::New => 0.0,
+            MoonPhase::FirstQuarter => 90.0,
+            MoonPhase::Full => 180.0,
+            MoonPhase::LastQuarter => -90.0,
+        }
+    }
+
+    /// UTC Julian date of the first such phase at or after `j`.
+    pub fn next_after(&self, j: f64) -> f64 {
+        next_elongation(j, self.elongation()).0
+    }
+
+    /// UTC Julian date of the last such phase before `j`. Months run from
+    /// 29.3 to 29.8 days, so a window of 30 days can hold two.
+    pub fn previous_before(&self, j: f64) -> f64 {
+        let first = self.next_after(j - 30.0);
+        let second = self.next_after(first + 1.0);
+        if second < j {
+            second
+        } else {
+            first
+        }
+    }
+}
+
+impl FromStr for MoonPhase {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MoonPhase, String> {
+        match s.to_lowercase().as_str() {
+            "new" => Ok(MoonPhase::New),
+            "first-quarter" | "first" => Ok(MoonPhase::FirstQuarter),
+            "full" => Ok(MoonPhase::Full),
+            "last-quarter" | "last" => Ok(MoonPhase::LastQuarter),
+            _ => Err(format!("unknown moon phase `{}` (expected new, first-quarter, full or last-quarter)", s)),
+        }
+    }
+}
+
+/// Mean time from perigee to perigee in days.
+pub const ANOMALISTIC_MONTH: f64 = 27.55455;
+
+/// Whether the full moon at the UTC Julian date `j` is a supermoon in
+/// Nolle's sense: within 90% of the way from the apogee to the perigee of
+/// the orbit it falls in.
+pub fn is_supermoon(j: f64) -> bool {
+    let from = j - ANOMALISTIC_MONTH / 2.0;
+    let ((_, perigee), (_, apogee)) = (LunarApsis::Perigee.next_after(from), LunarApsis::Apogee.next_after(from));
+    moon_distance(j) <= apogee - 0.9 * (apogee - perigee)
+}
+
+/// The points of the Moon's orbit closest to and farthest from the Earth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LunarApsis {
+    Perigee,
+    Apogee,
+}
+
+impl LunarApsis {
+    pub const ALL: [LunarApsis; 2] = [LunarApsis::Perigee, LunarApsis::Apogee];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LunarApsis::Perigee => "perigee",
+            LunarApsis::Apogee => "apogee",
+        }
+    }
+
+    /// UTC Julian date of the first apsis after `j`, and the distance in km
+    /// then. The Sun's pull moves the apsides by days from their mean
+    /// period, so they are searched for rather than counted on.
+    pub fn next_after(&self, j: f64) -> (f64, f64) {
+        const STEP: f64 = 0.25;
+        let sign = match self {
+            LunarApsis::Perigee => 1.0,
+            LunarApsis::Apogee => -1.0,
+        };
+        let f = |j: f64| sign * moon_distance(j);
+        let (mut lo, mut mid) = (j, j + STEP);
+        let (mut f_lo, mut f_mid) = (f(lo), f(mid));
+        while mid < j + ANOMALISTIC_MONTH + 1.0 {
+            let f_hi = f(mid + STEP);
+            if f_mid <= f_lo && f_mid <= f_hi {
+                break;
+            }
+            (lo, f_lo, mid, f_mid) = (mid, f_mid, mid + STEP, f_hi);
+        }
+        let mut hi = mid + STEP;
+        while hi - lo > 1.0 / 1440.0 {
+            let (a, b) = (lo + (hi - lo) / 3.0, hi - (hi - lo) / 3.0);
+            if f(a) < f(b) {
+                hi = b;
+            } else {
+                lo = a;
+            }
+        }
+        let t = (lo + hi) / 2.0;
+        (t, moon_distance(t))
+    }
+}
+
+/// Elevation and azimuth in degrees of the Moon's centre seen from `lat`,
+/// `long` at the UTC Julian date `j`, corrected for the parallax of up to a
+/// degree but not for refraction.
+pub fn moon_horizontal(lat: f64, long: f64, j: f64) -> (f64, f64) {
+    moon_horizontal_with(&TimeScales::default(), lat, long, j)
+}
+
+/// Like [`moon_horizontal`] with the time scales `scales`.
+pub fn moon_horizontal_with(scales: &TimeScales, lat: f64, long: f64, j: f64) -> (f64, f64) {
+    let jde = scales.utc_to_tt(j);
+    let (lambda, beta, distance) = moon_position(jde);
+    let (ra, dec) = ecliptic_to_equatorial(lambda + nutation_in_longitude(jde), beta, jde);
+    let h = (sidereal_time(scales.utc_to_ut1(j) - 2451545.0, long) - ra).to_radians();
+    let (rlat, delta) = (lat.to_radians(), dec.to_radians());
+    let elevation = (rlat.sin() * delta.sin() + rlat.cos() * delta.cos() * h.cos()).asin().to_degrees();
+    let azimuth = h.sin().atan2(h.cos() * rlat.sin() - delta.tan() * rlat.cos()).to_degrees() + 180.0;
+    let parallax = (EARTH_RADIUS / distance).asin().to_degrees();
+    (elevation - parallax * elevation.to_radians().cos(), azimuth.rem_euclid(360.0))
+}
+
+/// Right ascension and declination in degrees of the ecliptic longitude
+/// `lambda` and latitude `beta` at the TT Julian date `jde`.
+pub fn ecliptic_to_equatorial(lambda: f64, beta: f64, jde: f64) -> (f64, f64) {
+    let (lambda, beta, epsilon) = (lambda.to_radians(), beta.to_radians(), obliquity(jde).to_radians());
+    let ra = (lambda.sin() * epsilon.cos() - beta.tan() * epsilon.sin()).atan2(lambda.cos());
+    let dec = (beta.sin() * epsilon.cos() + beta.cos() * epsilon.sin() * lambda.sin()).asin();
+    (ra.to_degrees().rem_euclid(360.0), dec.to_degrees())
+}
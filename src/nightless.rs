@@ -0,0 +1,44 @@
+//! Runs of days on which the sun never sinks below a twilight's lower
+//! limit, so the darker phase never comes: without true night for
+//! astronomical twilight, the white nights of nautical and civil twilight.
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::solar::{degrees::get_crossings, jdn, sun_position, Twilight};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Period {
+    pub twilight: Twilight,
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+impl Period {
+    pub fn days(&self) -> i64 {
+        (self.last - self.first).num_days() + 1
+    }
+}
+
+/// Whether the sun stays above `twilight`'s lower limit all through the
+/// day `today`: it does not cross the limit and stands above it at noon.
+pub fn is_nightless(lat: f64, long: f64, today: f64, twilight: Twilight) -> bool {
+    let (dawn, dusk) = get_crossings(lat, long, today, twilight.altitude());
+    (dawn.is_nan() || dusk.is_nan()) && sun_position(lat, long, today - long / 360.0).0 > twilight.altitude()
+}
+
+/// The periods of `year` without `twilight`'s night, in date order. A
+/// period running over New Year is cut at it.
+pub fn periods(lat: f64, long: f64, year: i32, twilight: Twilight) -> Option<Vec<Period>> {
+    let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+    let mut periods: Vec<Period> = Vec::new();
+    for date in first.iter_days().take_while(|d| d.year() == year) {
+        if !is_nightless(lat, long, jdn(date), twilight) {
+            continue;
+        }
+        match periods.last_mut() {
+            Some(period) if period.last.succ_opt() == Some(date) => period.last = date,
+            _ => periods.push(Period { twilight, first: date, last: date }),
+        }
+    }
+    Some(periods)
+}
@@ -0,0 +1,20 @@
+use std::error::Error;
+use std::process::Command;
+
+use super::{Notifier, Occurrence};
+
+/// Shows a desktop notification through `notify-send`.
+pub struct Desktop;
+
+impl Notifier for Desktop {
+    fn notify(&self, occurrence: &Occurrence) -> Result<(), Box<dyn Error>> {
+        let status = Command::new("notify-send")
+            .args(["--app-name=so2lar", &occurrence.summary(), &occurrence.body()])
+            .status()
+            .map_err(|e| format!("cannot run notify-send: {}", e))?;
+        if !status.success() {
+            return Err(format!("notify-send exited with {}", status).into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use std::error::Error;
+use std::process::Command;
+
+use super::{Notifier, Occurrence};
+
+/// Runs `command` through the shell at each event, with the event's name in
+/// `SO2LAR_EVENT`, its time in RFC 3339 in `SO2LAR_TIME` and the offset from
+/// it in signed seconds in `SO2LAR_OFFSET_SECONDS`, negative before it.
+pub struct Exec {
+    pub command: String,
+}
+
+impl Notifier for Exec {
+    fn notify(&self, occurrence: &Occurrence) -> Result<(), Box<dyn Error>> {
+        let mut cmd = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+        let status = cmd
+            .args([if cfg!(windows) { "/C" } else { "-c" }, &self.command])
+            .env("SO2LAR_EVENT", occurrence.event.name())
+            .env("SO2LAR_TIME", occurrence.time.to_rfc3339())
+            .env("SO2LAR_OFFSET_SECONDS", (-occurrence.lead.num_seconds()).to_string())
+            .status()
+            .map_err(|e| format!("cannot run `{}`: {}", self.command, e))?;
+        if !status.success() {
+            return Err(format!("`{}` exited with {}", self.command, status).into());
+        }
+        Ok(())
+    }
+}
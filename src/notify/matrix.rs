@@ -0,0 +1,53 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::Utc;
+
+use super::{curl, Notifier, Occurrence};
+use crate::format::{json_object, json_string};
+
+/// Posts each event as a text message to a Matrix room through the
+/// client-server API.
+pub struct Matrix {
+    /// Base URL such as `https://matrix.org`.
+    pub homeserver: String,
+    /// Room id such as `!abcdef:matrix.org`; the account must have joined it.
+    pub room: String,
+    pub access_token: String,
+    transactions: AtomicU64,
+}
+
+impl Matrix {
+    pub fn new(homeserver: String, room: String, access_token: String) -> Matrix {
+        Matrix { homeserver: homeserver.trim_end_matches('/').to_string(), room, access_token, transactions: AtomicU64::new(0) }
+    }
+
+    /// From the `homeserver`, `room` and `access_token` of a `[notify.matrix]` table.
+    pub fn from_settings(settings: &BTreeMap<String, String>) -> Result<Matrix, String> {
+        let get = |key: &str| settings.get(key).cloned().ok_or_else(|| format!("notify.matrix needs {}", key));
+        Ok(Matrix::new(get("homeserver")?, get("room")?, get("access_token")?))
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, for path segments.
+fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            b => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+impl Notifier for Matrix {
+    fn notify(&self, occurrence: &Occurrence) -> Result<(), Box<dyn Error>> {
+        // Transaction ids make retried requests idempotent; they must be unique per access token
+        let txn = format!("so2lar-{}-{}", Utc::now().timestamp_millis(), self.transactions.fetch_add(1, Ordering::Relaxed));
+        let url = format!("{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}", self.homeserver, encode(&self.room), txn);
+        let text = format!("{} ({})", occurrence.summary(), occurrence.body());
+        let body = json_object(&[("msgtype", json_string("m.text")), ("body", json_string(&text))]);
+        let auth = format!("Authorization: Bearer {}", self.access_token);
+        curl("PUT", &url, &[auth], &body).map_err(|e| format!("Matrix: {}", e).into())
+    }
+}
@@ -0,0 +1,129 @@
+use std::error::Error;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use chrono::{DateTime, Local, TimeDelta};
+
+use crate::solar::{Direction, ElevationCrossing, SolarEvent};
+
+pub mod desktop;
+pub mod exec;
+#[cfg(feature = "matrix")]
+pub mod matrix;
+#[cfg(feature = "telegram")]
+pub mod telegram;
+pub mod webhook;
+
+/// What a notification is about.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Solar(SolarEvent),
+    Crossing(ElevationCrossing),
+}
+
+impl Event {
+    pub fn name(&self) -> String {
+        match self {
+            Event::Solar(event) => event.name().to_string(),
+            Event::Crossing(crossing) => crossing.to_string(),
+        }
+    }
+}
+
+impl From<SolarEvent> for Event {
+    fn from(event: SolarEvent) -> Event {
+        Event::Solar(event)
+    }
+}
+
+pub struct Occurrence {
+    pub event: Event,
+    pub time: DateTime<Local>,
+    /// How long before the event the notification fires.
+    pub lead: TimeDelta,
+    pub lat: f64,
+    pub long: f64,
+}
+
+impl Occurrence {
+    /// A headline such as "Sunset in 10 minutes".
+    pub fn summary(&self) -> String {
+        let mut name = self.event.name();
+        name[..1].make_ascii_uppercase();
+        match self.lead.num_minutes() {
+            0 => format!("{} now", name),
+            1 => format!("{} in 1 minute", name),
+            m if m < 0 => format!("{} was {} minutes ago", name, -m),
+            m => format!("{} in {} minutes", name, m),
+        }
+    }
+
+    /// The event and its clock time, such as "Sunset at 18:42".
+    pub fn body(&self) -> String {
+        let mut name = self.event.name();
+        name[..1].make_ascii_uppercase();
+        format!("{} at {}", name, self.time.format("%H:%M"))
+    }
+}
+
+/// Sends `body` with `curl` to `url` using the HTTP `method` and extra
+/// `headers`, failing on HTTP errors. The URL, headers and body go to curl
+/// as a config on stdin, as tokens in them would show in the process list.
+pub(crate) fn curl(method: &str, url: &str, headers: &[String], body: &str) -> Result<(), Box<dyn Error>> {
+    let mut config = format!("url = {}\nrequest = {}\nheader = \"Content-Type: application/json\"\n", curl_quote(url), curl_quote(method));
+    for header in headers {
+        config.push_str(&format!("header = {}\n", curl_quote(header)));
+    }
+    config.push_str(&format!("data-raw = {}\n", curl_quote(body)));
+    let mut child = Command::new("curl")
+        .args(["-fsS", "--max-time", "10", "--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    child.stdin.take().unwrap().write_all(config.as_bytes())?;
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(format!("curl exited with {}", status).into());
+    }
+    Ok(())
+}
+
+/// `s` as a quoted string of curl's config syntax.
+fn curl_quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub trait Notifier {
+    fn notify(&self, occurrence: &Occurrence) -> Result<(), Box<dyn Error>>;
+}
+
+/// A notifier together with the events and lead times it is interested in.
+pub struct Subscription {
+    pub notifier: Box<dyn Notifier>,
+    pub events: Vec<SolarEvent>,
+    /// Altitudes of one's own, notified like `events`.
+    pub crossings: Vec<ElevationCrossing>,
+    pub leads: Vec<TimeDelta>,
+}
+
+impl Subscription {
+    /// Also notifies when the sun passes `angle` degrees in `direction`.
+    pub fn on_elevation_crossing(mut self, angle: f64, direction: Direction) -> Subscription {
+        self.crossings.push(ElevationCrossing { altitude: angle, direction });
+        self
+    }
+}
@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use super::{curl, Notifier, Occurrence};
+use crate::format::{json_object, json_string};
+
+/// Sends each event as a message from a Telegram bot to a chat.
+pub struct Telegram {
+    /// Token from @BotFather, `123456:ABC-DEF...`.
+    pub bot_token: String,
+    /// Numeric chat id, or `@channelname` for public channels.
+    pub chat_id: String,
+}
+
+impl Telegram {
+    /// From the `bot_token` and `chat_id` of a `[notify.telegram]` table.
+    pub fn from_settings(settings: &BTreeMap<String, String>) -> Result<Telegram, String> {
+        let get = |key: &str| settings.get(key).cloned().ok_or_else(|| format!("notify.telegram needs {}", key));
+        Ok(Telegram { bot_token: get("bot_token")?, chat_id: get("chat_id")? })
+    }
+}
+
+impl Notifier for Telegram {
+    fn notify(&self, occurrence: &Occurrence) -> Result<(), Box<dyn Error>> {
+        let text = format!("{} ({})", occurrence.summary(), occurrence.body());
+        let body = json_object(&[("chat_id", json_string(&self.chat_id)), ("text", json_string(&text))]);
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.bot_token);
+        curl("POST", &url, &[], &body).map_err(|e| format!("Telegram: {}", e).into())
+    }
+}
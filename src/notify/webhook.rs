@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+use super::{curl, Notifier, Occurrence};
+use crate::format::{json_object, json_string};
+use crate::hmac::{hex, hmac_sha256};
+
+/// POSTs a JSON description of each event to `url` using `curl`. With a
+/// secret, the body's HMAC-SHA256 is sent as `X-So2lar-Signature: sha256=<hex>`.
+pub struct Webhook {
+    pub url: String,
+    pub secret: Option<String>,
+    pub retries: u32,
+}
+
+impl Webhook {
+    fn payload(occurrence: &Occurrence) -> String {
+        json_object(&[
+            ("event", json_string(&occurrence.event.name())),
+            ("time", json_string(&occurrence.time.to_rfc3339())),
+            ("latitude", occurrence.lat.to_string()),
+            ("longitude", occurrence.long.to_string()),
+        ])
+    }
+
+    fn post(&self, body: &str) -> Result<(), Box<dyn Error>> {
+        let mut headers = Vec::new();
+        if let Some(secret) = &self.secret {
+            let signature = hex(&hmac_sha256(secret.as_bytes(), body.as_bytes()));
+            headers.push(format!("X-So2lar-Signature: sha256={}", signature));
+        }
+        curl("POST", &self.url, &headers, body)
+    }
+}
+
+impl Notifier for Webhook {
+    fn notify(&self, occurrence: &Occurrence) -> Result<(), Box<dyn Error>> {
+        let body = Self::payload(occurrence);
+        let mut attempt = 0;
+        loop {
+            match self.post(&body) {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.retries => {
+                    warn!("Webhook {} failed ({}), retrying", self.url, e);
+                    thread::sleep(Duration::from_secs(1 << attempt));
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use chrono::NaiveDate;
+
+use crate::solar::{jdn, next_anomaly, sun_distance, sun_position};
+use crate::timescale::TimeScales;
+
+/// Astronomical unit in km.
+pub const AU: f64 = 149597870.7;
+
+/// The points of the Earth's orbit closest to and farthest from the Sun.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Apsis {
+    Perihelion,
+    Aphelion,
+}
+
+impl Apsis {
+    pub const ALL: [Apsis; 2] = [Apsis::Perihelion, Apsis::Aphelion];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Apsis::Perihelion => "perihelion",
+            Apsis::Aphelion => "aphelion",
+        }
+    }
+
+    fn anomaly(&self) -> f64 {
+        match self {
+            Apsis::Perihelion => 0.0,
+            Apsis::Aphelion => 180.0,
+        }
+    }
+
+    /// UTC Julian date of the apsis in the Gregorian `year`. This is the
+    /// passage of the mean orbit; the Moon's pull moves the Earth's actual
+    /// closest approach by up to about two days.
+    pub fn in_year(&self, year: i32) -> Option<f64> {
+        let scales = TimeScales::default();
+        let start = jdn(NaiveDate::from_ymd_opt(year, 1, 1)?) - 0.5;
+        Some(scales.tt_to_utc(next_anomaly(scales.utc_to_tt(start), self.anomaly())))
+    }
+}
+
+/// Earth–Sun distance in astronomical units at the UTC Julian date `j`.
+pub fn distance(j: f64) -> f64 {
+    sun_distance(TimeScales::default().utc_to_tt(j))
+}
+
+/// Total solar irradiance at 1 AU in W/m² (Kopp & Lean, 2011).
+pub const SOLAR_CONSTANT: f64 = 1361.0;
+
+/// Irradiance in W/m² on a surface facing the Sun at the top of the
+/// atmosphere at the UTC Julian date `j`, scaled by the inverse square of
+/// the Earth–Sun distance.
+pub fn irradiance(j: f64) -> f64 {
+    SOLAR_CONSTANT / distance(j).powi(2)
+}
+
+/// Like [`irradiance`] on a horizontal surface at the location, zero while
+/// the Sun is below the horizon.
+pub fn horizontal_irradiance(lat: f64, long: f64, j: f64) -> f64 {
+    let (elevation, _) = sun_position(lat, long, j);
+    irradiance(j) * elevation.to_radians().sin().max(0.0)
+}
@@ -0,0 +1,53 @@
+//! Times of day shared by several places, such as when both ends of a call
+//! or a live video link are in daylight.
+
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
+
+use crate::solar::{degrees::crossings_tz, jdn, julian2utc, sun_position};
+
+/// A span of time from its start to its end.
+pub type Interval = (DateTime<Utc>, DateTime<Utc>);
+
+/// Daylight at `lat`, `long` with the sun above `altitude`, over the solar
+/// days before, of and after `date`, which covers any civil day around it.
+/// A day the sun does not set counts whole, from one midnight of mean
+/// solar time to the next.
+pub fn daylight(lat: f64, long: f64, date: NaiveDate, altitude: f64) -> Vec<Interval> {
+    (-1..=1)
+        .filter_map(|day| {
+            let today = jdn(date) + day as f64;
+            if let Some(crossings) = crossings_tz(lat, long, today, altitude, &Utc) {
+                return Some(crossings);
+            }
+            let noon = today - long / 360.0;
+            (sun_position(lat, long, noon).0 > altitude).then(|| {
+                let midnight = julian2utc(noon - 0.5);
+                (midnight, midnight + TimeDelta::days(1))
+            })
+        })
+        .collect()
+}
+
+/// The spans in both `a` and `b`, in time order; both must be in time order.
+pub fn intersect(a: &[Interval], b: &[Interval]) -> Vec<Interval> {
+    let mut spans = Vec::new();
+    for (a_start, a_end) in a {
+        for (b_start, b_end) in b {
+            let (start, end) = (*a_start.max(b_start), *a_end.min(b_end));
+            if start < end {
+                spans.push((start, end));
+            }
+        }
+    }
+    spans.sort();
+    // Adjacent whole days of polar daylight join up
+    spans.dedup_by(|next, prev| {
+        if next.0 <= prev.1 {
+            prev.1 = prev.1.max(next.1);
+            true
+        } else {
+            false
+        }
+    });
+    spans
+}
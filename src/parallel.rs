@@ -0,0 +1,38 @@
+//! Ordered parallel maps for computations over many days or locations.
+
+use std::env;
+use std::num::NonZeroUsize;
+use std::thread;
+
+use crate::progress::Progress;
+use crate::timing;
+
+/// Worker threads: `SO2LAR_THREADS` if set, otherwise one per core.
+pub fn threads() -> usize {
+    env::var("SO2LAR_THREADS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+}
+
+/// `f` applied to every item, spread over [`threads`] scoped threads in
+/// contiguous chunks. The results keep the order of `items`, so the output
+/// is the same as that of a serial map. Long maps show a [`Progress`] bar.
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = threads().min(items.len());
+    timing::map(threads);
+    let progress = Progress::new(items.len());
+    let f = &|item: &T| {
+        let result = f(item);
+        progress.inc();
+        result
+    };
+    if threads <= 1 {
+        return items.iter().map(f).collect();
+    }
+    thread::scope(|s| {
+        let workers: Vec<_> = items.chunks(items.len().div_ceil(threads)).map(|chunk| s.spawn(move || chunk.iter().map(f).collect::<Vec<R>>())).collect();
+        workers.into_iter().flat_map(|w| w.join().unwrap()).collect()
+    })
+}
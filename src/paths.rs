@@ -0,0 +1,88 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::platform::Files;
+
+const APP: &str = "so2lar";
+
+fn var_dir(name: &str) -> Option<PathBuf> {
+    env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+fn home() -> Option<PathBuf> {
+    var_dir("HOME").or_else(|| var_dir("USERPROFILE"))
+}
+
+/// `$XDG_CONFIG_HOME`, falling back to `~/.config`. systemd and most other
+/// Unix tools look there even on macOS.
+pub fn xdg_config_home() -> Option<PathBuf> {
+    var_dir("XDG_CONFIG_HOME").or_else(|| Some(home()?.join(".config")))
+}
+
+/// Directory of the configuration file: the XDG config directory on Unix,
+/// `~/Library/Application Support` on macOS and `%APPDATA%` on Windows.
+pub fn config_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        var_dir("APPDATA")
+    } else if cfg!(target_os = "macos") && var_dir("XDG_CONFIG_HOME").is_none() {
+        Some(home()?.join("Library").join("Application Support"))
+    } else {
+        xdg_config_home()
+    };
+    Some(base?.join(APP))
+}
+
+/// Directory for persistent state such as bookmarks: `$XDG_DATA_HOME`
+/// (`~/.local/share`), `~/Library/Application Support` or `%APPDATA%`.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        var_dir("APPDATA")
+    } else if cfg!(target_os = "macos") && var_dir("XDG_DATA_HOME").is_none() {
+        Some(home()?.join("Library").join("Application Support"))
+    } else {
+        var_dir("XDG_DATA_HOME").or_else(|| Some(home()?.join(".local").join("share")))
+    };
+    Some(base?.join(APP))
+}
+
+/// Directory for disposable data such as geocoding results: `$XDG_CACHE_HOME`
+/// (`~/.cache`), `~/Library/Caches` or `%LOCALAPPDATA%`.
+pub fn cache_dir() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        var_dir("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") && var_dir("XDG_CACHE_HOME").is_none() {
+        Some(home()?.join("Library").join("Caches"))
+    } else {
+        var_dir("XDG_CACHE_HOME").or_else(|| Some(home()?.join(".cache")))
+    };
+    Some(base?.join(APP))
+}
+
+pub fn config_file() -> Option<PathBuf> {
+    Some(config_dir()?.join("config.toml"))
+}
+
+pub fn bookmarks_file() -> Option<PathBuf> {
+    Some(data_dir()?.join("bookmarks.toml"))
+}
+
+pub fn last_location_file() -> Option<PathBuf> {
+    Some(data_dir()?.join("last-location"))
+}
+
+pub fn geocode_cache_file() -> Option<PathBuf> {
+    Some(cache_dir()?.join("geocode.tsv"))
+}
+
+/// Responses of the network features, see [`crate::fetch::HttpCache`].
+pub fn http_cache_dir() -> Option<PathBuf> {
+    Some(cache_dir()?.join("http"))
+}
+
+/// Creates the parent directory of `path` if needed.
+pub fn ensure_parent(files: &dyn Files, path: &Path) -> std::io::Result<()> {
+    match path.parent() {
+        Some(dir) => files.create_dir_all(dir),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+
+use crate::solar::{sun_position, Twilight};
+
+/// Illumination phase of the sky, from the altitude of the sun's centre.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Day,
+    Twilight { phase: Twilight, rising: bool },
+    Night,
+}
+
+impl Phase {
+    /// Phase at the UTC Julian date `j`.
+    pub fn at(lat: f64, long: f64, j: f64) -> Phase {
+        let (elevation, _) = sun_position(lat, long, j);
+        if elevation > Twilight::Civil.upper_altitude() {
+            return Phase::Day;
+        }
+        match Twilight::ALL.into_iter().find(|t| elevation > t.altitude()) {
+            Some(phase) => Phase::Twilight { phase, rising: sun_position(lat, long, j + 1.0 / 1440.0).0 > elevation },
+            None => Phase::Night,
+        }
+    }
+
+    /// First instant within two days after `j` at which the phase changes,
+    /// with the phase that follows.
+    pub fn next_change(lat: f64, long: f64, j: f64) -> Option<(f64, Phase)> {
+        const STEP: f64 = 1.0 / 1440.0;
+        let current = Phase::at(lat, long, j);
+        let (mut lo, mut hi) = (1..=2 * 1440)
+            .map(|i| (j + (i - 1) as f64 * STEP, j + i as f64 * STEP))
+            .find(|&(_, t)| Phase::at(lat, long, t) != current)?;
+        while hi - lo > 0.1 / 86400.0 {
+            let mid = (lo + hi) / 2.0;
+            if Phase::at(lat, long, mid) == current {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some((hi, Phase::at(lat, long, hi)))
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Phase::Day => write!(f, "day"),
+            Phase::Twilight { phase, rising: true } => write!(f, "{} dawn", phase.name()),
+            Phase::Twilight { phase, rising: false } => write!(f, "{} dusk", phase.name()),
+            Phase::Night => write!(f, "night"),
+        }
+    }
+}
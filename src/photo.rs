@@ -0,0 +1,80 @@
+//! The day as photographers see it: blue hour, golden hour and harsh light.
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::solar::{azimuth_at, degrees::crossings_tz};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Light {
+    /// Sun below -6°.
+    Night,
+    /// Sun between -6° and -4°.
+    BlueHour,
+    /// Sun between -4° and 6°.
+    GoldenHour,
+    /// Sun above 6°.
+    Harsh,
+}
+
+impl Light {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Light::Night => "night",
+            Light::BlueHour => "blue hour",
+            Light::GoldenHour => "golden hour",
+            Light::Harsh => "harsh light",
+        }
+    }
+
+    /// The light with the sun's centre at `elevation` degrees.
+    pub fn at(elevation: f64) -> Light {
+        BOUNDARIES.iter().rev().find(|(altitude, _, _)| elevation >= *altitude).map_or(Light::Night, |(_, _, above)| *above)
+    }
+}
+
+/// Sun altitudes separating the light below from the light above.
+const BOUNDARIES: [(f64, Light, Light); 3] =
+    [(-6.0, Light::Night, Light::BlueHour), (-4.0, Light::BlueHour, Light::GoldenHour), (6.0, Light::GoldenHour, Light::Harsh)];
+
+/// A change of light and the sun's azimuth at that moment.
+#[derive(Clone, Copy, Debug)]
+pub struct Transition {
+    pub time: DateTime<Utc>,
+    /// The light that begins.
+    pub light: Light,
+    pub azimuth: f64,
+}
+
+/// Changes of light in the course of the day `today`, in time order. Altitudes
+/// the sun does not cross that day, as in winter or near the poles, are left out.
+pub fn timeline(lat: f64, long: f64, today: f64) -> Vec<Transition> {
+    let mut transitions: Vec<Transition> = BOUNDARIES
+        .iter()
+        .filter_map(|&(altitude, below, above)| crossings_tz(lat, long, today, altitude, &Utc).map(|(rise, set)| [(rise, above), (set, below)]))
+        .flatten()
+        .map(|(time, light)| Transition { time, light, azimuth: azimuth_at(lat, long, &time) })
+        .collect();
+    transitions.sort_by_key(|t| t.time);
+    transitions
+}
+
+/// How far `t` is from the nearest golden hour of the days around it: zero
+/// during one, negative before one begins and positive after one ends.
+/// `None` when the sun does not pass through the golden hour around then.
+pub fn golden_hour_offset(lat: f64, long: f64, today: f64, t: DateTime<Utc>) -> Option<TimeDelta> {
+    let mut transitions: Vec<Transition> = (-1..=1).flat_map(|day| timeline(lat, long, today + day as f64)).collect();
+    transitions.sort_by_key(|t| t.time);
+    transitions
+        .windows(2)
+        .filter(|w| w[0].light == Light::GoldenHour)
+        .map(|w| {
+            if t < w[0].time {
+                t - w[0].time
+            } else if t > w[1].time {
+                t - w[1].time
+            } else {
+                TimeDelta::zero()
+            }
+        })
+        .min_by_key(|d| d.abs())
+}
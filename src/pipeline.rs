@@ -0,0 +1,152 @@
+//! Line-by-line processing for Unix pipelines: each input line is one
+//! request, a JSON object or a CSV record, and is answered by one line in
+//! the same form before the next is read, so memory stays constant however
+//! long the input runs.
+
+use std::io::{self, BufRead, Write};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::format::{csv_row, json_object, DisplayZone, Precision, Value};
+use crate::json::Json;
+use crate::solar::{datetime2julian, day_length, degrees::sunrise_sunset_tz, jdn, sun_position};
+use crate::validate;
+
+/// Columns of the answers, in CSV order.
+pub const COLUMNS: [&str; 9] = ["lat", "long", "date", "sunrise", "sunset", "day_length_seconds", "elevation", "azimuth", "error"];
+
+/// Columns of CSV requests without a header line.
+const DEFAULT_INPUT: [&str; 4] = ["lat", "long", "date", "time"];
+
+const INPUT_NAMES: [&str; 5] = ["lat", "long", "lon", "date", "time"];
+
+/// A location and day, and optionally an instant for the sun's position.
+struct Request {
+    lat: f64,
+    long: f64,
+    date: NaiveDate,
+    time: Option<DateTime<Utc>>,
+}
+
+/// Answers requests with the location and date given on the command line
+/// for fields they leave out.
+pub struct Pipeline {
+    pub lat: f64,
+    pub long: f64,
+    pub date: NaiveDate,
+    pub zone: DisplayZone,
+    pub precision: Precision,
+    /// Names of the CSV columns, from the last header line.
+    columns: Vec<String>,
+}
+
+impl Pipeline {
+    pub fn new(lat: f64, long: f64, date: NaiveDate, zone: DisplayZone, precision: Precision) -> Pipeline {
+        Pipeline { lat, long, date, zone, precision, columns: DEFAULT_INPUT.iter().map(|c| c.to_string()).collect() }
+    }
+
+    /// Answers the lines of `r` on `w` until `r` ends. Blank lines are skipped.
+    pub fn run<R: BufRead, W: Write>(&mut self, r: R, w: &mut W) -> io::Result<()> {
+        for line in r.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            writeln!(w, "{}", self.handle(&line))?;
+        }
+        Ok(())
+    }
+
+    /// The answer to one line. A CSV header line is answered with the
+    /// header of the answers and names the columns of the records after it.
+    pub fn handle(&mut self, line: &str) -> String {
+        let line = line.trim();
+        if line.starts_with('{') {
+            let parsed = Json::parse(line);
+            let id = parsed.as_ref().ok().and_then(|json| json.get("id")).map(Json::to_string);
+            let fields = match parsed.and_then(|json| self.json_request(&json)) {
+                Ok(request) => self.answer(&request),
+                Err(e) => error_fields(e),
+            };
+            let mut fields: Vec<(&str, String)> = fields.iter().filter(|(_, v)| !matches!(v, Value::Null)).map(|(k, v)| (*k, v.json())).collect();
+            if let Some(id) = id {
+                fields.insert(0, ("id", id));
+            }
+            return json_object(&fields);
+        }
+        let record: Vec<&str> = line.split(',').map(|f| f.trim().trim_matches('"')).collect();
+        if record.first().is_some_and(|f| f.parse::<f64>().is_err() && INPUT_NAMES.contains(&f.to_lowercase().as_str())) {
+            self.columns = record.iter().map(|f| f.to_lowercase()).collect();
+            return COLUMNS.join(",");
+        }
+        let fields = match self.csv_request(&record) {
+            Ok(request) => self.answer(&request),
+            Err(e) => error_fields(e),
+        };
+        csv_row(&fields.into_iter().map(|(_, v)| v).collect::<Vec<_>>())
+    }
+
+    fn json_request(&self, json: &Json) -> Result<Request, String> {
+        let number = |key: &str| match json.get(key) {
+            None | Some(Json::Null) => Ok(None),
+            Some(v) => v.as_f64().map(Some).ok_or_else(|| format!("{} must be a number", key)),
+        };
+        let text = |key: &str| match json.get(key) {
+            None | Some(Json::Null) => Ok(None),
+            Some(v) => v.as_str().map(Some).ok_or_else(|| format!("{} must be a string", key)),
+        };
+        let long = match number("long")? {
+            Some(long) => Some(long),
+            None => number("lon")?,
+        };
+        self.request(number("lat")?, long, text("date")?, text("time")?)
+    }
+
+    fn csv_request(&self, record: &[&str]) -> Result<Request, String> {
+        let field = |names: &[&str]| {
+            self.columns.iter().position(|c| names.contains(&c.as_str())).and_then(|i| record.get(i)).copied().filter(|f| !f.is_empty())
+        };
+        let number = |names: &[&str]| {
+            field(names).map(|f| f.parse::<f64>().map_err(|_| format!("invalid {} `{}`", names[0], f))).transpose()
+        };
+        self.request(number(&["lat"])?, number(&["long", "lon"])?, field(&["date"]), field(&["time"]))
+    }
+
+    fn request(&self, lat: Option<f64>, long: Option<f64>, date: Option<&str>, time: Option<&str>) -> Result<Request, String> {
+        let (lat, long) = validate::location(lat.unwrap_or(self.lat), long.unwrap_or(self.long))?;
+        let time = time
+            .map(|t| DateTime::parse_from_rfc3339(t).map(|t| t.to_utc()).map_err(|e| format!("invalid time `{}`: {} (expected RFC 3339)", t, e)))
+            .transpose()?;
+        let date = match (date, time) {
+            (Some(date), _) => validate::date(date)?,
+            (None, Some(time)) => self.zone.convert(&time).date_naive(),
+            (None, None) => self.date,
+        };
+        Ok(Request { lat, long, date, time })
+    }
+
+    fn answer(&self, request: &Request) -> Vec<(&'static str, Value)> {
+        let Request { lat, long, date, time } = *request;
+        let today = jdn(date);
+        let rise_set = sunrise_sunset_tz(lat, long, today, &Utc);
+        let format_time = |t: DateTime<Utc>| Value::from(self.precision.rfc3339(&self.zone.convert(&t)));
+        let position = time.map(|t| sun_position(lat, long, datetime2julian(&t)));
+        let round = |x: f64| (x * 1e4).round() / 1e4;
+        let values: [Value; 9] = [
+            lat.into(),
+            long.into(),
+            date.to_string().into(),
+            rise_set.map(|(rise, _)| format_time(rise)).into(),
+            rise_set.map(|(_, set)| format_time(set)).into(),
+            day_length(lat, long, today).num_seconds().into(),
+            position.map(|(elevation, _)| round(elevation)).into(),
+            position.map(|(_, azimuth)| round(azimuth)).into(),
+            Value::Null,
+        ];
+        COLUMNS.into_iter().zip(values).collect()
+    }
+}
+
+fn error_fields(e: String) -> Vec<(&'static str, Value)> {
+    COLUMNS.into_iter().map(|c| (c, if c == "error" { Value::from(e.as_str()) } else { Value::Null })).collect()
+}
@@ -0,0 +1,197 @@
+//! Rise, transit and set of the bright planets from the mean Keplerian
+//! elements of Standish, "Approximate Positions of the Planets" (JPL, valid
+//! 1800 to 2050). Positions are good to a few arc minutes, which moves the
+//! times by a minute or so, enough for planning a night outside.
+
+use std::str::FromStr;
+
+use crate::solar::{obliquity, sidereal_time};
+use crate::timescale::TimeScales;
+
+/// Altitude of a point-like planet at rising and setting, lowered by refraction.
+pub const RISE_ALTITUDE: f64 = -0.5667;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Planet {
+    Venus,
+    Mars,
+    Jupiter,
+    Saturn,
+}
+
+/// Semi-major axis (au), eccentricity, inclination, mean longitude,
+/// longitude of perihelion and of the ascending node (degrees) at J2000,
+/// each with its rate per Julian century.
+type Elements = [(f64, f64); 6];
+
+const EARTH_MOON_BARYCENTRE: Elements = [
+    (1.00000261, 0.00000562),
+    (0.01671123, -0.00004392),
+    (-0.00001531, -0.01294668),
+    (100.46457166, 35999.37244981),
+    (102.93768193, 0.32327364),
+    (0.0, 0.0),
+];
+
+impl Planet {
+    pub const ALL: [Planet; 4] = [Planet::Venus, Planet::Mars, Planet::Jupiter, Planet::Saturn];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Planet::Venus => "venus",
+            Planet::Mars => "mars",
+            Planet::Jupiter => "jupiter",
+            Planet::Saturn => "saturn",
+        }
+    }
+
+    fn elements(&self) -> Elements {
+        match self {
+            Planet::Venus => [
+                (0.72333566, 0.00000390),
+                (0.00677672, -0.00004107),
+                (3.39467605, -0.00078890),
+                (181.97909950, 58517.81538729),
+                (131.60246718, 0.00268329),
+                (76.67984255, -0.27769418),
+            ],
+            Planet::Mars => [
+                (1.52371034, 0.00001847),
+                (0.09339410, 0.00007882),
+                (1.84969142, -0.00813131),
+                (-4.55343205, 19140.30268499),
+                (-23.94362959, 0.44441088),
+                (49.55953891, -0.29257343),
+            ],
+            Planet::Jupiter => [
+                (5.20288700, -0.00011607),
+                (0.04838624, -0.00013253),
+                (1.30439695, -0.00183714),
+                (34.39644051, 3034.74612775),
+                (14.72847983, 0.21252668),
+                (100.47390909, 0.20469106),
+            ],
+            Planet::Saturn => [
+                (9.53667594, -0.00125060),
+                (0.05386179, -0.00050991),
+                (2.48599187, 0.00193609),
+                (49.95424423, 1222.49362201),
+                (92.59887831, -0.41897216),
+                (113.66242448, -0.28867794),
+            ],
+        }
+    }
+}
+
+impl FromStr for Planet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Planet, String> {
+        Planet::ALL.into_iter().find(|p| p.name().eq_ignore_ascii_case(s)).ok_or_else(|| {
+            let names: Vec<&str> = Planet::ALL.iter().map(Planet::name).collect();
+            format!("unknown planet `{}` (expected {})", s, names.join(", "))
+        })
+    }
+}
+
+/// Heliocentric ecliptic coordinates in au, J2000 ecliptic and equinox, at
+/// `t` Julian centuries of TT since J2000.
+fn heliocentric(elements: &Elements, t: f64) -> [f64; 3] {
+    let [a, e, i, l, perihelion, node] = elements.map(|(value, rate)| value + rate * t);
+    let omega = (perihelion - node).to_radians();
+    let m = (l - perihelion).rem_euclid(360.0).to_radians();
+    let (i, node) = (i.to_radians(), node.to_radians());
+    let mut ecc = m + e * m.sin();
+    for _ in 0..10 {
+        ecc -= (ecc - e * ecc.sin() - m) / (1.0 - e * ecc.cos());
+    }
+    let (x, y) = (a * (ecc.cos() - e), a * (1.0 - e * e).sqrt() * ecc.sin());
+    let (co, so, cn, sn, ci, si) = (omega.cos(), omega.sin(), node.cos(), node.sin(), i.cos(), i.sin());
+    [
+        (co * cn - so * sn * ci) * x + (-so * cn - co * sn * ci) * y,
+        (co * sn + so * cn * ci) * x + (-so * sn + co * cn * ci) * y,
+        so * si * x + co * si * y,
+    ]
+}
+
+/// Geocentric right ascension and declination in degrees, equinox of date,
+/// of `planet` at the TT Julian date `jde`.
+pub fn planet_equatorial(planet: Planet, jde: f64) -> (f64, f64) {
+    let t = (jde - 2451545.0) / 36525.0;
+    let (p, earth) = (heliocentric(&planet.elements(), t), heliocentric(&EARTH_MOON_BARYCENTRE, t));
+    let (x, y, z) = (p[0] - earth[0], p[1] - earth[1], p[2] - earth[2]);
+    // Precession from J2000 as a shift of the ecliptic longitude
+    let lambda = (y.atan2(x).to_degrees() + 1.396971 * t).to_radians();
+    let beta = z.atan2(x.hypot(y));
+    let epsilon = obliquity(jde).to_radians();
+    let ra = (lambda.sin() * epsilon.cos() - beta.tan() * epsilon.sin()).atan2(lambda.cos());
+    let dec = (beta.sin() * epsilon.cos() + beta.cos() * epsilon.sin() * lambda.sin()).asin();
+    (ra.to_degrees().rem_euclid(360.0), dec.to_degrees())
+}
+
+/// Hour angle in degrees from -180 to 180, and elevation and azimuth in
+/// degrees, of `planet` seen from `lat`, `long` at the UTC Julian date `j`.
+fn horizontal(scales: &TimeScales, planet: Planet, lat: f64, long: f64, j: f64) -> (f64, f64, f64) {
+    let (ra, dec) = planet_equatorial(planet, scales.utc_to_tt(j));
+    let hour_angle = (sidereal_time(scales.utc_to_ut1(j) - 2451545.0, long) - ra + 180.0).rem_euclid(360.0) - 180.0;
+    let (h, rlat, delta) = (hour_angle.to_radians(), lat.to_radians(), dec.to_radians());
+    let elevation = (rlat.sin() * delta.sin() + rlat.cos() * delta.cos() * h.cos()).asin().to_degrees();
+    let azimuth = h.sin().atan2(h.cos() * rlat.sin() - delta.tan() * rlat.cos()).to_degrees() + 180.0;
+    (hour_angle, elevation, azimuth.rem_euclid(360.0))
+}
+
+/// Elevation and azimuth in degrees of `planet` at the UTC Julian date `j`,
+/// without refraction.
+pub fn planet_horizontal(planet: Planet, lat: f64, long: f64, j: f64) -> (f64, f64) {
+    let (_, elevation, azimuth) = horizontal(&TimeScales::default(), planet, lat, long, j);
+    (elevation, azimuth)
+}
+
+/// Rise, transit and set of a planet within one day, as UTC Julian dates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlanetDay {
+    pub planet: Planet,
+    pub rise: Option<f64>,
+    pub transit: Option<f64>,
+    pub set: Option<f64>,
+    /// Elevation in degrees at the transit.
+    pub transit_altitude: Option<f64>,
+}
+
+impl PlanetDay {
+    /// The first rise, transit and set of `planet` in the day starting at
+    /// the UTC Julian date `from`.
+    pub fn new(planet: Planet, lat: f64, long: f64, from: f64) -> PlanetDay {
+        const STEP: f64 = 10.0 / 1440.0;
+        let scales = TimeScales::default();
+        let at = |j: f64| horizontal(&scales, planet, lat, long, j);
+        // First time in the day at which `f` turns from false to true
+        let find = |f: &dyn Fn(f64) -> bool| {
+            let mut lo = from;
+            let mut was = f(lo);
+            for i in 1..=144 {
+                let hi = from + i as f64 * STEP;
+                let is = f(hi);
+                if is && !was {
+                    let (mut lo, mut hi) = (lo, hi);
+                    while hi - lo > 1.0 / 86400.0 {
+                        let mid = (lo + hi) / 2.0;
+                        if f(mid) {
+                            hi = mid;
+                        } else {
+                            lo = mid;
+                        }
+                    }
+                    return Some(hi);
+                }
+                (lo, was) = (hi, is);
+            }
+            None
+        };
+        let rise = find(&|j| at(j).1 > RISE_ALTITUDE);
+        let set = find(&|j| at(j).1 < RISE_ALTITUDE);
+        // The hour angle wraps from 180 to -180 at the lower culmination
+        let transit = find(&|j| (0.0..90.0).contains(&at(j).0));
+        PlanetDay { planet, rise, transit, set, transit_altitude: transit.map(|j| at(j).1) }
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Timelike};
+
+use crate::format::DisplayZone;
+use crate::plot::png::Image;
+use crate::parallel;
+use crate::solar::{degrees::sunrise_sunset, jdn};
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 500.0;
+const MARGIN: f64 = 50.0;
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const SERIES: [(&str, &str, [u8; 3]); 3] = [
+    ("Sunrise", "#e69f00", [230, 159, 0]),
+    ("Solar noon", "#777777", [119, 119, 119]),
+    ("Sunset", "#d55e00", [213, 94, 0]),
+];
+
+/// Sunrise, solar noon and sunset of one day as clock times in the display
+/// zone, with the zone's UTC offset.
+pub struct DayTimes {
+    pub date: NaiveDate,
+    pub offset: FixedOffset,
+    pub times: [DateTime<FixedOffset>; 3],
+}
+
+/// Sunrise, noon and sunset through a year. Days without sunrise or sunset
+/// are `None`; together with offset changes they break the curves.
+pub struct YearCurves {
+    pub year: i32,
+    pub days: Vec<Option<DayTimes>>,
+}
+
+fn minutes_of_day(t: &DateTime<FixedOffset>) -> f64 {
+    t.hour() as f64 * 60.0 + t.minute() as f64 + t.second() as f64 / 60.0
+}
+
+impl YearCurves {
+    pub fn new(lat: f64, long: f64, year: i32, zone: DisplayZone) -> Option<YearCurves> {
+        let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+        let dates: Vec<NaiveDate> = first.iter_days().take_while(|d| d.year() == year).collect();
+        let days = parallel::map(&dates, |&date| {
+            let (rise, set) = sunrise_sunset(lat, long, jdn(date))?;
+            let noon = rise + (set - rise) / 2;
+            let times = [rise, noon, set].map(|t| zone.convert(&t));
+            Some(DayTimes { date, offset: *times[1].offset(), times })
+        });
+        Some(YearCurves { year, days })
+    }
+
+    /// Runs of consecutive days sharing a UTC offset, as (day of year, minutes
+    /// after midnight) points of the series `i`.
+    fn segments(&self, i: usize) -> Vec<Vec<(usize, f64)>> {
+        let mut segments: Vec<Vec<(usize, f64)>> = Vec::new();
+        let mut prev_offset = None;
+        for (day, times) in self.days.iter().enumerate() {
+            let Some(times) = times else {
+                prev_offset = None;
+                continue;
+            };
+            if prev_offset != Some(times.offset) {
+                segments.push(Vec::new());
+            }
+            prev_offset = Some(times.offset);
+            segments.last_mut().unwrap().push((day, minutes_of_day(&times.times[i])));
+        }
+        segments
+    }
+
+    fn x(&self, day: usize) -> f64 {
+        MARGIN + day as f64 / self.days.len() as f64 * (WIDTH - 2.0 * MARGIN)
+    }
+
+    fn month_start(&self, month: usize) -> usize {
+        NaiveDate::from_ymd_opt(self.year, month as u32 + 1, 1).map_or(0, |d| d.ordinal0() as usize)
+    }
+
+    fn y(minutes: f64) -> f64 {
+        MARGIN + minutes / 1440.0 * (HEIGHT - 2.0 * MARGIN)
+    }
+
+    pub fn write_svg<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="sans-serif" font-size="12">"#, WIDTH, HEIGHT)?;
+        writeln!(w, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+        writeln!(w, r#"<text x="{}" y="{}" text-anchor="middle" font-size="14">Sunrise and sunset {}</text>"#, WIDTH / 2.0, MARGIN / 2.0, self.year)?;
+        for hour in (0..=24).step_by(3) {
+            let y = Self::y(hour as f64 * 60.0);
+            writeln!(w, r##"<line x1="{}" y1="{y:.1}" x2="{}" y2="{y:.1}" stroke="#ddd"/>"##, MARGIN, WIDTH - MARGIN)?;
+            writeln!(w, r#"<text x="{}" y="{:.1}" text-anchor="end">{:02}:00</text>"#, MARGIN - 5.0, y + 4.0, hour)?;
+        }
+        for (month, name) in MONTHS.iter().enumerate() {
+            let x = self.x(self.month_start(month));
+            writeln!(w, r##"<line x1="{x:.1}" y1="{}" x2="{x:.1}" y2="{}" stroke="#ddd"/>"##, MARGIN, HEIGHT - MARGIN)?;
+            writeln!(w, r#"<text x="{:.1}" y="{}">{}</text>"#, x + 3.0, HEIGHT - MARGIN + 15.0, name)?;
+        }
+        for (i, (name, color, _)) in SERIES.iter().enumerate() {
+            for segment in self.segments(i) {
+                let points: Vec<String> = segment.iter().map(|&(day, m)| format!("{:.1},{:.1}", self.x(day), Self::y(m))).collect();
+                writeln!(w, r#"<polyline fill="none" stroke="{}" stroke-width="2" points="{}"/>"#, color, points.join(" "))?;
+            }
+            let y = HEIGHT - 12.0;
+            let x = MARGIN + i as f64 * 120.0;
+            writeln!(w, r#"<line x1="{x}" y1="{}" x2="{}" y2="{}" stroke="{}" stroke-width="2"/>"#, y - 4.0, x + 20.0, y - 4.0, color)?;
+            writeln!(w, r#"<text x="{}" y="{}">{}</text>"#, x + 25.0, y, name)?;
+        }
+        writeln!(w, "</svg>")
+    }
+
+    pub fn render_png(&self) -> Image {
+        let mut image = Image::new(WIDTH as u32, HEIGHT as u32, [255, 255, 255]);
+        for hour in (0..=24).step_by(3) {
+            image.fill_rect(MARGIN as u32, Self::y(hour as f64 * 60.0) as u32, (WIDTH - 2.0 * MARGIN) as u32, 1, [221, 221, 221]);
+        }
+        for month in 0..12 {
+            image.fill_rect(self.x(self.month_start(month)) as u32, MARGIN as u32, 1, (HEIGHT - 2.0 * MARGIN) as u32, [221, 221, 221]);
+        }
+        for (i, (_, _, rgb)) in SERIES.iter().enumerate() {
+            for segment in self.segments(i) {
+                for pair in segment.windows(2) {
+                    let ((d0, m0), (d1, m1)) = (pair[0], pair[1]);
+                    let (x0, y0, x1, y1) = (self.x(d0), Self::y(m0), self.x(d1), Self::y(m1));
+                    let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0) as usize;
+                    for s in 0..=steps {
+                        let t = s as f64 / steps as f64;
+                        let (x, y) = (x0 + t * (x1 - x0), y0 + t * (y1 - y0));
+                        image.fill_rect(x as u32, (y as u32).saturating_sub(1), 2, 2, *rgb);
+                    }
+                }
+            }
+        }
+        image
+    }
+}
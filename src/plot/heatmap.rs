@@ -0,0 +1,68 @@
+use chrono::{Datelike, Days, NaiveDate, NaiveTime, TimeDelta};
+
+use crate::format::DisplayZone;
+use crate::parallel;
+use crate::plot::png::Image;
+use crate::solar::{datetime2julian, sun_position, Twilight};
+
+const DAY: [u8; 3] = [255, 214, 102];
+const CIVIL: [u8; 3] = [120, 170, 220];
+const NAUTICAL: [u8; 3] = [50, 90, 160];
+const ASTRONOMICAL: [u8; 3] = [25, 40, 90];
+const NIGHT: [u8; 3] = [8, 10, 30];
+const GRID: [u8; 3] = [128, 128, 128];
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+
+const MARGIN: u32 = 16;
+const DAY_WIDTH: u32 = 2;
+const MINUTES_PER_ROW: u32 = 5;
+
+fn band(elevation: f64) -> [u8; 3] {
+    if elevation > Twilight::Civil.upper_altitude() {
+        DAY
+    } else if elevation > Twilight::Civil.altitude() {
+        CIVIL
+    } else if elevation > Twilight::Nautical.altitude() {
+        NAUTICAL
+    } else if elevation > Twilight::Astronomical.altitude() {
+        ASTRONOMICAL
+    } else {
+        NIGHT
+    }
+}
+
+/// Renders a year of daylight with the date running left to right and the
+/// clock time in `zone` top to bottom, coloured by day, twilight phase and
+/// night. Grid lines mark the months and every third hour.
+pub fn render(lat: f64, long: f64, year: i32, zone: DisplayZone) -> Option<Image> {
+    let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+    let days = NaiveDate::from_ymd_opt(year + 1, 1, 1)?.signed_duration_since(first).num_days() as u32;
+    let rows = 24 * 60 / MINUTES_PER_ROW;
+    let mut image = Image::new(days * DAY_WIDTH + 2 * MARGIN, rows + 2 * MARGIN, BACKGROUND);
+    let dates: Vec<NaiveDate> = (0..days).map(|day| first.checked_add_days(Days::new(day as u64))).collect::<Option<_>>()?;
+    let columns = parallel::map(&dates, |date| {
+        let midnight = date.and_time(NaiveTime::MIN);
+        (0..rows)
+            .map(|row| {
+                let local = midnight + TimeDelta::minutes((row * MINUTES_PER_ROW) as i64);
+                let utc = local - zone.offset_at_utc(&local);
+                band(sun_position(lat, long, datetime2julian(&utc.and_utc())).0)
+            })
+            .collect::<Vec<_>>()
+    });
+    for (day, (date, column)) in (0..).zip(dates.iter().zip(columns)) {
+        for (row, colour) in (0..).zip(column) {
+            image.fill_rect(MARGIN + day * DAY_WIDTH, MARGIN + row, DAY_WIDTH, 1, colour);
+        }
+        if date.day() == 1 {
+            image.fill_rect(MARGIN + day * DAY_WIDTH, MARGIN / 2, 1, rows + MARGIN, GRID);
+        }
+    }
+    for hour in (0..=24).step_by(3) {
+        let y = MARGIN + hour * 60 / MINUTES_PER_ROW;
+        for x in (MARGIN / 2..image.width - MARGIN / 2).step_by(2) {
+            image.set(x, y, GRID);
+        }
+    }
+    Some(image)
+}
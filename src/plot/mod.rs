@@ -0,0 +1,3 @@
+pub mod curves;
+pub mod heatmap;
+pub mod png;
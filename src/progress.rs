@@ -0,0 +1,80 @@
+//! A progress bar with an estimate of the time left, drawn on stderr for
+//! computations over many days or points. It appears only once a job has
+//! run for a second, so short runs print nothing, and never when stderr is
+//! not a terminal.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a job runs before the bar appears.
+const DELAY: Duration = Duration::from_secs(1);
+
+/// How often the bar is redrawn.
+const REDRAW: Duration = Duration::from_millis(100);
+
+const WIDTH: usize = 30;
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turns progress bars on or off, for `--quiet` and machine-readable output.
+pub fn init(enabled: bool) {
+    ENABLED.store(enabled && io::stderr().is_terminal(), Ordering::SeqCst);
+}
+
+/// Progress through `total` items, shared by the threads working on them.
+pub struct Progress {
+    total: usize,
+    done: AtomicUsize,
+    start: Instant,
+    /// When the bar was last drawn, `None` before the first time.
+    drawn: Mutex<Option<Instant>>,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Progress {
+        Progress { total, done: AtomicUsize::new(0), start: Instant::now(), drawn: Mutex::new(None) }
+    }
+
+    /// Counts one more item as done.
+    pub fn inc(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        if !ENABLED.load(Ordering::Relaxed) || self.start.elapsed() < DELAY {
+            return;
+        }
+        let Ok(mut drawn) = self.drawn.try_lock() else {
+            return;
+        };
+        if drawn.is_some_and(|t| t.elapsed() < REDRAW) && done < self.total {
+            return;
+        }
+        *drawn = Some(Instant::now());
+        let _ = write!(io::stderr(), "\r\x1b[K{}", self.line(done));
+    }
+
+    fn line(&self, done: usize) -> String {
+        let fraction = done as f64 / self.total.max(1) as f64;
+        let filled = (fraction * WIDTH as f64) as usize;
+        let left = self.start.elapsed().as_secs_f64() * (1.0 / fraction - 1.0);
+        format!(
+            "[{}{}] {}/{} {:3.0}% ETA {}:{:02}",
+            "=".repeat(filled),
+            " ".repeat(WIDTH - filled),
+            done,
+            self.total,
+            fraction * 100.0,
+            left as u64 / 60,
+            left as u64 % 60
+        )
+    }
+}
+
+impl Drop for Progress {
+    /// Clears the bar so the output that follows starts on a clean line.
+    fn drop(&mut self) {
+        if self.drawn.get_mut().is_ok_and(|d| d.is_some()) {
+            let _ = write!(io::stderr(), "\r\x1b[K");
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Atmospheric refraction, which lifts the sun by about half a degree at the
+//! horizon. Standards differ on how to model it there.
+
+use std::str::FromStr;
+
+use crate::constants::HORIZON_REFRACTION;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RefractionModel {
+    /// Geometric positions.
+    None,
+    /// Bennett (1982), from the apparent altitude, as used by almanacs.
+    Bennett,
+    /// Sæmundsson (1986), from the true altitude.
+    Saemundsson,
+    /// The same refraction in degrees at every altitude, the classic 34' of
+    /// the sunrise equation by default.
+    Constant(f64),
+}
+
+impl Default for RefractionModel {
+    fn default() -> RefractionModel {
+        RefractionModel::Constant(HORIZON_REFRACTION)
+    }
+}
+
+impl RefractionModel {
+    /// Refraction in degrees of a body at the true (geometric) altitude `altitude`.
+    pub fn from_true(&self, altitude: f64) -> f64 {
+        match *self {
+            RefractionModel::None => 0.0,
+            RefractionModel::Saemundsson => saemundsson(altitude),
+            // Fixed point of h + R(h + R) for the apparent altitude
+            RefractionModel::Bennett => (0..5).fold(0.0, |r, _| bennett(altitude + r)),
+            RefractionModel::Constant(r) => r,
+        }
+    }
+
+    /// Apparent altitude of a body at the true altitude `altitude`.
+    pub fn apparent(&self, altitude: f64) -> f64 {
+        altitude + self.from_true(altitude)
+    }
+
+    /// Refraction in degrees of a body that appears on the horizon.
+    pub fn at_horizon(&self) -> f64 {
+        match *self {
+            RefractionModel::None => 0.0,
+            RefractionModel::Bennett => bennett(0.0),
+            RefractionModel::Saemundsson => (0..5).fold(0.0, |r, _| saemundsson(-r)),
+            RefractionModel::Constant(r) => r,
+        }
+    }
+}
+
+/// Bennett's formula in degrees for the apparent altitude `h`.
+fn bennett(h: f64) -> f64 {
+    let h = h.max(-1.0);
+    1.0 / (h + 7.31 / (h + 4.4)).to_radians().tan() / 60.0
+}
+
+/// Sæmundsson's formula in degrees for the true altitude `h`.
+fn saemundsson(h: f64) -> f64 {
+    let h = h.max(-1.5);
+    1.02 / (h + 10.3 / (h + 5.11)).to_radians().tan() / 60.0
+}
+
+impl FromStr for RefractionModel {
+    type Err = String;
+
+    /// `none`, `bennett`, `saemundsson`, or a constant in degrees (`0.5667`) or arc minutes (`34'`).
+    fn from_str(s: &str) -> Result<RefractionModel, String> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(RefractionModel::None),
+            "bennett" => Ok(RefractionModel::Bennett),
+            "saemundsson" | "sæmundsson" => Ok(RefractionModel::Saemundsson),
+            s => {
+                let (number, scale) = s.strip_suffix('\'').map_or((s, 1.0), |n| (n, 1.0 / 60.0));
+                number.trim().parse::<f64>().map(|r| RefractionModel::Constant(r * scale)).map_err(|_| {
+                    format!("unknown refraction model `{}` (expected none, bennett, saemundsson or a constant like 34')", s)
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use chrono::{Datelike, NaiveDate, TimeDelta};
+
+use crate::config::Config;
+use crate::duration::format_hm;
+use crate::format::DisplayZone;
+use crate::solar::{day_length, degrees::sunrise_sunset, jdn, julian2datetime, DaySolution};
+use crate::validate;
+
+const HELP: &str = "set loc LAT LONG | set loc NAME   change the location (NAME from the configuration file)
+set date YYYY-MM-DD              change the date
+set zone local|utc               change the time zone of printed times
+date [+N|-N|YYYY-MM-DD]          show or move the date
+loc                              show the location
+rise, sunset, noon, length       one event of the current date
+events                           all of them
+table MON                        sunrise, sunset and day length for each day of a month (jan..dec)
+help, quit";
+
+/// State of an interactive session: queries answer for the current location
+/// and date until a `set` command changes them.
+pub struct Repl {
+    pub lat: f64,
+    pub long: f64,
+    pub date: NaiveDate,
+    pub zone: DisplayZone,
+    pub config: Config,
+}
+
+impl Repl {
+    /// Evaluates lines from `r` until it ends or `quit`, prompting when stdin is a terminal.
+    pub fn run<R: BufRead, W: Write>(&mut self, r: R, w: &mut W) -> io::Result<()> {
+        let prompt = io::stdin().is_terminal();
+        let mut lines = r.lines();
+        loop {
+            if prompt {
+                write!(w, "so2lar> ")?;
+                w.flush()?;
+            }
+            let Some(line) = lines.next() else { break };
+            let line = line?;
+            match line.trim() {
+                "" => continue,
+                "quit" | "exit" => break,
+                line => match self.eval(line) {
+                    Ok(output) => writeln!(w, "{}", output)?,
+                    Err(e) => writeln!(w, "error: {}", e)?,
+                },
+            }
+            w.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Output of one command, updating the state for `set` and `date`.
+    pub fn eval(&mut self, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["help"] => Ok(HELP.to_string()),
+            ["set", "loc", lat, long] => {
+                self.lat = validate::latitude(lat.parse().map_err(|_| format!("invalid latitude `{}`", lat))?)?;
+                self.long = validate::longitude(long.parse().map_err(|_| format!("invalid longitude `{}`", long))?)?;
+                Ok(self.location())
+            }
+            ["set", "loc", name] => {
+                let profile = self.config.location(name)?;
+                (self.lat, self.long) = (profile.lat, profile.long);
+                Ok(self.location())
+            }
+            ["set", "date", date] | ["date", date] if !date.starts_with(['+', '-']) => {
+                self.date = validate::date(date)?;
+                Ok(self.date.to_string())
+            }
+            ["date", days] => {
+                let days: i64 = days.parse().map_err(|_| format!("invalid day offset `{}`", days))?;
+                self.date = self.date.checked_add_signed(TimeDelta::days(days)).ok_or("date out of range")?;
+                Ok(self.date.to_string())
+            }
+            ["date"] => Ok(self.date.to_string()),
+            ["set", "zone", zone] => {
+                self.zone = match *zone {
+                    "local" => DisplayZone::Local,
+                    "utc" => DisplayZone::Utc,
+                    _ => return Err(format!("unknown zone `{}` (expected local or utc)", zone)),
+                };
+                Ok(zone.to_string())
+            }
+            ["loc"] => Ok(self.location()),
+            ["rise"] => Ok(self.event(0)),
+            ["sunset"] => Ok(self.event(1)),
+            ["noon"] => Ok(self.noon()),
+            ["length"] => Ok(format_hm(day_length(self.lat, self.long, jdn(self.date)))),
+            ["events"] => Ok(format!(
+                "sunrise {}\nnoon    {}\nsunset  {}\nlength  {}",
+                self.event(0),
+                self.noon(),
+                self.event(1),
+                format_hm(day_length(self.lat, self.long, jdn(self.date)))
+            )),
+            ["table", month] => self.table(month),
+            _ => Err(format!("unknown command `{}`, try help", line)),
+        }
+    }
+
+    fn location(&self) -> String {
+        format!("{:.4} {:.4}", self.lat, self.long)
+    }
+
+    /// Sunrise (`which == 0`) or sunset on the current date.
+    fn event(&self, which: usize) -> String {
+        match sunrise_sunset(self.lat, self.long, jdn(self.date)) {
+            Some(times) => {
+                let t = if which == 0 { times.0 } else { times.1 };
+                self.zone.convert(&t).format("%Y-%m-%d %H:%M:%S %:z").to_string()
+            }
+            None => "none".to_string(),
+        }
+    }
+
+    fn noon(&self) -> String {
+        let transit = DaySolution::from_degrees(self.lat, self.long, jdn(self.date)).j_transit;
+        self.zone.convert(&julian2datetime(transit)).format("%Y-%m-%d %H:%M:%S %:z").to_string()
+    }
+
+    fn table(&self, month: &str) -> Result<String, String> {
+        const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+        let index = MONTHS
+            .iter()
+            .position(|m| month.to_lowercase().starts_with(m))
+            .ok_or_else(|| format!("unknown month `{}`", month))?;
+        let first = NaiveDate::from_ymd_opt(self.date.year(), index as u32 + 1, 1).unwrap();
+        let mut rows = vec!["date        sunrise   sunset    length".to_string()];
+        for day in first.iter_days().take_while(|d| d.month() == first.month()) {
+            let today = jdn(day);
+            let (rise, set) = match sunrise_sunset(self.lat, self.long, today) {
+                Some((rise, set)) => (
+                    self.zone.convert(&rise).format("%H:%M:%S").to_string(),
+                    self.zone.convert(&set).format("%H:%M:%S").to_string(),
+                ),
+                None => ("--".to_string(), "--".to_string()),
+            };
+            rows.push(format!("{}  {:8}  {:8}  {}", day, rise, set, format_hm(day_length(self.lat, self.long, today))));
+        }
+        Ok(rows.join("\n"))
+    }
+}
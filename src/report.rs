@@ -0,0 +1,265 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Write};
+use std::io;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, TimeDelta};
+
+use crate::duration::{format_hm, format_signed};
+use crate::format::{DisplayZone, Field, OutputFormatter, Precision, Table, Value};
+use crate::parallel;
+use crate::sample::Sample;
+use crate::seasons::{Hemisphere, SeasonMarker};
+use crate::solar::{day_length, degrees::sunrise_sunset, jdn, julian2utc};
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+pub fn format_coords(lat: f64, long: f64) -> String {
+    let ns = if lat >= 0.0 { 'N' } else { 'S' };
+    let ew = if long >= 0.0 { 'E' } else { 'W' };
+    format!("{:.4}°{}, {:.4}°{}", lat.abs(), ns, long.abs(), ew)
+}
+
+pub struct YearReport {
+    pub lat: f64,
+    pub long: f64,
+    pub year: i32,
+    pub days: Vec<(NaiveDate, TimeDelta)>,
+    pub markers: Vec<(SeasonMarker, DateTime<FixedOffset>)>,
+    /// Whose seasons the markers are named for, by default that of `lat`.
+    pub hemisphere: Hemisphere,
+}
+
+impl YearReport {
+    pub fn new(lat: f64, long: f64, year: i32, zone: DisplayZone) -> Option<YearReport> {
+        let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+        let last = NaiveDate::from_ymd_opt(year, 12, 31)?;
+        let dates: Vec<NaiveDate> = first.iter_days().take_while(|d| *d <= last).collect();
+        let days = parallel::map(&dates, |d| (*d, day_length(lat, long, jdn(*d))));
+        let markers = SeasonMarker::ALL.iter().map(|m| (*m, zone.convert(&julian2utc(m.in_year(year))))).collect();
+        Some(YearReport { lat, long, year, days, markers, hemisphere: Hemisphere::of(lat) })
+    }
+
+    pub fn total(&self) -> TimeDelta {
+        self.days.iter().map(|(_, len)| *len).sum()
+    }
+
+    /// First day of maximal length, which is the onset of midnight sun at high latitudes.
+    pub fn longest(&self) -> (NaiveDate, TimeDelta) {
+        *self.days.iter().rev().max_by_key(|(_, len)| *len).unwrap()
+    }
+
+    pub fn shortest(&self) -> (NaiveDate, TimeDelta) {
+        *self.days.iter().min_by_key(|(_, len)| *len).unwrap()
+    }
+
+    /// Mean day length of each month, January first.
+    pub fn monthly_means(&self) -> Vec<TimeDelta> {
+        (1..=12)
+            .map(|m| {
+                let lens: Vec<_> = self.days.iter().filter(|(d, _)| d.month() == m).map(|(_, len)| *len).collect();
+                lens.iter().sum::<TimeDelta>() / lens.len() as i32
+            })
+            .collect()
+    }
+
+    fn text(&self) -> Result<String, fmt::Error> {
+        let mut w = String::new();
+        writeln!(w, "Daylight report {} for {}", self.year, format_coords(self.lat, self.long))?;
+        writeln!(w)?;
+        let total = self.total();
+        writeln!(w, "Total daylight: {}h {}m", total.num_hours(), total.num_minutes() % 60)?;
+        let (date, len) = self.longest();
+        writeln!(w, "Longest day:    {} ({})", date, format_hm(len))?;
+        let (date, len) = self.shortest();
+        writeln!(w, "Shortest day:   {} ({})", date, format_hm(len))?;
+        writeln!(w)?;
+        writeln!(w, "Month  Mean day length")?;
+        for (m, mean) in self.monthly_means().iter().enumerate() {
+            writeln!(w, "{}    {}", MONTHS[m], format_hm(*mean))?;
+        }
+        writeln!(w)?;
+        for (marker, t) in &self.markers {
+            let name = format!("{} ({}):", marker.name(), marker.local_name(self.hemisphere));
+            writeln!(w, "{:<37} {}", name, t.format("%Y-%m-%d %H:%M"))?;
+        }
+        Ok(w)
+    }
+
+    pub fn write(&self, w: &mut dyn io::Write, formatter: &dyn OutputFormatter) -> io::Result<()> {
+        let (longest, longest_len) = self.longest();
+        let (shortest, shortest_len) = self.shortest();
+        let mut fields: Vec<(String, Value)> = vec![
+            ("year".to_string(), (self.year as i64).into()),
+            ("latitude".to_string(), self.lat.into()),
+            ("longitude".to_string(), self.long.into()),
+            ("total_seconds".to_string(), self.total().num_seconds().into()),
+            ("longest".to_string(), longest.to_string().into()),
+            ("longest_seconds".to_string(), longest_len.num_seconds().into()),
+            ("shortest".to_string(), shortest.to_string().into()),
+            ("shortest_seconds".to_string(), shortest_len.num_seconds().into()),
+        ];
+        for (m, mean) in self.monthly_means().iter().enumerate() {
+            fields.push((format!("mean_{}_seconds", MONTHS[m].to_lowercase()), mean.num_seconds().into()));
+        }
+        for (marker, t) in &self.markers {
+            fields.push((marker.name().to_lowercase().replace(' ', "_"), t.to_rfc3339().into()));
+        }
+        let fields: Vec<(&str, Value)> = fields.iter().map(|(k, v)| (k.as_str(), v.clone())).collect();
+        formatter.record(w, &fields, &self.text().unwrap())
+    }
+}
+
+pub struct DayRow {
+    pub date: NaiveDate,
+    /// UTC offset of the display zone at noon UTC of `date`.
+    pub offset: FixedOffset,
+    pub rise: Option<DateTime<FixedOffset>>,
+    pub set: Option<DateTime<FixedOffset>>,
+    pub length: TimeDelta,
+}
+
+pub struct MonthReport {
+    pub lat: f64,
+    pub long: f64,
+    pub month: NaiveDate,
+    pub days: Vec<DayRow>,
+    /// Offset before the first day, to detect a transition on the 1st.
+    pub previous_offset: FixedOffset,
+    /// Add sunrise/sunset columns in standard time, ignoring daylight saving.
+    pub standard_time: bool,
+    pub zone: DisplayZone,
+    pub precision: Precision,
+    /// Days listed in the table; the summary still covers the whole month.
+    pub sample: Sample,
+}
+
+fn offset_on(zone: DisplayZone, date: NaiveDate) -> FixedOffset {
+    zone.offset_at_utc(&date.and_hms_opt(12, 0, 0).unwrap())
+}
+
+/// Offset of `zone` without daylight saving, taken as the smaller of the
+/// January and July offsets of the year.
+pub fn standard_offset(zone: DisplayZone, year: i32) -> FixedOffset {
+    let jan = offset_on(zone, NaiveDate::from_ymd_opt(year, 1, 1).unwrap());
+    let jul = offset_on(zone, NaiveDate::from_ymd_opt(year, 7, 1).unwrap());
+    if jan.local_minus_utc() <= jul.local_minus_utc() { jan } else { jul }
+}
+
+impl MonthReport {
+    /// Report for the month containing `month`.
+    pub fn new(lat: f64, long: f64, month: NaiveDate, zone: DisplayZone) -> MonthReport {
+        let month = month.with_day(1).unwrap();
+        let days = month
+            .iter_days()
+            .take_while(|d| d.month() == month.month())
+            .map(|date| {
+                let today = jdn(date);
+                let events = sunrise_sunset(lat, long, today);
+                DayRow {
+                    date,
+                    offset: offset_on(zone, date),
+                    rise: events.map(|e| zone.convert(&e.0)),
+                    set: events.map(|e| zone.convert(&e.1)),
+                    length: day_length(lat, long, today),
+                }
+            })
+            .collect();
+        let previous_offset = offset_on(zone, month.pred_opt().unwrap());
+        MonthReport { lat, long, month, days, previous_offset, standard_time: false, zone, precision: Precision::Seconds, sample: Sample::Daily }
+    }
+
+    /// Describes a change of the UTC offset since the previous day.
+    fn transition(before: FixedOffset, after: FixedOffset) -> Option<String> {
+        match after.local_minus_utc().cmp(&before.local_minus_utc()) {
+            Ordering::Equal => None,
+            Ordering::Greater => Some(format!("DST starts ({} -> {})", before, after)),
+            Ordering::Less => Some(format!("DST ends ({} -> {})", before, after)),
+        }
+    }
+
+    fn table(&self) -> Table {
+        let mut columns = vec!["date", "sunrise", "sunset", "day_length", "day_length_seconds", "utc_offset", "note"];
+        if self.standard_time {
+            columns.extend(["sunrise_standard", "sunset_standard"]);
+        }
+        let standard = standard_offset(self.zone, self.month.year());
+        let mut table = Table::new(columns);
+        let mut before = self.previous_offset;
+        for day in &self.days {
+            let mut row: Vec<Value> = vec![
+                day.date.to_string().into(),
+                day.rise.map(|t| self.precision.time(&t)).into(),
+                day.set.map(|t| self.precision.time(&t)).into(),
+                format_hm(day.length).into(),
+                day.length.num_seconds().into(),
+                day.offset.to_string().into(),
+                Self::transition(before, day.offset).into(),
+            ];
+            if self.standard_time {
+                row.push(day.rise.map(|t| self.precision.time(&t.with_timezone(&standard))).into());
+                row.push(day.set.map(|t| self.precision.time(&t.with_timezone(&standard))).into());
+            }
+            if self.sample.includes(day.date, self.month) {
+                table.push(row);
+            }
+            before = day.offset;
+        }
+        table
+    }
+
+    fn summary(&self) -> Vec<Field> {
+        let total: TimeDelta = self.days.iter().map(|d| d.length).sum();
+        let mean = total / self.days.len() as i32;
+        let longest = self.days.iter().rev().max_by_key(|d| d.length).unwrap();
+        let shortest = self.days.iter().min_by_key(|d| d.length).unwrap();
+        let change = self.days[self.days.len() - 1].length - self.days[0].length;
+        let duration = |label, key, d: TimeDelta, text| Field { label, key, value: d.num_seconds().into(), text };
+        let date = |label, key, d: NaiveDate| Field { label, key, value: d.to_string().into(), text: d.to_string() };
+        vec![
+            duration("Total daylight", "total_seconds", total, format_hm(total)),
+            duration("Mean day length", "mean_seconds", mean, format_hm(mean)),
+            date("Longest day", "longest", longest.date),
+            date("Shortest day", "shortest", shortest.date),
+            duration("Change over the month", "change_seconds", change, format_signed(change)),
+        ]
+    }
+
+    /// Writes the daily rows followed by monthly aggregates.
+    pub fn write(&self, w: &mut dyn io::Write, formatter: &dyn OutputFormatter) -> io::Result<()> {
+        let title = format!("Daylight {} for {}", self.month.format("%Y-%m"), format_coords(self.lat, self.long));
+        let meta = [
+            ("month", self.month.format("%Y-%m").to_string().into()),
+            ("latitude", self.lat.into()),
+            ("longitude", self.long.into()),
+        ];
+        formatter.report(w, &title, &meta, &self.table(), &self.summary())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn southern_year_report_names_the_local_seasons() {
+        let report = YearReport::new(-54.8, -68.3, 2026, DisplayZone::Utc).unwrap();
+        assert_eq!(report.hemisphere, Hemisphere::South);
+        let text = report.text().unwrap();
+        assert!(text.contains("for 54.8000°S, 68.3000°W"), "{}", text);
+        for line in ["March equinox (autumn equinox):", "June solstice (winter solstice):", "September equinox (spring equinox):", "December solstice (summer solstice):"] {
+            assert!(text.contains(line), "{} in {}", line, text);
+        }
+        assert_eq!(report.longest().0.month(), 12);
+        assert_eq!(report.shortest().0.month(), 6);
+    }
+
+    #[test]
+    fn equatorial_days_last_about_twelve_hours() {
+        let report = YearReport::new(-0.18, -78.47, 2026, DisplayZone::Utc).unwrap();
+        // Refraction and the sun's radius add a few minutes to both ends
+        for (date, len) in &report.days {
+            assert!((TimeDelta::hours(12)..TimeDelta::minutes(12 * 60 + 10)).contains(len), "{} {}", date, format_hm(*len));
+        }
+        assert!(report.longest().1 - report.shortest().1 < TimeDelta::minutes(2));
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::{self, BufRead, Write};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::cache::ResponseCache;
+use crate::format::{json_object, json_string, DisplayZone};
+use crate::json::Json;
+use crate::moon::moon_horizontal;
+use crate::phase::Phase;
+use crate::solar::{datetime2julian, day_length, degrees::sunrise_sunset_tz, jdn, julian2utc, subsolar_point, sun_position};
+use crate::validate;
+
+const PARSE_ERROR: i32 = -32700;
+const INVALID_REQUEST: i32 = -32600;
+pub(crate) const METHOD_NOT_FOUND: i32 = -32601;
+pub(crate) const INVALID_PARAMS: i32 = -32602;
+
+pub(crate) struct RpcError(pub(crate) i32, pub(crate) String);
+
+/// JSON-RPC 2.0 over line-delimited stdin/stdout. Parameters default to the
+/// server's location and the current time; times are answered in `zone`.
+pub struct RpcServer {
+    pub lat: f64,
+    pub long: f64,
+    pub zone: DisplayZone,
+    /// Where `events` answers are kept, if anywhere.
+    pub cache: Option<ResponseCache>,
+}
+
+impl RpcServer {
+    /// Answers requests from `r` on `w`, one JSON document per line, until `r` ends.
+    pub fn serve<R: BufRead, W: Write>(&self, r: R, w: &mut W) -> io::Result<()> {
+        for line in r.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle(&line) {
+                writeln!(w, "{}", response)?;
+                w.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Response to one request line, or `None` for a notification.
+    pub fn handle(&self, line: &str) -> Option<String> {
+        let request = match Json::parse(line) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(&Json::Null, RpcError(PARSE_ERROR, e))),
+        };
+        let id = request.get("id").cloned();
+        let result = match request.get("method").and_then(Json::as_str) {
+            Some(method) => {
+                info!("RPC {}", method);
+                self.call(method, request.get("params").unwrap_or(&Json::Null))
+            }
+            None => Err(RpcError(INVALID_REQUEST, "missing method".to_string())),
+        };
+        let id = id?;
+        Some(match result {
+            Ok(result) => json_object(&[("jsonrpc", json_string("2.0")), ("id", id.to_string()), ("result", result)]),
+            Err(e) => error_response(&id, e),
+        })
+    }
+
+    pub(crate) fn call(&self, method: &str, params: &Json) -> Result<String, RpcError> {
+        let lat = number_param(params, "lat")?.unwrap_or(self.lat);
+        let long = number_param(params, "long")?.unwrap_or(self.long);
+        let (lat, long) = validate::location(lat, long).map_err(invalid_params)?;
+        let time = match params.get("time").map(|t| t.as_str().ok_or(())) {
+            None => Utc::now(),
+            Some(Ok(t)) => DateTime::parse_from_rfc3339(t).map_err(|e| invalid_params(format!("time: {}", e)))?.to_utc(),
+            Some(Err(())) => return Err(invalid_params("time must be an RFC 3339 string")),
+        };
+        let format_time = |t: DateTime<Utc>| json_string(&self.zone.convert(&t).to_rfc3339_opts(SecondsFormat::Secs, false));
+        let j = datetime2julian(&time);
+        match method {
+            "events" => {
+                let date = match params.get("date").map(|d| d.as_str().ok_or(())) {
+                    None => self.zone.convert(&time).date_naive(),
+                    Some(Ok(d)) => validate::date(d).map_err(invalid_params)?,
+                    Some(Err(())) => return Err(invalid_params("date must be a YYYY-MM-DD string")),
+                };
+                let events = |lat, long| {
+                    let today = jdn(date);
+                    let (rise, set) = match sunrise_sunset_tz(lat, long, today, &Utc) {
+                        Some((rise, set)) => (format_time(rise), format_time(set)),
+                        None => ("null".to_string(), "null".to_string()),
+                    };
+                    json_object(&[
+                        ("date", json_string(&date.to_string())),
+                        ("sunrise", rise),
+                        ("sunset", set),
+                        ("day_length_seconds", day_length(lat, long, today).num_seconds().to_string()),
+                    ])
+                };
+                Ok(match &self.cache {
+                    Some(cache) => cache.get_or_insert("events", lat, long, date, events),
+                    None => events(lat, long),
+                })
+            }
+            "position" => {
+                let (elevation, azimuth) = sun_position(lat, long, j);
+                let (moon_elevation, moon_azimuth) = moon_horizontal(lat, long, j);
+                Ok(json_object(&[
+                    ("time", format_time(time)),
+                    ("elevation", format!("{:.4}", elevation)),
+                    ("azimuth", format!("{:.4}", azimuth)),
+                    ("moon_elevation", format!("{:.4}", moon_elevation)),
+                    ("moon_azimuth", format!("{:.4}", moon_azimuth)),
+                ]))
+            }
+            "phase" => {
+                let mut fields = vec![("time", format_time(time)), ("phase", json_string(&Phase::at(lat, long, j).to_string()))];
+                if let Some((j, next)) = Phase::next_change(lat, long, j) {
+                    fields.push(("next_phase", json_string(&next.to_string())));
+                    fields.push(("next_change", format_time(julian2utc(j))));
+                }
+                Ok(json_object(&fields))
+            }
+            "subsolar" => {
+                let (sub_lat, sub_long) = subsolar_point(j);
+                Ok(json_object(&[("time", format_time(time)), ("latitude", format!("{:.4}", sub_lat)), ("longitude", format!("{:.4}", sub_long))]))
+            }
+            _ => Err(RpcError(METHOD_NOT_FOUND, format!("unknown method `{}`", method))),
+        }
+    }
+}
+
+fn invalid_params(message: impl Into<String>) -> RpcError {
+    RpcError(INVALID_PARAMS, message.into())
+}
+
+fn number_param(params: &Json, key: &str) -> Result<Option<f64>, RpcError> {
+    match params.get(key) {
+        None => Ok(None),
+        Some(v) => v.as_f64().map(Some).ok_or_else(|| invalid_params(format!("{} must be a number", key))),
+    }
+}
+
+fn error_response(id: &Json, RpcError(code, message): RpcError) -> String {
+    let error = json_object(&[("code", code.to_string()), ("message", json_string(&message))]);
+    json_object(&[("jsonrpc", json_string("2.0")), ("id", id.to_string()), ("error", error)])
+}
@@ -0,0 +1,54 @@
+//! Thinning daily exports down to representative days with `--sample`.
+
+use std::str::FromStr;
+
+use chrono::{Datelike, NaiveDate};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sample {
+    #[default]
+    Daily,
+    /// Every seventh day, counted from the first day of the export.
+    Weekly,
+    /// The given day of each month, clamped to the end of shorter months.
+    Monthly(u32),
+}
+
+impl Sample {
+    /// Whether `date` is kept in an export starting at `first`.
+    pub fn includes(&self, date: NaiveDate, first: NaiveDate) -> bool {
+        match *self {
+            Sample::Daily => true,
+            Sample::Weekly => (date - first).num_days() % 7 == 0,
+            Sample::Monthly(day) => {
+                let last = NaiveDate::from_ymd_opt(date.year(), date.month(), 1)
+                    .and_then(|d| d.checked_add_months(chrono::Months::new(1)))
+                    .and_then(|d| d.pred_opt())
+                    .map_or(31, |d| d.day());
+                date.day() == day.min(last)
+            }
+        }
+    }
+}
+
+impl FromStr for Sample {
+    type Err = String;
+
+    /// Accepts `daily`, `weekly`, `monthly` and e.g. `1st of each month` or `15th of every month`.
+    fn from_str(s: &str) -> Result<Sample, String> {
+        let s = s.trim().to_lowercase();
+        let err = || format!("unknown sample `{}` (expected daily, weekly, monthly or e.g. \"1st of each month\")", s);
+        match s.as_str() {
+            "daily" => return Ok(Sample::Daily),
+            "weekly" => return Ok(Sample::Weekly),
+            "monthly" => return Ok(Sample::Monthly(1)),
+            _ => {}
+        }
+        let day = s.strip_suffix(" of each month").or_else(|| s.strip_suffix(" of every month")).ok_or_else(err)?;
+        let day = ["st", "nd", "rd", "th"].iter().find_map(|suffix| day.strip_suffix(suffix)).unwrap_or(day);
+        match day.parse() {
+            Ok(day @ 1..=31) => Ok(Sample::Monthly(day)),
+            _ => Err(err()),
+        }
+    }
+}
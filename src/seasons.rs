@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use crate::solar::apparent_sun_longitude;
+use crate::timescale::TimeScales;
+
+/// The half of the Earth whose seasons are meant. South of the equator the
+/// June solstice begins winter and the noon sun stands in the north.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hemisphere {
+    North,
+    South,
+}
+
+impl Hemisphere {
+    /// The hemisphere of `lat`; the equator counts as northern, its seasons
+    /// being a convention either way.
+    pub fn of(lat: f64) -> Hemisphere {
+        if lat >= 0.0 {
+            Hemisphere::North
+        } else {
+            Hemisphere::South
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hemisphere::North => "north",
+            Hemisphere::South => "south",
+        }
+    }
+}
+
+impl FromStr for Hemisphere {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Hemisphere, String> {
+        match s.to_lowercase().as_str() {
+            "north" | "n" => Ok(Hemisphere::North),
+            "south" | "s" => Ok(Hemisphere::South),
+            _ => Err(format!("unknown hemisphere `{}` (expected north or south)", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeasonMarker {
+    MarchEquinox,
+    JuneSolstice,
+    SeptemberEquinox,
+    DecemberSolstice,
+}
+
+impl SeasonMarker {
+    pub const ALL: [SeasonMarker; 4] = [
+        SeasonMarker::MarchEquinox,
+        SeasonMarker::JuneSolstice,
+        SeasonMarker::SeptemberEquinox,
+        SeasonMarker::DecemberSolstice,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SeasonMarker::MarchEquinox => "March equinox",
+            SeasonMarker::JuneSolstice => "June solstice",
+            SeasonMarker::SeptemberEquinox => "September equinox",
+            SeasonMarker::DecemberSolstice => "December solstice",
+        }
+    }
+
+    /// Name from the observer's point of view, e.g. "winter solstice" for
+    /// the December solstice in the northern hemisphere.
+    pub fn local_name(&self, hemisphere: Hemisphere) -> &'static str {
+        let north = hemisphere == Hemisphere::North;
+        match (self, north) {
+            (SeasonMarker::MarchEquinox, true) | (SeasonMarker::SeptemberEquinox, false) => "spring equinox",
+            (SeasonMarker::JuneSolstice, true) | (SeasonMarker::DecemberSolstice, false) => "summer solstice",
+            (SeasonMarker::SeptemberEquinox, true) | (SeasonMarker::MarchEquinox, false) => "autumn equinox",
+            (SeasonMarker::DecemberSolstice, true) | (SeasonMarker::JuneSolstice, false) => "winter solstice",
+        }
+    }
+
+    /// Astronomical season that begins at the marker for the observer, e.g.
+    /// "summer" at the June solstice in the northern hemisphere.
+    pub fn local_season(&self, hemisphere: Hemisphere) -> &'static str {
+        match self.local_name(hemisphere) {
+            "spring equinox" => "spring",
+            "summer solstice" => "summer",
+            "autumn equinox" => "autumn",
+            _ => "winter",
+        }
+    }
+
+    fn longitude(&self) -> f64 {
+        match self {
+            SeasonMarker::MarchEquinox => 0.0,
+            SeasonMarker::JuneSolstice => 90.0,
+            SeasonMarker::SeptemberEquinox => 180.0,
+            SeasonMarker::DecemberSolstice => 270.0,
+        }
+    }
+
+    /// UTC Julian date of the marker in the Gregorian `year`.
+    pub fn in_year(&self, year: i32) -> f64 {
+        // Days after January 0.5 at which the marker roughly falls
+        let approx = match self {
+            SeasonMarker::MarchEquinox => 79.0,
+            SeasonMarker::JuneSolstice => 172.0,
+            SeasonMarker::SeptemberEquinox => 265.0,
+            SeasonMarker::DecemberSolstice => 355.0,
+        };
+        let mut j = 2451544.5 + 365.2422 * (year - 2000) as f64 + approx;
+        for _ in 0..10 {
+            let diff = (self.longitude() - apparent_sun_longitude(j) + 540.0) % 360.0 - 180.0;
+            j += diff / 360.0 * 365.2422;
+            if diff.abs() < 1e-6 {
+                break;
+            }
+        }
+        // The iteration runs in TT, the scale of the orbital elements
+        TimeScales::default().tt_to_utc(j)
+    }
+
+    /// All markers from the year before `year` to the year after, in order.
+    pub fn around(year: i32) -> Vec<(SeasonMarker, f64)> {
+        (year - 1..=year + 1)
+            .flat_map(|y| SeasonMarker::ALL.iter().map(move |m| (*m, m.in_year(y))))
+            .collect()
+    }
+
+    /// Most recent marker at or before the true Julian date `j`.
+    pub fn previous(j: f64, year: i32, filter: impl Fn(&SeasonMarker) -> bool) -> Option<(SeasonMarker, f64)> {
+        SeasonMarker::around(year).into_iter().filter(|(m, t)| filter(m) && *t <= j).last()
+    }
+
+    /// First marker after the true Julian date `j`.
+    pub fn next(j: f64, year: i32) -> Option<(SeasonMarker, f64)> {
+        SeasonMarker::around(year).into_iter().find(|(_, t)| *t > j)
+    }
+
+    pub fn is_solstice(&self) -> bool {
+        matches!(self, SeasonMarker::JuneSolstice | SeasonMarker::DecemberSolstice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn southern_seasons_are_inverted() {
+        assert_eq!(Hemisphere::of(-54.8), Hemisphere::South);
+        assert_eq!(Hemisphere::of(-0.18), Hemisphere::South);
+        assert_eq!(Hemisphere::of(0.0), Hemisphere::North);
+        let names = |hemisphere| SeasonMarker::ALL.map(|m| (m.local_name(hemisphere), m.local_season(hemisphere)));
+        assert_eq!(
+            names(Hemisphere::North),
+            [("spring equinox", "spring"), ("summer solstice", "summer"), ("autumn equinox", "autumn"), ("winter solstice", "winter")]
+        );
+        assert_eq!(
+            names(Hemisphere::South),
+            [("autumn equinox", "autumn"), ("winter solstice", "winter"), ("spring equinox", "spring"), ("summer solstice", "summer")]
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn noon_sun_stands_north_in_ushuaia() {
+        use crate::angle::Coordinates;
+        use crate::solar::{jdn, DaySolution};
+
+        let ushuaia = Coordinates::new(-54.8, -68.3).unwrap();
+        let berlin = Coordinates::new(52.52, 13.405).unwrap();
+        for date in ["2026-03-20", "2026-06-21", "2026-09-23", "2026-12-21"] {
+            let today = jdn(date.parse().unwrap());
+            let noon = DaySolution::new(&ushuaia, today).culmination();
+            assert!(noon.north && noon.altitude > 0.0, "{} {:?}", date, noon);
+            assert!(!DaySolution::new(&berlin, today).culmination().north, "{}", date);
+        }
+        // 90° less the latitude and the sun's declination at the solstices
+        let june = DaySolution::new(&ushuaia, jdn("2026-06-21".parse().unwrap())).culmination();
+        let december = DaySolution::new(&ushuaia, jdn("2026-12-21".parse().unwrap())).culmination();
+        assert!((june.altitude - 11.8).abs() < 0.3, "{}", june.altitude);
+        assert!((december.altitude - 58.6).abs() < 0.3, "{}", december.altitude);
+    }
+}
@@ -0,0 +1,58 @@
+//! SIGINT and SIGTERM as a flag to poll, so a server can finish the requests
+//! it has accepted before exiting, and SIGHUP as one to reload on.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static TERMINATED: AtomicBool = AtomicBool::new(false);
+static HUNG_UP: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" {
+    fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+}
+
+/// Whether SIGINT or SIGTERM arrived since [`catch_termination`].
+pub fn terminated() -> bool {
+    TERMINATED.load(Ordering::SeqCst)
+}
+
+/// Makes SIGINT and SIGTERM set [`terminated`] instead of ending the process.
+#[cfg(unix)]
+pub fn catch_termination() {
+    const SIGINT: i32 = 2;
+    const SIGTERM: i32 = 15;
+    extern "C" fn handler(_: i32) {
+        TERMINATED.store(true, Ordering::SeqCst);
+    }
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+    unsafe {
+        signal(SIGINT, handler);
+        signal(SIGTERM, handler);
+    }
+}
+
+/// Windows ends console programs on Ctrl-C without a handler to install here.
+#[cfg(not(unix))]
+pub fn catch_termination() {}
+
+/// Whether SIGHUP arrived since the last call, after [`catch_hangup`].
+pub fn hung_up() -> bool {
+    HUNG_UP.swap(false, Ordering::SeqCst)
+}
+
+/// Makes SIGHUP set [`hung_up`] instead of ending the process.
+#[cfg(unix)]
+pub fn catch_hangup() {
+    const SIGHUP: i32 = 1;
+    extern "C" fn handler(_: i32) {
+        HUNG_UP.store(true, Ordering::SeqCst);
+    }
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe
+    unsafe {
+        signal(SIGHUP, handler);
+    }
+}
+
+/// Windows has no SIGHUP; the daemon still sees the configuration change.
+#[cfg(not(unix))]
+pub fn catch_hangup() {}
@@ -0,0 +1,1138 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+
+use crate::angle::{Coordinates, Degrees, Latitude};
+#[cfg(feature = "chrono")]
+use crate::constants::{MODEL_UNCERTAINTY, REFRACTION_UNCERTAINTY};
+use crate::constants::{Model, DIP_FACTOR, EARTH_RADIUS, J2000, JULIAN_CENTURY, MEAN_ANOMALY, SCALE_HEIGHT, SEMI_DIAMETER, SIDEREAL_TIME, STANDARD_ALTITUDE};
+#[cfg(feature = "chrono")]
+use crate::format::FormatOptions;
+use crate::refraction::RefractionModel;
+use crate::timescale::TimeScales;
+
+/// Local time of the Julian date `j` from the sunrise equation, whose dates
+/// run one day ahead of [`datetime2julian`]'s. The instant is found in UTC
+/// and the time zone library only supplies the offset, so days with DST
+/// changes convert like any other. Rounds to whole milliseconds.
+#[cfg(feature = "chrono")]
+pub fn julian2datetime(j: f64) -> DateTime<Local> {
+    julian2datetime_tz(j, &Local)
+}
+
+/// Like [`julian2datetime`] in the time zone `tz`. Dates outside the range
+/// of [`DateTime`] give the instants a day inside its first and last, which
+/// every time zone can show, and NaN the Unix epoch.
+#[cfg(feature = "chrono")]
+pub fn julian2datetime_tz<Tz: TimeZone>(j: f64, tz: &Tz) -> DateTime<Tz> {
+    from_millis((j - 2440588.5) * 86400000.0).with_timezone(tz)
+}
+
+/// Like [`julian2utc`], or `None` when `j` is NaN or outside the range of
+/// [`DateTime`].
+#[cfg(feature = "chrono")]
+pub fn julian2utc_checked(j: f64) -> Option<DateTime<Utc>> {
+    let millis = ((j - 2440587.5) * 86400000.0).round();
+    // The cast saturates, which would let far dates through as the limits
+    if !(i64::MIN as f64..i64::MAX as f64).contains(&millis) {
+        return None;
+    }
+    DateTime::from_timestamp_millis(millis as i64)
+}
+
+#[cfg(feature = "chrono")]
+fn from_millis(millis: f64) -> DateTime<Utc> {
+    // UTC offsets stay below a day, so these convert to any zone
+    let first = DateTime::<Utc>::MIN_UTC + TimeDelta::days(1);
+    let last = DateTime::<Utc>::MAX_UTC - TimeDelta::days(1);
+    let millis = millis.round();
+    if millis.is_nan() {
+        return DateTime::from_timestamp_millis(0).unwrap();
+    }
+    if millis <= first.timestamp_millis() as f64 {
+        return first;
+    }
+    if millis >= last.timestamp_millis() as f64 {
+        return last;
+    }
+    DateTime::from_timestamp_millis(millis as i64).unwrap()
+}
+
+/// Julian day number of a Gregorian date, the `today` argument of the event functions.
+#[cfg(feature = "chrono")]
+pub fn jdn(date: NaiveDate) -> f64 {
+    // Day 1 of the Common Era, 0001-01-01, is JDN 1721426
+    (date.num_days_from_ce() as i64 + 1721425) as f64
+}
+
+/// Julian date of `t` to the millisecond. [`julian2utc`] is its inverse, and
+/// [`julian2datetime_tz`] of it plus one day, exactly for the years 40000 BC
+/// to AD 40000 and to within a millisecond in the rest of [`DateTime`]'s range.
+#[cfg(feature = "chrono")]
+pub fn datetime2julian<Tz: TimeZone>(t: &DateTime<Tz>) -> f64 {
+    t.timestamp_millis() as f64 / 86400000.0 + 2440587.5
+}
+
+/// The instant of the Julian date `j`, rounded to whole milliseconds, with
+/// out-of-range dates and NaN handled as by [`julian2datetime_tz`].
+#[cfg(feature = "chrono")]
+pub fn julian2utc(j: f64) -> DateTime<Utc> {
+    from_millis((j - 2440587.5) * 86400000.0)
+}
+
+/// Days after J2000 of the mean solar noon at `long` on day `n`.
+pub const fn mean_solar_time(n: f64, long: f64) -> f64 {
+    n - long / 360.0
+}
+
+/// Mean anomaly of the sun in degrees, `j_star` days after J2000.
+pub const fn solar_mean_anomaly(j_star: f64) -> f64 {
+    Model::DEFAULT.mean_anomaly(j_star)
+}
+
+/// Days since J2000 of the UTC day `j_date` falls on, counted from 1 at the
+/// noon that starts J2000's day. For a Julian day number this is the ceiling
+/// the sunrise equation takes; a Julian date in the morning UTC, where that
+/// ceiling falls back a day, stays on its civil day.
+fn normalized_date(j_date: f64) -> f64 {
+    (j_date + 0.5).floor() - J2000 + 1.0
+}
+
+/// Equation of the center for the mean anomaly `m` at `d` days after J2000;
+/// the coefficients shrink with the eccentricity of the Earth's orbit. Not
+/// `const` like the functions around it, as `sin` is not.
+pub fn equation_of_the_center(m: f64, d: f64) -> f64 {
+    Model::DEFAULT.equation_of_the_center(m, d)
+}
+
+/// Ecliptic longitude of the sun in degrees from the mean anomaly `m` and
+/// the equation of the center `c`.
+pub const fn ecliptic_longitude(m: f64, c: f64) -> f64 {
+    Model::DEFAULT.ecliptic_longitude(m, c)
+}
+
+/// Mean obliquity of the ecliptic in degrees at the TT Julian date `j`
+/// (Laskar's cubic as given by Meeus), which falls by 47" per century.
+pub const fn obliquity(j: f64) -> f64 {
+    Model::DEFAULT.obliquity(j)
+}
+
+fn declination_of_the_sun(lambda: f64, epsilon: f64) -> f64 {
+    (lambda.to_radians().sin() * epsilon.to_radians().sin()).asin().to_degrees()
+}
+
+fn right_ascension(lambda: f64, epsilon: f64) -> f64 {
+    let rlam = lambda.to_radians();
+    (rlam.sin() * epsilon.to_radians().cos()).atan2(rlam.cos()).to_degrees()
+}
+
+/// Local sidereal time in degrees at `long`, `d` UT1 days after J2000.
+pub const fn sidereal_time(d: f64, long: f64) -> f64 {
+    (SIDEREAL_TIME[0] + SIDEREAL_TIME[1] * d + long) % 360.0
+}
+
+/// Hour angle in degrees, either side of the transit, at which the sun at
+/// declination `delta` crosses `altitude` in degrees at latitude `lat`; NaN
+/// when it stays above or below all day. Sunrise, twilight and the golden
+/// hour are this at -0.833°, -6° to -18° and -4° to 6°; any other threshold works
+/// the same way through [`DaySolution::crossings`].
+pub fn hour_angle_at_altitude(lat: Latitude, delta: f64, altitude: f64) -> f64 {
+    degrees::hour_angle_at_altitude(lat.degrees().0, delta, altitude)
+}
+
+pub fn get_sunrise_sunset(at: &Coordinates, today: f64) -> (f64, f64) {
+    DaySolution::new(at, today).sunrise_sunset()
+}
+
+/// Julian dates at which the sun's centre passes `altitude` degrees on the
+/// day `today`, rising and setting; NaN if it never does.
+pub fn get_crossings(at: &Coordinates, today: f64, altitude: f64) -> (f64, f64) {
+    DaySolution::new(at, today).crossings(altitude)
+}
+
+/// The sunrise equation on latitudes and longitudes in bare degrees, for
+/// callers inside the crate that have checked them already.
+pub(crate) mod degrees {
+    #[cfg(feature = "chrono")]
+    use chrono::{DateTime, Local, TimeZone};
+
+    #[cfg(feature = "chrono")]
+    use super::DaySolution;
+    #[cfg(feature = "chrono")]
+    use crate::constants::STANDARD_ALTITUDE;
+
+    pub(crate) fn hour_angle_at_altitude(lat: f64, delta: f64, altitude: f64) -> f64 {
+        let rlat = lat.to_radians();
+        let rdel = delta.to_radians();
+        ((altitude.to_radians().sin() - rlat.sin() * rdel.sin()) / (rlat.cos() * rdel.cos())).acos().to_degrees()
+    }
+
+    #[cfg(feature = "chrono")]
+    pub(crate) fn get_crossings(lat: f64, long: f64, today: f64, altitude: f64) -> (f64, f64) {
+        DaySolution::from_degrees(lat, long, today).crossings(altitude)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub(crate) fn sunrise_sunset(lat: f64, long: f64, today: f64) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        sunrise_sunset_tz(lat, long, today, &Local)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub(crate) fn sunrise_sunset_tz<Tz: TimeZone>(lat: f64, long: f64, today: f64, tz: &Tz) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+        crossings_tz(lat, long, today, STANDARD_ALTITUDE, tz)
+    }
+
+    #[cfg(feature = "chrono")]
+    pub(crate) fn crossings_tz<Tz: TimeZone>(lat: f64, long: f64, today: f64, altitude: f64, tz: &Tz) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+        DaySolution::from_degrees(lat, long, today).crossings_tz(altitude, tz)
+    }
+}
+
+/// Which day a sunrise or sunset counts for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DayBoundary {
+    /// The solar day around the transit nearest the Julian day number, as
+    /// the sunrise equation has it.
+    #[default]
+    Solar,
+    /// The civil day on which it falls in the display time zone, which
+    /// differs far from the zone's meridian or with long days near the poles.
+    Civil,
+}
+
+impl FromStr for DayBoundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DayBoundary, String> {
+        match s.to_lowercase().as_str() {
+            "solar" => Ok(DayBoundary::Solar),
+            "civil" => Ok(DayBoundary::Civil),
+            _ => Err(format!("unknown day boundary `{}` (expected solar or civil)", s)),
+        }
+    }
+}
+
+/// Crossings of `altitude` whose civil date is `date`, taken from the solar
+/// days before, of and after it; `local_date` gives the civil date of a
+/// Julian date. NaN where the civil day has none, as when the zone's
+/// midnight falls between a sunset and the solar day it belongs to. The
+/// sunset comes before the sunrise where the day's own sunset falls after
+/// midnight, as in UTC well west of Greenwich.
+#[cfg(feature = "chrono")]
+pub fn civil_crossings(at: &Coordinates, date: NaiveDate, altitude: f64, local_date: impl Fn(f64) -> NaiveDate) -> (f64, f64) {
+    let days: Vec<(f64, f64)> = [0.0, -1.0, 1.0].iter().map(|d| get_crossings(at, jdn(date) + d, altitude)).collect();
+    let on_date = |j: &f64| !j.is_nan() && local_date(*j) == date;
+    let rise = days.iter().map(|c| c.0).find(on_date).unwrap_or(f64::NAN);
+    let set = days.iter().map(|c| c.1).find(on_date).unwrap_or(f64::NAN);
+    (rise, set)
+}
+
+/// Altitude of the sun's centre at sunrise for an observer `elevation` metres
+/// above the terrain, who sees past the geometric horizon. The dip of the
+/// horizon is exact for heights of kilometres, as from aircraft or balloons,
+/// and the refraction weakens with the thinner air above the observer; near
+/// the ground this is the usual -0.833° - 2.076'·√h.
+pub fn sunrise_altitude(elevation: f64) -> f64 {
+    sunrise_altitude_with(elevation, &RefractionModel::default())
+}
+
+/// Like [`sunrise_altitude`] with the refraction at the horizon of `model`.
+pub fn sunrise_altitude_with(elevation: f64, model: &RefractionModel) -> f64 {
+    let h = elevation.max(0.0);
+    let refraction = model.at_horizon() * (-h / SCALE_HEIGHT).exp();
+    let dip = DIP_FACTOR * (EARTH_RADIUS / (EARTH_RADIUS + h)).acos().to_degrees();
+    -SEMI_DIAMETER - refraction - dip
+}
+
+/// Intermediate values of the sunrise equation for one day at one location.
+/// Everything but the hour angle is independent of the altitude, so the
+/// times of sunrise, sunset and all twilights follow from one solution.
+#[derive(Clone, Copy, Debug)]
+pub struct DaySolution {
+    pub lat: f64,
+    pub long: f64,
+    pub today: f64,
+    pub n: f64,
+    pub j_star: f64,
+    pub m: f64,
+    pub c: f64,
+    pub lambda: f64,
+    pub delta: f64,
+    pub j_transit: f64,
+    pub model: Model,
+}
+
+impl DaySolution {
+    pub fn new(at: &Coordinates, today: f64) -> DaySolution {
+        DaySolution::with_model(at, today, &Model::DEFAULT)
+    }
+
+    /// Like [`DaySolution::new`] with the parameters of `model`.
+    pub fn with_model(at: &Coordinates, today: f64, model: &Model) -> DaySolution {
+        DaySolution::from_degrees_with_model(at.lat.degrees().0, at.long.degrees().0, today, model)
+    }
+
+    pub(crate) fn from_degrees(lat: f64, long: f64, today: f64) -> DaySolution {
+        DaySolution::from_degrees_with_model(lat, long, today, &Model::DEFAULT)
+    }
+
+    pub(crate) fn from_degrees_with_model(lat: f64, long: f64, today: f64, model: &Model) -> DaySolution {
+        let n = normalized_date(today);
+        info!("Normalized date: {}", n);
+        let j_star = mean_solar_time(n, long);
+        info!("Mean solar time: {}", j_star);
+        let m = model.mean_anomaly(j_star);
+        info!("Solar mean anomaly {}", m);
+        let c = model.equation_of_the_center(m, j_star);
+        info!("Equation of the center: {}", c);
+        let lambda = model.ecliptic_longitude(m, c);
+        info!("Ecliptic longitude: {}", lambda);
+        let delta = declination_of_the_sun(lambda, model.obliquity(J2000 + j_star));
+        info!("Declination of the sun: {}", delta);
+        let j_transit = model.transit(j_star, m, lambda);
+        info!("Jtransit: {}", j_transit);
+        DaySolution { lat, long, today, n, j_star, m, c, lambda, delta, j_transit, model: *model }
+    }
+
+    /// Rising and setting crossings of `altitude`, NaN if there are none.
+    pub fn crossings(&self, altitude: f64) -> (f64, f64) {
+        let omega_0 = degrees::hour_angle_at_altitude(self.lat, self.delta, altitude);
+        info!("Hour angle: {}", omega_0);
+        (self.j_transit - omega_0 / 360.0, self.j_transit + omega_0 / 360.0)
+    }
+
+    pub fn sunrise_sunset(&self) -> (f64, f64) {
+        self.crossings(STANDARD_ALTITUDE)
+    }
+
+    /// Estimated uncertainty of the crossings of `altitude`: the error of the
+    /// equation, and of the refraction near the horizon, over the rate at
+    /// which the sun climbs there. It grows towards the poles, where the sun
+    /// crosses at a shallow angle. `None` when the sun does not cross.
+    #[cfg(feature = "chrono")]
+    pub fn uncertainty(&self, altitude: f64) -> Option<TimeDelta> {
+        let omega_0 = degrees::hour_angle_at_altitude(self.lat, self.delta, altitude);
+        if omega_0.is_nan() {
+            return None;
+        }
+        let error = MODEL_UNCERTAINTY + if altitude > -2.0 { REFRACTION_UNCERTAINTY } else { 0.0 };
+        // Degrees per minute, from the earth turning a quarter degree a minute
+        let rate = 0.25 * self.lat.to_radians().cos() * self.delta.to_radians().cos() * omega_0.to_radians().sin() / altitude.to_radians().cos();
+        // A crossing is never off by more than half a day
+        let minutes = (error / rate.abs()).min(720.0);
+        Some(TimeDelta::milliseconds((minutes * 60_000.0).round() as i64))
+    }
+
+    /// Local apparent solar time of the Julian date `j`, on the scale of
+    /// `j_transit`: the transit is 12:00 by definition, whatever the time
+    /// zone, and times more than half a day away wrap around midnight.
+    #[cfg(feature = "chrono")]
+    pub fn apparent_solar_time(&self, j: f64) -> NaiveTime {
+        let seconds = (43200.0 + (j - self.j_transit) * 86400.0).round().rem_euclid(86400.0);
+        NaiveTime::from_num_seconds_from_midnight_opt(seconds as u32, 0).unwrap()
+    }
+
+    /// Local times of the crossings of `altitude`, or `None` if there are none.
+    #[cfg(feature = "chrono")]
+    pub fn local_crossings(&self, altitude: f64) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        self.crossings_tz(altitude, &Local)
+    }
+
+    /// Like [`DaySolution::local_crossings`] in the time zone `tz`.
+    #[cfg(feature = "chrono")]
+    pub fn crossings_tz<Tz: TimeZone>(&self, altitude: f64, tz: &Tz) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+        let (rise, set) = self.crossings(altitude);
+        if rise.is_nan() || set.is_nan() {
+            return None;
+        }
+        Some((julian2datetime_tz(rise, tz), julian2datetime_tz(set, tz)))
+    }
+}
+
+/// Every intermediate value of the sunrise equation for one day and one
+/// altitude, for cross-checking against references.
+#[derive(Clone, Copy, Debug)]
+pub struct SolarDebug {
+    pub solution: DaySolution,
+    /// Obliquity of the ecliptic in degrees.
+    pub epsilon: f64,
+    /// Altitude of the crossings in degrees.
+    pub altitude: f64,
+    /// Hour angle of the crossings in degrees, NaN if there are none.
+    pub omega_0: f64,
+    pub j_rise: f64,
+    pub j_set: f64,
+}
+
+impl SolarDebug {
+    pub fn new(solution: DaySolution, altitude: f64) -> SolarDebug {
+        let epsilon = solution.model.obliquity(J2000 + solution.j_star);
+        let omega_0 = degrees::hour_angle_at_altitude(solution.lat, solution.delta, altitude);
+        let (j_rise, j_set) = solution.crossings(altitude);
+        SolarDebug { solution, epsilon, altitude, omega_0, j_rise, j_set }
+    }
+
+    /// Name, symbol, value and unit of each quantity in the order of computation.
+    pub fn quantities(&self) -> [(&'static str, &'static str, f64, &'static str); 12] {
+        let s = &self.solution;
+        [
+            ("normalized date", "n", s.n, "days since J2000"),
+            ("mean solar time", "J*", s.j_star, "days since J2000"),
+            ("solar mean anomaly", "M", s.m, "degrees"),
+            ("equation of the center", "C", s.c, "degrees"),
+            ("ecliptic longitude", "λ", s.lambda, "degrees"),
+            ("obliquity of the ecliptic", "ε", self.epsilon, "degrees"),
+            ("declination of the sun", "δ", s.delta, "degrees"),
+            ("altitude of the crossings", "h₀", self.altitude, "degrees"),
+            ("hour angle", "ω₀", self.omega_0, "degrees"),
+            ("solar transit", "J_transit", s.j_transit, "Julian date"),
+            ("sunrise", "J_rise", self.j_rise, "Julian date"),
+            ("sunset", "J_set", self.j_set, "Julian date"),
+        ]
+    }
+}
+
+/// The sun at its highest on a day.
+#[cfg(feature = "chrono")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Culmination {
+    pub time: DateTime<Utc>,
+    /// Degrees above the horizon, negative on polar nights.
+    pub altitude: f64,
+    /// Whether the sun stands north of the observer rather than south.
+    pub north: bool,
+}
+
+#[cfg(feature = "chrono")]
+impl DaySolution {
+    /// When the sun transits the meridian and how high it stands then.
+    pub fn culmination(&self) -> Culmination {
+        let time = julian2datetime_tz(self.j_transit, &Utc);
+        let (altitude, azimuth) = sun_position(self.lat, self.long, datetime2julian(&time));
+        Culmination { time, altitude, north: !(90.0..270.0).contains(&azimuth.rem_euclid(360.0)) }
+    }
+
+    /// Stand-in for sunrise and sunset on days the sun does not cross
+    /// `altitude`, for displays that need a time: the lowest sun after noon
+    /// on a polar day, when it comes closest to setting, and noon itself on
+    /// a polar night. `None` on days with both crossings.
+    pub fn polar_fallback(&self, altitude: f64) -> Option<PolarFallback> {
+        let (rise, set) = self.crossings(altitude);
+        if !rise.is_nan() && !set.is_nan() {
+            return None;
+        }
+        let noon = self.culmination();
+        Some(match noon.altitude > altitude {
+            true => PolarFallback { polar_day: true, time: julian2datetime_tz(self.j_transit + 0.5, &Utc) },
+            false => PolarFallback { polar_day: false, time: noon.time },
+        })
+    }
+}
+
+/// A time standing in for sunrise and sunset, see [`DaySolution::polar_fallback`].
+#[cfg(feature = "chrono")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolarFallback {
+    /// Whether the sun stays up rather than down.
+    pub polar_day: bool,
+    pub time: DateTime<Utc>,
+}
+
+#[cfg(feature = "chrono")]
+impl PolarFallback {
+    /// What the time is, `lowest-sun` or `highest-sun`.
+    pub fn name(&self) -> &'static str {
+        if self.polar_day { "lowest-sun" } else { "highest-sun" }
+    }
+}
+
+/// Day solutions memoized by location and day, for callers that ask about
+/// the same days repeatedly.
+#[derive(Default)]
+pub struct SolutionCache {
+    solutions: HashMap<(u64, u64, u64), DaySolution>,
+}
+
+impl SolutionCache {
+    pub fn get(&mut self, at: &Coordinates, today: f64) -> &DaySolution {
+        self.get_degrees(at.lat.degrees().0, at.long.degrees().0, today)
+    }
+
+    pub(crate) fn get_degrees(&mut self, lat: f64, long: f64, today: f64) -> &DaySolution {
+        self.solutions
+            .entry((lat.to_bits(), long.to_bits(), today.to_bits()))
+            .or_insert_with(|| DaySolution::from_degrees(lat, long, today))
+    }
+
+    /// Drops solutions for days before `today`.
+    pub fn forget_before(&mut self, today: f64) {
+        self.solutions.retain(|_, s| s.today >= today);
+    }
+}
+
+/// Ecliptic longitude of the sun in degrees at the TT Julian date `j`,
+/// including the precession of the perihelion that `ecliptic_longitude`
+/// leaves out (about 0.45° per 26 years).
+pub fn sun_longitude(j: f64) -> f64 {
+    let d = j - J2000;
+    let m = solar_mean_anomaly(d);
+    (ecliptic_longitude(m, equation_of_the_center(m, d)) + 0.0000471 * d).rem_euclid(360.0)
+}
+
+/// Nutation in longitude in degrees at the TT Julian date `j`, from the
+/// four largest terms of the IAU 1980 series (accurate to about 0.5").
+pub fn nutation_in_longitude(j: f64) -> f64 {
+    let t = (j - J2000) / JULIAN_CENTURY;
+    let omega = (125.04452 - 1934.136261 * t).to_radians();
+    let l_sun = (280.4665 + 36000.7698 * t).to_radians();
+    let l_moon = (218.3165 + 481267.8813 * t).to_radians();
+    (-17.20 * omega.sin() - 1.32 * (2.0 * l_sun).sin() - 0.23 * (2.0 * l_moon).sin() + 0.21 * (2.0 * omega).sin()) / 3600.0
+}
+
+/// Apparent ecliptic longitude of the sun in degrees at the TT Julian date
+/// `j`: [`sun_longitude`] referred to the true equinox of date by nutation,
+/// and displaced by the annual aberration of 20.5" towards the sun's past
+/// position.
+pub fn apparent_sun_longitude(j: f64) -> f64 {
+    (sun_longitude(j) + nutation_in_longitude(j) - 20.4898 / 3600.0 / sun_distance(j)).rem_euclid(360.0)
+}
+
+/// Distance between the centres of the Earth and the Sun in astronomical
+/// units at the TT Julian date `j`.
+pub fn sun_distance(j: f64) -> f64 {
+    let m = solar_mean_anomaly(j - J2000).to_radians();
+    1.00014 - 0.01671 * m.cos() - 0.00014 * (2.0 * m).cos()
+}
+
+/// TT Julian date at or after `j` at which the Sun's mean anomaly next
+/// reaches `anomaly` degrees; 0° is the perihelion, 180° the aphelion.
+pub fn next_anomaly(j: f64, anomaly: f64) -> f64 {
+    j + (anomaly - solar_mean_anomaly(j - J2000)).rem_euclid(360.0) / MEAN_ANOMALY[1]
+}
+
+/// Local sunrise and sunset on the day `today`, or `None` during polar day or night.
+#[cfg(feature = "chrono")]
+pub fn sunrise_sunset(at: &Coordinates, today: f64) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    sunrise_sunset_tz(at, today, &Local)
+}
+
+/// Like [`sunrise_sunset`] in the time zone `tz`.
+#[cfg(feature = "chrono")]
+pub fn sunrise_sunset_tz<Tz: TimeZone>(at: &Coordinates, today: f64, tz: &Tz) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+    crossings_tz(at, today, STANDARD_ALTITUDE, tz)
+}
+
+/// Local times at which the sun rises above and sinks below `altitude` on the
+/// day `today`, or `None` if it stays on one side all day.
+#[cfg(feature = "chrono")]
+pub fn crossings(at: &Coordinates, today: f64, altitude: f64) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    crossings_tz(at, today, altitude, &Local)
+}
+
+/// Like [`crossings`] in the time zone `tz`.
+#[cfg(feature = "chrono")]
+pub fn crossings_tz<Tz: TimeZone>(at: &Coordinates, today: f64, altitude: f64, tz: &Tz) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+    DaySolution::new(at, today).crossings_tz(altitude, tz)
+}
+
+/// Time from sunset on the day `today` to sunrise on the next day.
+#[cfg(feature = "chrono")]
+pub fn night_length(lat: f64, long: f64, today: f64) -> TimeDelta {
+    match (degrees::sunrise_sunset(lat, long, today), degrees::sunrise_sunset(lat, long, today + 1.0)) {
+        (Some((_, set)), Some((rise, _))) => rise - set,
+        _ => TimeDelta::days(1) - day_length(lat, long, today),
+    }
+}
+
+/// Astronomical dusk on the day `today` and dawn on the next day, the
+/// window in which the sky is fully dark. `None` if the sun does not reach
+/// 18° below the horizon, or does not climb back above it.
+#[cfg(feature = "chrono")]
+pub fn darkness(lat: f64, long: f64, today: f64) -> Option<(DateTime<Local>, DateTime<Local>)> {
+    darkness_tz(lat, long, today, &Local)
+}
+
+/// Like [`darkness`] in the time zone `tz`.
+#[cfg(feature = "chrono")]
+pub fn darkness_tz<Tz: TimeZone>(lat: f64, long: f64, today: f64, tz: &Tz) -> Option<(DateTime<Tz>, DateTime<Tz>)> {
+    let (_, dusk) = degrees::crossings_tz(lat, long, today, Twilight::Astronomical.altitude(), tz)?;
+    let (dawn, _) = degrees::crossings_tz(lat, long, today + 1.0, Twilight::Astronomical.altitude(), tz)?;
+    Some((dusk, dawn))
+}
+
+/// Time between sunrise and sunset on the day `today`; 24 hours during polar
+/// day and zero during polar night.
+#[cfg(feature = "chrono")]
+pub fn day_length(lat: f64, long: f64, today: f64) -> TimeDelta {
+    day_length_at(lat, long, today, STANDARD_ALTITUDE)
+}
+
+/// Like [`day_length`] between the crossings of `altitude`.
+#[cfg(feature = "chrono")]
+pub fn day_length_at(lat: f64, long: f64, today: f64, altitude: f64) -> TimeDelta {
+    let Some((rise, set)) = degrees::crossings_tz(lat, long, today, altitude, &Utc) else {
+        let (noon_elevation, _) = sun_position(lat, long, today - long / 360.0);
+        return if noon_elevation > altitude { TimeDelta::days(1) } else { TimeDelta::zero() };
+    };
+    set - rise
+}
+
+/// Where the sun stands when a day as counted by [`day_length_at`] begins
+/// and ends. Almanacs, weather services and apps differ here by minutes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DayLengthDefinition {
+    /// The upper limb at the refracted horizon, as sunrise and sunset are.
+    #[default]
+    UpperLimb,
+    /// The centre at the refracted horizon.
+    Center,
+    /// The upper limb at the horizon without the atmosphere's refraction.
+    UpperLimbGeometric,
+    /// The centre at the horizon without refraction.
+    Geometric,
+    /// Civil dawn to civil dusk.
+    Civil,
+}
+
+impl DayLengthDefinition {
+    /// The altitude in degrees where the day begins and ends, given the
+    /// sunrise `horizon` with the observer's elevation and refraction.
+    pub fn altitude(&self, horizon: f64) -> f64 {
+        match self {
+            DayLengthDefinition::UpperLimb => horizon,
+            DayLengthDefinition::Center => horizon + SEMI_DIAMETER,
+            DayLengthDefinition::UpperLimbGeometric => -SEMI_DIAMETER,
+            DayLengthDefinition::Geometric => 0.0,
+            DayLengthDefinition::Civil => Twilight::Civil.altitude(),
+        }
+    }
+}
+
+impl FromStr for DayLengthDefinition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<DayLengthDefinition, String> {
+        match s.to_lowercase().as_str() {
+            "upper-limb" => Ok(DayLengthDefinition::UpperLimb),
+            "center" | "centre" => Ok(DayLengthDefinition::Center),
+            "upper-limb-geometric" => Ok(DayLengthDefinition::UpperLimbGeometric),
+            "geometric" => Ok(DayLengthDefinition::Geometric),
+            "civil" => Ok(DayLengthDefinition::Civil),
+            _ => Err(format!("unknown day length definition `{}` (expected upper-limb, center, upper-limb-geometric, geometric or civil)", s)),
+        }
+    }
+}
+
+/// Time the sun's centre spends above `altitude` degrees during the solar
+/// day around noon on the day `today`, integrated over the elevation curve in
+/// one-minute steps with crossings interpolated linearly.
+#[cfg(feature = "chrono")]
+pub fn time_above(lat: f64, long: f64, today: f64, altitude: f64) -> TimeDelta {
+    const STEPS: usize = 1440;
+    let start = today - long / 360.0 - 0.5;
+    let elevation = |i: usize| sun_position(lat, long, start + i as f64 / STEPS as f64).0 - altitude;
+    let mut prev = elevation(0);
+    let mut minutes = 0.0;
+    for i in 1..=STEPS {
+        let cur = elevation(i);
+        minutes += match (prev > 0.0, cur > 0.0) {
+            (true, true) => 1.0,
+            (false, false) => 0.0,
+            (true, false) => prev / (prev - cur),
+            (false, true) => cur / (cur - prev),
+        };
+        prev = cur;
+    }
+    TimeDelta::milliseconds((minutes * 60_000.0).round() as i64)
+}
+
+/// Current change of the day length per day on the day `today`, from the
+/// central difference of the neighbouring days. Positive while days lengthen.
+#[cfg(feature = "chrono")]
+pub fn day_length_rate(lat: f64, long: f64, today: f64) -> TimeDelta {
+    (day_length(lat, long, today + 1.0) - day_length(lat, long, today - 1.0)) / 2
+}
+
+/// Azimuth in degrees clockwise from north of the sun at the instant `t`,
+/// e.g. the direction of sunrise when `t` is the sunrise time.
+#[cfg(feature = "chrono")]
+pub fn azimuth_at<Tz: TimeZone>(lat: f64, long: f64, t: &DateTime<Tz>) -> f64 {
+    sun_position(lat, long, datetime2julian(t)).1
+}
+
+/// Latitude and longitude in degrees where the sun is in the zenith at the
+/// UTC Julian date `j`, seen from the centre of the Earth.
+pub fn subsolar_point(j: f64) -> (f64, f64) {
+    let scales = TimeScales::default();
+    let tt = scales.utc_to_tt(j);
+    let (lambda, epsilon) = (apparent_sun_longitude(tt), obliquity(tt));
+    let gmst = sidereal_time(scales.utc_to_ut1(j) - J2000, 0.0);
+    let long = (right_ascension(lambda, epsilon) - gmst).rem_euclid(360.0);
+    (declination_of_the_sun(lambda, epsilon), if long > 180.0 { long - 360.0 } else { long })
+}
+
+/// Equatorial horizontal parallax of the sun in degrees at the TT Julian
+/// date `j`, the angle the Earth's equatorial radius subtends from the sun.
+pub fn solar_parallax(j: f64) -> f64 {
+    8.794 / 3600.0 / sun_distance(j)
+}
+
+/// The origin positions are referred to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Frame {
+    /// The centre of the Earth, as ephemerides and almanacs tabulate positions.
+    Geocentric,
+    /// The observer on the Earth's surface, as positions are seen; lower by
+    /// the parallax, at most 8.8" for the sun.
+    #[default]
+    Topocentric,
+}
+
+/// Topocentric elevation and azimuth (clockwise from north) of the sun in
+/// degrees at the UTC Julian date `j`, treating UT1 as UTC.
+pub fn sun_position(lat: f64, long: f64, j: f64) -> (f64, f64) {
+    sun_position_with(&TimeScales::default(), lat, long, j)
+}
+
+/// Where the sun is in the sky, in degrees, displayed as e.g.
+/// `elevation 33.7° at 178° S`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SunPosition {
+    pub elevation: Degrees,
+    /// Clockwise from north.
+    pub azimuth: Degrees,
+}
+
+impl SunPosition {
+    /// The position seen from `at` at the Julian date `j`.
+    pub fn at(at: &Coordinates, j: f64) -> SunPosition {
+        let (elevation, azimuth) = at.sun_position(j);
+        SunPosition { elevation, azimuth }
+    }
+
+    /// The elevation to a tenth of a degree and the azimuth as `options.bearings`.
+    #[cfg(feature = "chrono")]
+    pub fn format_with(&self, options: &FormatOptions) -> String {
+        format!("elevation {:.1} at {}", self.elevation, options.bearings.format(self.azimuth.0, str::to_string))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl fmt::Display for SunPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format_with(&FormatOptions::default()))
+    }
+}
+
+/// Like [`sun_position`], evaluating the orbit in TT and the Earth's
+/// rotation in UT1 as given by `scales`.
+pub fn sun_position_with(scales: &TimeScales, lat: f64, long: f64, j: f64) -> (f64, f64) {
+    sun_position_in(Frame::Topocentric, scales, lat, long, j)
+}
+
+/// Like [`sun_position_with`] in the frame `frame`.
+pub fn sun_position_in(frame: Frame, scales: &TimeScales, lat: f64, long: f64, j: f64) -> (f64, f64) {
+    let d = scales.utc_to_ut1(j) - J2000;
+    let tt = scales.utc_to_tt(j);
+    let (lambda, epsilon) = (apparent_sun_longitude(tt), obliquity(tt));
+    let delta = declination_of_the_sun(lambda, epsilon).to_radians();
+    let h = (sidereal_time(d, long) - right_ascension(lambda, epsilon)).to_radians();
+    let rlat = lat.to_radians();
+    let elevation = (rlat.sin() * delta.sin() + rlat.cos() * delta.cos() * h.cos()).asin().to_degrees();
+    let azimuth = h.sin().atan2(h.cos() * rlat.sin() - delta.tan() * rlat.cos()).to_degrees() + 180.0;
+    let elevation = match frame {
+        Frame::Geocentric => elevation,
+        Frame::Topocentric => elevation - solar_parallax(tt) * elevation.to_radians().cos(),
+    };
+    (elevation, azimuth.rem_euclid(360.0))
+}
+
+/// Twilight phases, bounded below by the altitude of the sun's centre.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Twilight {
+    Civil,
+    Nautical,
+    Astronomical,
+}
+
+impl Twilight {
+    pub const ALL: [Twilight; 3] = [Twilight::Civil, Twilight::Nautical, Twilight::Astronomical];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Twilight::Civil => "civil",
+            Twilight::Nautical => "nautical",
+            Twilight::Astronomical => "astronomical",
+        }
+    }
+
+    /// Lower altitude bound of the phase, where it gives way to the next darker one.
+    pub fn altitude(&self) -> f64 {
+        match self {
+            Twilight::Civil => -6.0,
+            Twilight::Nautical => -12.0,
+            Twilight::Astronomical => -18.0,
+        }
+    }
+
+    /// Upper altitude bound of the phase.
+    pub fn upper_altitude(&self) -> f64 {
+        match self {
+            Twilight::Civil => STANDARD_ALTITUDE,
+            Twilight::Nautical => -6.0,
+            Twilight::Astronomical => -12.0,
+        }
+    }
+
+    /// How long the phase lasts on the day `today`.
+    #[cfg(feature = "chrono")]
+    pub fn length(&self, lat: f64, long: f64, today: f64) -> TwilightLength {
+        let solution = DaySolution::from_degrees(lat, long, today);
+        let inner = solution.local_crossings(self.upper_altitude());
+        let outer = solution.local_crossings(self.altitude());
+        match (inner, outer) {
+            (Some((rise, set)), Some((dawn, dusk))) => TwilightLength::Timed { morning: rise - dawn, evening: dusk - set },
+            (Some(_), None) => TwilightLength::AllNight,
+            (None, Some(_)) => TwilightLength::Midday,
+            (None, None) => {
+                let (noon_elevation, _) = sun_position(lat, long, today - long / 360.0);
+                if (self.altitude()..=self.upper_altitude()).contains(&noon_elevation) {
+                    TwilightLength::AllDay
+                } else {
+                    TwilightLength::Never
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for Twilight {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Twilight, String> {
+        Twilight::ALL
+            .into_iter()
+            .find(|t| t.name() == s.to_lowercase())
+            .ok_or_else(|| format!("unknown twilight `{}` (expected civil, nautical or astronomical)", s))
+    }
+}
+
+/// Duration of a twilight phase on one day. Towards the poles the phases
+/// first merge across midnight, then shrink to noon, and finally vanish.
+#[cfg(feature = "chrono")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TwilightLength {
+    /// Separate morning and evening twilight.
+    Timed { morning: TimeDelta, evening: TimeDelta },
+    /// The sun does not sink below the phase, so dusk runs into dawn.
+    AllNight,
+    /// The sun does not rise above the phase; it lasts from dawn to dusk around noon.
+    Midday,
+    /// The sun stays within the phase for the whole day.
+    AllDay,
+    /// The sun never enters the phase.
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolarEvent {
+    Sunrise,
+    Sunset,
+}
+
+impl SolarEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolarEvent::Sunrise => "sunrise",
+            SolarEvent::Sunset => "sunset",
+        }
+    }
+
+    /// Time of the event on the day with Julian day number `today`.
+    #[cfg(feature = "chrono")]
+    pub fn time(&self, lat: f64, long: f64, today: f64) -> DateTime<Local> {
+        self.time_in(&DaySolution::from_degrees(lat, long, today))
+    }
+
+    #[cfg(feature = "chrono")]
+    pub fn time_in(&self, solution: &DaySolution) -> DateTime<Local> {
+        self.time_tz(solution, &Local)
+    }
+
+    /// Time of the event in the time zone `tz`.
+    #[cfg(feature = "chrono")]
+    pub fn time_tz<Tz: TimeZone>(&self, solution: &DaySolution, tz: &Tz) -> DateTime<Tz> {
+        let (rise, set) = solution.sunrise_sunset();
+        match self {
+            SolarEvent::Sunrise => julian2datetime_tz(rise, tz),
+            SolarEvent::Sunset => julian2datetime_tz(set, tz),
+        }
+    }
+
+    /// First occurrence of the event after `now` within a year from the day
+    /// `today`, in the time zone of `now`.
+    #[cfg(feature = "chrono")]
+    pub fn next_after<Tz: TimeZone>(&self, lat: f64, long: f64, today: f64, now: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        self.next_after_in(&mut SolutionCache::default(), lat, long, today, now)
+    }
+
+    /// Like [`SolarEvent::next_after`], reusing the day solutions in `cache`.
+    #[cfg(feature = "chrono")]
+    pub fn next_after_in<Tz: TimeZone>(&self, cache: &mut SolutionCache, lat: f64, long: f64, today: f64, now: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = now.timezone();
+        (0..366)
+            .map(|day| self.time_tz(cache.get_degrees(lat, long, today + day as f64), &tz))
+            .find(|t| *t > now)
+    }
+}
+
+/// Whether the sun climbs or sinks through an altitude.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Rising,
+    Setting,
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Direction, String> {
+        match s.to_lowercase().as_str() {
+            "rising" | "up" => Ok(Direction::Rising),
+            "setting" | "down" => Ok(Direction::Setting),
+            _ => Err(format!("unknown direction `{}` (expected rising or setting)", s)),
+        }
+    }
+}
+
+/// The sun passing `altitude` degrees in `direction`, a threshold of one's
+/// own such as -4° for street lights.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ElevationCrossing {
+    pub altitude: f64,
+    pub direction: Direction,
+}
+
+#[cfg(feature = "chrono")]
+impl ElevationCrossing {
+    /// Time of the crossing on the day of `solution`, `None` if the sun
+    /// stays on one side of the altitude all day.
+    pub fn time_tz<Tz: TimeZone>(&self, solution: &DaySolution, tz: &Tz) -> Option<DateTime<Tz>> {
+        let (rise, set) = solution.crossings_tz(self.altitude, tz)?;
+        Some(if self.direction == Direction::Rising { rise } else { set })
+    }
+
+    /// Like [`SolarEvent::next_after_in`] for the crossing; days without it are passed over.
+    pub fn next_after_in<Tz: TimeZone>(&self, cache: &mut SolutionCache, lat: f64, long: f64, today: f64, now: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = now.timezone();
+        (0..366).filter_map(|day| self.time_tz(cache.get_degrees(lat, long, today + day as f64), &tz)).find(|t| *t > now)
+    }
+}
+
+impl fmt::Display for ElevationCrossing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let verb = if self.direction == Direction::Rising { "rising" } else { "setting" };
+        write!(f, "sun {} through {}°", verb, self.altitude)
+    }
+}
+
+impl fmt::Display for SolarEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SolarEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<SolarEvent, String> {
+        match s.to_lowercase().as_str() {
+            "sunrise" => Ok(SolarEvent::Sunrise),
+            "sunset" => Ok(SolarEvent::Sunset),
+            _ => Err(format!("unknown event `{}` (expected sunrise or sunset)", s)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "chrono"))]
+mod tests {
+    use std::sync::Mutex;
+    use std::{env, thread};
+
+    use chrono::{FixedOffset, NaiveDateTime};
+
+    use super::*;
+
+    /// Serializes the tests that set `TZ`, which is process-wide.
+    static TZ_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Checks sunrise and sunset at `lat`, `long` on `date` against local
+    /// times to the minute and their UTC offset in hours, with `TZ` set to
+    /// the POSIX rule `tz`. Runs on a new thread, as chrono reads `TZ` once
+    /// per thread.
+    fn assert_local_sunrise_sunset(tz: &'static str, lat: f64, long: f64, date: &str, rise: &str, set: &str, offset: i32) {
+        let _lock = TZ_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        env::set_var("TZ", tz);
+        let today = jdn(date.parse().unwrap());
+        let (actual_rise, actual_set) = thread::spawn(move || sunrise_sunset(&Coordinates::new(lat, long).unwrap(), today).unwrap()).join().unwrap();
+        for (actual, expected) in [(actual_rise.fixed_offset(), rise), (actual_set.fixed_offset(), set)] {
+            let expected = NaiveDateTime::parse_from_str(&format!("{} {}", date, expected), "%Y-%m-%d %H:%M").unwrap();
+            assert_eq!(actual.offset().local_minus_utc(), offset * 3600, "{} on {}", actual, date);
+            assert!((actual.naive_local() - expected).num_seconds().abs() <= 60, "{} on {}, expected {}", actual, date, expected);
+        }
+    }
+
+    #[test]
+    fn sunrise_sunset_on_the_spring_forward_day() {
+        // Berlin moves to CEST at 02:00, before sunrise
+        assert_local_sunrise_sunset("CET-1CEST,M3.5.0,M10.5.0/3", 52.52, 13.405, "2026-03-28", "05:48", "18:34", 1);
+        assert_local_sunrise_sunset("CET-1CEST,M3.5.0,M10.5.0/3", 52.52, 13.405, "2026-03-29", "06:46", "19:36", 2);
+    }
+
+    #[test]
+    fn sunrise_sunset_on_the_fall_back_day() {
+        // Berlin returns to CET at 03:00, before sunrise
+        assert_local_sunrise_sunset("CET-1CEST,M3.5.0,M10.5.0/3", 52.52, 13.405, "2026-10-24", "07:49", "17:51", 2);
+        assert_local_sunrise_sunset("CET-1CEST,M3.5.0,M10.5.0/3", 52.52, 13.405, "2026-10-25", "06:51", "16:50", 1);
+    }
+
+    #[test]
+    fn sunrise_sunset_across_southern_daylight_saving() {
+        // Sydney keeps AEDT from the first Sunday in October to the first in April
+        assert_local_sunrise_sunset("AEST-10AEDT,M10.1.0,M4.1.0/3", -33.87, 151.21, "2026-04-05", "06:10", "17:45", 10);
+        assert_local_sunrise_sunset("AEST-10AEDT,M10.1.0,M4.1.0/3", -33.87, 151.21, "2026-10-03", "05:29", "17:59", 10);
+        assert_local_sunrise_sunset("AEST-10AEDT,M10.1.0,M4.1.0/3", -33.87, 151.21, "2026-10-04", "06:28", "19:00", 11);
+    }
+
+    #[test]
+    fn extreme_julian_dates_convert_to_any_offset() {
+        let east = FixedOffset::east_opt(14 * 3600).unwrap();
+        let west = FixedOffset::west_opt(12 * 3600).unwrap();
+        for j in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e12, -1e12] {
+            let utc = julian2datetime_tz(j, &Utc);
+            assert_eq!(julian2datetime_tz(j, &east), utc);
+            assert_eq!(julian2datetime_tz(j, &west), utc);
+            assert_eq!(julian2utc(j).with_timezone(&east), julian2utc(j));
+            assert!(julian2datetime_tz(j, &east).naive_local() > julian2datetime_tz(j, &west).naive_local());
+            assert_eq!(julian2datetime_tz(j, &east).date_naive(), (utc + TimeDelta::hours(14)).date_naive());
+        }
+        assert_eq!(julian2datetime_tz(f64::NAN, &east).timestamp(), 0);
+        assert_eq!(julian2datetime_tz(1e12, &east), julian2datetime_tz(f64::INFINITY, &east));
+        assert_eq!(julian2datetime_tz(-1e12, &west), julian2datetime_tz(f64::NEG_INFINITY, &west));
+        assert!(julian2datetime_tz(-1e12, &west) < julian2datetime_tz(1e12, &west));
+        assert_eq!(julian2utc_checked(f64::NAN), None);
+        assert_eq!(julian2utc_checked(1e12), None);
+    }
+
+    #[test]
+    fn julian_dates_round_trip() {
+        let east = FixedOffset::east_opt(14 * 3600).unwrap();
+        for t in ["2024-02-29T23:59:59.999Z", "1970-01-01T00:00:00Z", "0001-01-01T12:00:00Z", "9999-12-31T23:59:59.123Z"] {
+            let t: DateTime<Utc> = t.parse().unwrap();
+            let j = datetime2julian(&t);
+            assert_eq!(julian2utc(j), t);
+            assert_eq!(julian2utc_checked(j), Some(t));
+            assert_eq!(julian2datetime_tz(j + 1.0, &east), t);
+        }
+    }
+
+    /// Days around a leap day and a new year, where a day counted twice or
+    /// skipped would show.
+    const EDGE_DATES: [&str; 6] = ["2024-02-28", "2024-02-29", "2024-03-01", "2025-12-30", "2025-12-31", "2026-01-01"];
+
+    #[test]
+    fn normalized_date_is_the_utc_day_of_a_julian_date() {
+        for date in EDGE_DATES {
+            let today = jdn(date.parse().unwrap());
+            let n = normalized_date(today);
+            // From midnight UTC, where the old ceiling fell back a day, to just before the next
+            for hours in [-12.0, -11.999, -6.0, 0.0, 6.0, 11.999] {
+                assert_eq!(normalized_date(today + hours / 24.0), n, "{} {:+} h", date, hours);
+            }
+            assert_eq!(normalized_date(today + 0.5), n + 1.0, "{}", date);
+            assert_eq!(DaySolution::from_degrees(52.52, 13.405, today - 0.4).j_transit, DaySolution::from_degrees(52.52, 13.405, today).j_transit);
+        }
+        assert_eq!(normalized_date(jdn("2000-01-01".parse().unwrap())), 1.0);
+    }
+
+    /// Sunrise and sunset on each of `EDGE_DATES` at `lat`, `long` under
+    /// `boundary`, as dates and times in the zone `zone`.
+    fn edge_crossings(lat: f64, long: f64, zone: FixedOffset, boundary: DayBoundary) -> Vec<(NaiveDate, DateTime<FixedOffset>, DateTime<FixedOffset>)> {
+        let at = Coordinates::new(lat, long).unwrap();
+        let local = |j: f64| julian2datetime_tz(j, &zone);
+        EDGE_DATES
+            .iter()
+            .map(|date| {
+                let date: NaiveDate = date.parse().unwrap();
+                let (rise, set) = match boundary {
+                    DayBoundary::Solar => get_crossings(&at, jdn(date), STANDARD_ALTITUDE),
+                    DayBoundary::Civil => civil_crossings(&at, date, STANDARD_ALTITUDE, |j| local(j).date_naive()),
+                };
+                assert!(!rise.is_nan() && !set.is_nan(), "{} {:?}", date, boundary);
+                (date, local(rise), local(set))
+            })
+            .collect()
+    }
+
+    /// Checks that consecutive days' sunrises and sunsets are a day apart to
+    /// within `drift` minutes.
+    fn assert_daily(days: &[(NaiveDate, DateTime<FixedOffset>, DateTime<FixedOffset>)], drift: i64) {
+        for pair in days.windows(2) {
+            let ((d0, rise0, set0), (d1, rise1, set1)) = (pair[0], pair[1]);
+            if d1 != d0 + TimeDelta::days(1) {
+                continue;
+            }
+            for step in [rise1 - rise0, set1 - set0] {
+                assert!((step - TimeDelta::days(1)).num_minutes().abs() <= drift, "{} to {}: {}", d0, d1, step);
+            }
+        }
+    }
+
+    #[test]
+    fn both_boundaries_agree_near_the_zone_meridian() {
+        let berlin = FixedOffset::east_opt(3600).unwrap();
+        for boundary in [DayBoundary::Solar, DayBoundary::Civil] {
+            let days = edge_crossings(52.52, 13.405, berlin, boundary);
+            for (date, rise, set) in &days {
+                assert_eq!((rise.date_naive(), set.date_naive()), (*date, *date), "{:?}", boundary);
+                assert!(rise < set, "{} {:?}", date, boundary);
+            }
+            assert_daily(&days, 3);
+        }
+        assert_eq!(edge_crossings(52.52, 13.405, berlin, DayBoundary::Solar), edge_crossings(52.52, 13.405, berlin, DayBoundary::Civil));
+    }
+
+    #[test]
+    fn civil_boundary_follows_a_zone_far_from_its_meridian() {
+        // Kiritimati keeps UTC+14 at 157° W, so its solar days end on the next civil day
+        let kiritimati = FixedOffset::east_opt(14 * 3600).unwrap();
+        let solar = edge_crossings(1.87, -157.4, kiritimati, DayBoundary::Solar);
+        let civil = edge_crossings(1.87, -157.4, kiritimati, DayBoundary::Civil);
+        for ((date, solar_rise, solar_set), (_, rise, set)) in solar.iter().zip(&civil) {
+            assert_eq!((solar_rise.date_naive(), solar_set.date_naive()), (*date + TimeDelta::days(1), *date + TimeDelta::days(1)));
+            assert_eq!((rise.date_naive(), set.date_naive()), (*date, *date));
+            assert!(rise < set, "{}", date);
+        }
+        assert_daily(&solar, 3);
+        assert_daily(&civil, 3);
+    }
+
+    #[test]
+    fn civil_day_in_utc_west_of_greenwich_starts_with_the_last_sunset() {
+        // In Los Angeles the sunset comes after midnight UTC, so the UTC day has
+        // the evening before's sunset ahead of its own sunrise
+        let utc = FixedOffset::east_opt(0).unwrap();
+        let solar = edge_crossings(34.05, -118.24, utc, DayBoundary::Solar);
+        let civil = edge_crossings(34.05, -118.24, utc, DayBoundary::Civil);
+        for (i, (date, rise, set)) in civil.iter().enumerate() {
+            assert_eq!((rise.date_naive(), set.date_naive()), (*date, *date));
+            assert!(set < rise, "{}", date);
+            assert_eq!(*rise, solar[i].1);
+            assert_eq!(set.date_naive(), solar[i].2.date_naive() - TimeDelta::days(1));
+        }
+        assert_daily(&civil, 3);
+    }
+}
@@ -0,0 +1,135 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use chrono::{DateTime, Local};
+
+use crate::daemon::{sleep_until, MAX_LATENESS};
+use crate::platform::SystemClock;
+use crate::solar::{jdn, julian2datetime, SolarEvent, SolutionCache};
+
+/// Future resolving at `deadline`. It does not depend on a particular
+/// executor: a helper thread wakes the task when the time has come.
+pub struct SleepUntil {
+    deadline: DateTime<Local>,
+    waker: Option<Arc<Mutex<Waker>>>,
+}
+
+pub fn sleep_until_async(deadline: DateTime<Local>) -> SleepUntil {
+    SleepUntil { deadline, waker: None }
+}
+
+impl Future for SleepUntil {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Local::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        match &self.waker {
+            Some(waker) => *waker.lock().unwrap() = cx.waker().clone(),
+            None => {
+                let waker = Arc::new(Mutex::new(cx.waker().clone()));
+                let (deadline, thread_waker) = (self.deadline, waker.clone());
+                thread::spawn(move || {
+                    sleep_until(&SystemClock, deadline);
+                    thread_waker.lock().unwrap().wake_by_ref();
+                });
+                self.waker = Some(waker);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// One entry of an [`EventStream`]. Every sunrise and sunset comes exactly
+/// once, as the time it occurs at or as a marker for the day it is missing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamEvent {
+    Occurs(SolarEvent, DateTime<Local>),
+    /// The time passed unseen, while the machine slept or the clock jumped ahead.
+    Skipped(SolarEvent, DateTime<Local>),
+    /// The sun stays up or down all day; reported at that day's solar noon.
+    DoesNotOccur(SolarEvent, DateTime<Local>),
+}
+
+impl StreamEvent {
+    pub fn event(&self) -> SolarEvent {
+        match *self {
+            StreamEvent::Occurs(event, _) | StreamEvent::Skipped(event, _) | StreamEvent::DoesNotOccur(event, _) => event,
+        }
+    }
+
+    pub fn time(&self) -> DateTime<Local> {
+        match *self {
+            StreamEvent::Occurs(_, time) | StreamEvent::Skipped(_, time) | StreamEvent::DoesNotOccur(_, time) => time,
+        }
+    }
+}
+
+/// Upcoming solar events at one location in chronological order, for async
+/// daemons: `while let Some(entry) = stream.next_event().await`.
+pub struct EventStream {
+    lat: f64,
+    long: f64,
+    events: Vec<SolarEvent>,
+    /// Time and index in `events` of the last entry returned; later entries
+    /// sort after it, so clock changes never bring one back.
+    last: (DateTime<Local>, usize),
+    cache: SolutionCache,
+}
+
+impl EventStream {
+    pub fn new(lat: f64, long: f64, events: Vec<SolarEvent>) -> EventStream {
+        let last = (Local::now(), events.len());
+        EventStream { lat, long, events, last, cache: SolutionCache::default() }
+    }
+
+    /// The next entry after the last one returned, without waiting for it.
+    pub fn upcoming(&mut self) -> Option<StreamEvent> {
+        self.peek().map(|(entry, _)| entry)
+    }
+
+    fn peek(&mut self) -> Option<(StreamEvent, usize)> {
+        let today = jdn(self.last.0.date_naive()) - 1.0;
+        self.cache.forget_before(today);
+        (0..366).find_map(|day| {
+            let solution = *self.cache.get_degrees(self.lat, self.long, today + day as f64);
+            let (rise, set) = solution.sunrise_sunset();
+            self.events
+                .iter()
+                .enumerate()
+                .map(|(i, event)| {
+                    let j = if *event == SolarEvent::Sunrise { rise } else { set };
+                    let entry = match j.is_nan() {
+                        true => StreamEvent::DoesNotOccur(*event, julian2datetime(solution.j_transit)),
+                        false => StreamEvent::Occurs(*event, julian2datetime(j)),
+                    };
+                    (entry, i)
+                })
+                .filter(|(entry, i)| (entry.time(), *i) > self.last)
+                .min_by_key(|(entry, i)| (entry.time(), *i))
+        })
+    }
+
+    /// Waits for the next entry and returns it, as skipped if it comes more
+    /// than [`MAX_LATENESS`] late; `None` if no day within a year has one.
+    pub async fn next_event(&mut self) -> Option<StreamEvent> {
+        let (entry, i) = self.peek()?;
+        sleep_until_async(entry.time()).await;
+        self.last = (entry.time(), i);
+        Some(match entry {
+            StreamEvent::Occurs(event, time) if Local::now() - time > MAX_LATENESS => StreamEvent::Skipped(event, time),
+            entry => entry,
+        })
+    }
+}
+
+// The stream and its futures can move between executor threads
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<EventStream>();
+    assert_send::<SleepUntil>();
+};
@@ -0,0 +1,116 @@
+//! Conversions between the time scales the solar model mixes: civil UTC,
+//! UT1 (Earth rotation, drives sidereal time) and TT (uniform dynamical
+//! time, drives the orbital elements). All dates are Julian dates.
+
+/// TAI − UTC in seconds from the first day of the given month.
+const LEAP_SECONDS: [(i32, u32, f64); 28] = [
+    (1972, 1, 10.0), (1972, 7, 11.0), (1973, 1, 12.0), (1974, 1, 13.0), (1975, 1, 14.0),
+    (1976, 1, 15.0), (1977, 1, 16.0), (1978, 1, 17.0), (1979, 1, 18.0), (1980, 1, 19.0),
+    (1981, 7, 20.0), (1982, 7, 21.0), (1983, 7, 22.0), (1985, 7, 23.0), (1988, 1, 24.0),
+    (1990, 1, 25.0), (1991, 1, 26.0), (1992, 7, 27.0), (1993, 7, 28.0), (1994, 7, 29.0),
+    (1996, 1, 30.0), (1997, 7, 31.0), (1999, 1, 32.0), (2006, 1, 33.0), (2009, 1, 34.0),
+    (2012, 7, 35.0), (2015, 7, 36.0), (2017, 1, 37.0),
+];
+
+const TT_MINUS_TAI: f64 = 32.184;
+
+/// Julian date of 0h UTC on the first of the month, by the integer formula
+/// of Fliegel and Van Flandern.
+const fn month_start(year: i32, month: u32) -> f64 {
+    let (y, m) = (year as i64, month as i64);
+    let a = (m - 14) / 12;
+    let jdn = 1461 * (y + 4800 + a) / 4 + 367 * (m - 2 - 12 * a) / 12 - 3 * ((y + 4900 + a) / 100) / 4 + 1 - 32075;
+    jdn as f64 - 0.5
+}
+
+/// ΔT = TT − UT1 in seconds from the polynomials of Espenak & Meeus, with
+/// their long-term parabola outside −500 to 2150; used before the
+/// leap-second era. Far from the present it is uncertain by minutes to hours.
+pub fn delta_t(jd: f64) -> f64 {
+    let y = 2000.0 + (jd - 2451544.5) / 365.2425;
+    let parabola = |y: f64| -20.0 + 32.0 * ((y - 1820.0) / 100.0).powi(2);
+    match y {
+        y if y < -500.0 => parabola(y),
+        y if y < 500.0 => polynomial(y / 100.0, &[10583.6, -1014.41, 33.78311, -5.952053, -0.1798452, 0.022174192, 0.0090316521]),
+        y if y < 1600.0 => polynomial((y - 1000.0) / 100.0, &[1574.2, -556.01, 71.23472, 0.319781, -0.8503463, -0.005050998, 0.0083572073]),
+        y if y < 1700.0 => polynomial(y - 1600.0, &[120.0, -0.9808, -0.01532, 1.0 / 7129.0]),
+        y if y < 1800.0 => polynomial(y - 1700.0, &[8.83, 0.1603, -0.0059285, 0.00013336, -1.0 / 1174000.0]),
+        y if y < 1860.0 => {
+            polynomial(y - 1800.0, &[13.72, -0.332447, 0.0068612, 0.0041116, -0.00037436, 0.0000121272, -0.0000001699, 0.000000000875])
+        }
+        y if y < 1900.0 => polynomial(y - 1860.0, &[7.62, 0.5737, -0.251754, 0.01680668, -0.0004473624, 1.0 / 233174.0]),
+        y if y < 1920.0 => polynomial(y - 1900.0, &[-2.79, 1.494119, -0.0598939, 0.0061966, -0.000197]),
+        y if y < 1941.0 => polynomial(y - 1920.0, &[21.20, 0.84493, -0.076100, 0.0020936]),
+        y if y < 1961.0 => polynomial(y - 1950.0, &[29.07, 0.407, -1.0 / 233.0, 1.0 / 2547.0]),
+        y if y < 1986.0 => polynomial(y - 1975.0, &[45.45, 1.067, -1.0 / 260.0, -1.0 / 718.0]),
+        y if y < 2005.0 => polynomial(y - 2000.0, &[63.86, 0.3345, -0.060374, 0.0017275, 0.000651814, 0.00002373599]),
+        y if y < 2050.0 => polynomial(y - 2000.0, &[62.92, 0.32217, 0.005589]),
+        y if y < 2150.0 => parabola(y) - 0.5628 * (2150.0 - y),
+        // NaN too
+        y => parabola(y),
+    }
+}
+
+/// `coefficients[0] + coefficients[1] * t + ...` by Horner's scheme.
+fn polynomial(t: f64, coefficients: &[f64]) -> f64 {
+    coefficients.iter().rev().fold(0.0, |sum, c| sum * t + c)
+}
+
+/// TT − UTC in seconds at the UTC Julian date `jd`, exact from the leap
+/// second table since 1972 and from ΔT before.
+pub fn tt_minus_utc(jd: f64) -> f64 {
+    if jd < month_start(1972, 1) {
+        return delta_t(jd);
+    }
+    // Only a NaN `jd` is in no interval
+    LEAP_SECONDS.iter().rev().find(|(y, m, _)| jd >= month_start(*y, *m)).map_or_else(|| delta_t(jd), |(_, _, tai_utc)| tai_utc + TT_MINUS_TAI)
+}
+
+/// Source of DUT1 = UT1 − UTC in seconds, which is below 0.9 s by
+/// definition but only known from IERS bulletins.
+pub trait Dut1Source {
+    fn dut1(&self, jd_utc: f64) -> f64;
+}
+
+/// Treats UT1 as UTC, the default when no bulletin value is available.
+pub struct ZeroDut1;
+
+impl Dut1Source for ZeroDut1 {
+    fn dut1(&self, _: f64) -> f64 {
+        0.0
+    }
+}
+
+/// A fixed DUT1, e.g. the current value from IERS Bulletin A.
+pub struct ConstantDut1(pub f64);
+
+impl Dut1Source for ConstantDut1 {
+    fn dut1(&self, _: f64) -> f64 {
+        self.0
+    }
+}
+
+pub struct TimeScales {
+    pub dut1: Box<dyn Dut1Source + Send + Sync>,
+}
+
+impl Default for TimeScales {
+    fn default() -> TimeScales {
+        TimeScales { dut1: Box::new(ZeroDut1) }
+    }
+}
+
+impl TimeScales {
+    pub fn utc_to_tt(&self, jd: f64) -> f64 {
+        jd + tt_minus_utc(jd) / 86400.0
+    }
+
+    pub fn tt_to_utc(&self, jd: f64) -> f64 {
+        let approx = jd - tt_minus_utc(jd) / 86400.0;
+        jd - tt_minus_utc(approx) / 86400.0
+    }
+
+    pub fn utc_to_ut1(&self, jd: f64) -> f64 {
+        jd + self.dut1.dut1(jd) / 86400.0
+    }
+}
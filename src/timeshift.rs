@@ -0,0 +1,106 @@
+//! How many mornings and evenings of a year are light at given clock times
+//! under the current rules, permanent standard time and permanent daylight
+//! saving time, the question behind the debates about ending the clock
+//! changes.
+
+use std::fmt;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, Utc};
+
+use crate::format::{DisplayZone, Table};
+use crate::parallel;
+use crate::report::standard_offset;
+use crate::solar::{degrees::crossings_tz, jdn, sun_position};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockRules {
+    /// The offsets of the zone with its changes.
+    Current,
+    PermanentStandard,
+    PermanentDst,
+}
+
+impl fmt::Display for ClockRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ClockRules::Current => "current",
+            ClockRules::PermanentStandard => "permanent standard",
+            ClockRules::PermanentDst => "permanent DST",
+        })
+    }
+}
+
+/// The days of one year under one set of clock rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Count {
+    pub rules: ClockRules,
+    /// Fixed offset of the permanent rules; `None` for the current ones.
+    pub offset: Option<FixedOffset>,
+    /// Days with the sun up before the morning time.
+    pub light_mornings: u32,
+    /// Days with the sun still up after the evening time.
+    pub light_evenings: u32,
+}
+
+pub struct TimeShiftReport {
+    pub year: i32,
+    pub days: u32,
+    pub morning: NaiveTime,
+    pub evening: NaiveTime,
+    pub counts: Vec<Count>,
+}
+
+impl TimeShiftReport {
+    /// Counts for `year` at `lat`, `long` in `zone`, with the sun crossing
+    /// `altitude` at sunrise and sunset. Permanent DST is an hour on
+    /// standard time where the zone does not change its clocks.
+    pub fn new(lat: f64, long: f64, year: i32, zone: DisplayZone, altitude: f64, morning: NaiveTime, evening: NaiveTime) -> Option<TimeShiftReport> {
+        let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+        let dates: Vec<NaiveDate> = first.iter_days().take_while(|d| d.year() == year).collect();
+        let standard = standard_offset(zone, year);
+        let summer = [first, first.with_month(7)?].map(|d| zone.offset_at_utc(&d.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap())));
+        let summer = summer.into_iter().max_by_key(|o| o.local_minus_utc()).filter(|o| *o != standard);
+        let dst = summer.or_else(|| FixedOffset::east_opt(standard.local_minus_utc() + 3600))?;
+        let rules = [(ClockRules::Current, None), (ClockRules::PermanentStandard, Some(standard)), (ClockRules::PermanentDst, Some(dst))];
+        let days = parallel::map(&dates, |date| {
+            let today = jdn(*date);
+            let crossings = crossings_tz(lat, long, today, altitude, &Utc);
+            rules.map(|(_, fixed)| match crossings {
+                Some((rise, set)) => {
+                    let offset = |t: &DateTime<Utc>| fixed.unwrap_or_else(|| zone.offset_at_utc(&t.naive_utc()));
+                    (rise.with_timezone(&offset(&rise)).time() < morning, set.with_timezone(&offset(&set)).time() > evening)
+                }
+                // The sun stays up or down all day
+                None => {
+                    let up = sun_position(lat, long, today - long / 360.0).0 > altitude;
+                    (up, up)
+                }
+            })
+        });
+        let counts = rules
+            .iter()
+            .enumerate()
+            .map(|(i, (rules, offset))| Count {
+                rules: *rules,
+                offset: *offset,
+                light_mornings: days.iter().filter(|day| day[i].0).count() as u32,
+                light_evenings: days.iter().filter(|day| day[i].1).count() as u32,
+            })
+            .collect();
+        Some(TimeShiftReport { year, days: dates.len() as u32, morning, evening, counts })
+    }
+
+    pub fn table(&self) -> Table {
+        let mut table = Table::new(vec!["rules", "utc_offset", "light_mornings", "light_evenings", "days"]);
+        for count in &self.counts {
+            table.push(vec![
+                count.rules.to_string().into(),
+                count.offset.map(|o| o.to_string()).into(),
+                (count.light_mornings as i64).into(),
+                (count.light_evenings as i64).into(),
+                (self.days as i64).into(),
+            ]);
+        }
+        table
+    }
+}
@@ -0,0 +1,84 @@
+//! Where the wall-clock time of a run goes, for `--timing`. Stages add up
+//! their time from any thread; the math is what is left of the total.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// The command line, environment and configuration file.
+    Parsing,
+    /// Requests of the network features, answered or from the cache.
+    Network,
+    /// Writing the output.
+    Output,
+}
+
+static NANOS: [AtomicU64; 3] = [AtomicU64::new(0), AtomicU64::new(0), AtomicU64::new(0)];
+static REQUESTS: AtomicUsize = AtomicUsize::new(0);
+static CACHED: AtomicUsize = AtomicUsize::new(0);
+static MAPS: AtomicUsize = AtomicUsize::new(0);
+static PARALLEL_MAPS: AtomicUsize = AtomicUsize::new(0);
+static MAX_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn add(stage: Stage, d: Duration) {
+    NANOS[stage as usize].fetch_add(d.as_nanos() as u64, Ordering::Relaxed);
+}
+
+/// Runs `f`, counting its time to `stage`.
+pub fn measure<R>(stage: Stage, f: impl FnOnce() -> R) -> R {
+    let start = Instant::now();
+    let result = f();
+    add(stage, start.elapsed());
+    result
+}
+
+/// Counts a network request, `cached` if the cache answered it.
+pub fn request(cached: bool) {
+    REQUESTS.fetch_add(1, Ordering::Relaxed);
+    if cached {
+        CACHED.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts a map of [`crate::parallel`] over `threads` threads.
+pub fn map(threads: usize) {
+    MAPS.fetch_add(1, Ordering::Relaxed);
+    if threads > 1 {
+        PARALLEL_MAPS.fetch_add(1, Ordering::Relaxed);
+    }
+    MAX_THREADS.fetch_max(threads, Ordering::Relaxed);
+}
+
+fn elapsed(stage: Stage) -> Duration {
+    Duration::from_nanos(NANOS[stage as usize].load(Ordering::Relaxed))
+}
+
+/// The stages of a run that took `total`, such as `parsing 0.4ms, network
+/// 0.0ms (0 requests, 0 cached), math 12.1ms, output 0.3ms, total 12.8ms`,
+/// and how many maps ran in parallel.
+pub fn report(total: Duration) -> String {
+    let ms = |d: Duration| format!("{:.1}ms", d.as_secs_f64() * 1000.0);
+    let (parsing, network, output) = (elapsed(Stage::Parsing), elapsed(Stage::Network), elapsed(Stage::Output));
+    let math = total.saturating_sub(parsing + network + output);
+    let (requests, cached) = (REQUESTS.load(Ordering::Relaxed), CACHED.load(Ordering::Relaxed));
+    let (maps, parallel) = (MAPS.load(Ordering::Relaxed), PARALLEL_MAPS.load(Ordering::Relaxed));
+    format!(
+        "timing: parsing {}, network {} ({} requests, {} cached), math {}, output {}, total {}\ntiming: {} of {} maps parallel, up to {} threads",
+        ms(parsing), ms(network), requests, cached, ms(math), ms(output), ms(total), parallel, maps, MAX_THREADS.load(Ordering::Relaxed)
+    )
+}
+
+/// A writer counting its time to [`Stage::Output`].
+pub struct Timed<W>(pub W);
+
+impl<W: Write> Write for Timed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        measure(Stage::Output, || self.0.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        measure(Stage::Output, || self.0.flush())
+    }
+}
@@ -0,0 +1,176 @@
+//! Offsets of named time zones from the system's zoneinfo files (TZif), for
+//! the places of one run that each keep their own zone. The process-wide
+//! local zone of `TZ` is left alone.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc, Weekday};
+
+/// A zone's UTC offsets in seconds: at each transition, and from the rule
+/// of its footer after the last one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zone {
+    transitions: Vec<(i64, i32)>,
+    /// Offset before the first transition.
+    initial: i32,
+    rule: Option<Rule>,
+}
+
+/// A POSIX TZ rule such as `CET-1CEST,M3.5.0,M10.5.0/3`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rule {
+    standard: i32,
+    /// Daylight saving offset with the local times it starts and ends.
+    dst: Option<(i32, Change, Change)>,
+}
+
+/// The `Mm.w.d/time` form of a rule change: day `d` of week `w` (5 for the
+/// last) of month `m`, at `time` seconds of local time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Change {
+    month: u32,
+    week: u32,
+    weekday: u32,
+    time: i64,
+}
+
+impl Zone {
+    /// The zone `name`, such as `Australia/Sydney`, from `TZDIR` or
+    /// /usr/share/zoneinfo.
+    pub fn load(name: &str) -> Result<Zone, String> {
+        if name.contains("..") {
+            return Err(format!("invalid time zone `{}`", name));
+        }
+        let dir = env::var_os("TZDIR").map_or_else(|| PathBuf::from("/usr/share/zoneinfo"), PathBuf::from);
+        let data = fs::read(dir.join(name)).map_err(|e| format!("time zone `{}`: {}", name, e))?;
+        Zone::parse(&data).ok_or_else(|| format!("time zone `{}`: not a TZif file", name))
+    }
+
+    /// A TZif file, from its 64-bit part where there is one.
+    pub fn parse(data: &[u8]) -> Option<Zone> {
+        let (header, body) = (data.get(..44)?, data.get(44..)?);
+        if &header[..4] != b"TZif" {
+            return None;
+        }
+        let counts = |h: &[u8]| -> Option<[usize; 6]> {
+            let mut c = [0; 6];
+            for (i, n) in c.iter_mut().enumerate() {
+                *n = u32::from_be_bytes(h.get(20 + 4 * i..24 + 4 * i)?.try_into().ok()?) as usize;
+            }
+            Some(c)
+        };
+        let [isutc, isstd, leap, time, types, chars] = counts(header)?;
+        let v1_len = time * 5 + types * 6 + chars + leap * 8 + isstd + isutc;
+        if header[4] == 0 {
+            return Zone::from_block(body, [isutc, isstd, leap, time, types, chars], 4, None);
+        }
+        let header = body.get(v1_len..v1_len + 44)?;
+        let counts = counts(header)?;
+        let body = &body[v1_len + 44..];
+        let [isutc, isstd, leap, time, types, chars] = counts;
+        let end = time * 9 + types * 6 + chars + leap * 12 + isstd + isutc;
+        let footer = std::str::from_utf8(body.get(end..)?).ok()?.trim_matches('\n');
+        Zone::from_block(body, counts, 8, Rule::parse(footer))
+    }
+
+    fn from_block(body: &[u8], [_, _, _, time, types, _]: [usize; 6], size: usize, rule: Option<Rule>) -> Option<Zone> {
+        let at = |i: usize| -> Option<i64> {
+            let bytes = body.get(i * size..(i + 1) * size)?;
+            Some(if size == 8 { i64::from_be_bytes(bytes.try_into().ok()?) } else { i32::from_be_bytes(bytes.try_into().ok()?) as i64 })
+        };
+        let indices = body.get(time * size..time * (size + 1))?;
+        let infos = body.get(time * (size + 1)..time * (size + 1) + types * 6)?;
+        let offset = |i: usize| -> Option<i32> { Some(i32::from_be_bytes(infos.get(i * 6..i * 6 + 4)?.try_into().ok()?)) };
+        let transitions = (0..time).map(|i| Some((at(i)?, offset(indices[i] as usize)?))).collect::<Option<Vec<_>>>()?;
+        Some(Zone { transitions, initial: offset(0)?, rule })
+    }
+
+    /// Offset from UTC at the instant `t`.
+    pub fn offset_at(&self, t: &DateTime<Utc>) -> FixedOffset {
+        let s = t.timestamp();
+        let seconds = match (self.transitions.last(), self.rule) {
+            (Some((last, _)), Some(rule)) if s >= *last => rule.offset_at(s),
+            (None, Some(rule)) => rule.offset_at(s),
+            _ => match self.transitions.iter().rposition(|(at, _)| *at <= s) {
+                Some(i) => self.transitions[i].1,
+                None => self.initial,
+            },
+        };
+        FixedOffset::east_opt(seconds).unwrap_or(FixedOffset::east_opt(0).unwrap())
+    }
+}
+
+impl Rule {
+    fn parse(s: &str) -> Option<Rule> {
+        let mut rest = s;
+        let name = |rest: &mut &str| -> Option<()> {
+            let end = if rest.starts_with('<') { rest.find('>')? + 1 } else { rest.find(|c: char| !c.is_ascii_alphabetic())? };
+            *rest = &rest[end..];
+            Some(())
+        };
+        name(&mut rest)?;
+        let standard = -Rule::time(&mut rest)?;
+        if rest.is_empty() {
+            return Some(Rule { standard, dst: None });
+        }
+        name(&mut rest)?;
+        let dst = if rest.starts_with(',') { standard + 3600 } else { -Rule::time(&mut rest)? };
+        let mut changes = rest.strip_prefix(',')?.split(',').map(Change::parse);
+        let (start, end) = (changes.next()??, changes.next()??);
+        Some(Rule { standard, dst: Some((dst, start, end)) })
+    }
+
+    /// `[+-]hh[:mm[:ss]]` in seconds, consumed from the front of `rest`.
+    fn time(rest: &mut &str) -> Option<i32> {
+        let end = rest.find(|c: char| !(c.is_ascii_digit() || "+-:".contains(c))).unwrap_or(rest.len());
+        let (field, remaining) = rest.split_at(end);
+        *rest = remaining;
+        let (sign, field) = match field.strip_prefix('-') {
+            Some(f) => (-1, f),
+            None => (1, field.trim_start_matches('+')),
+        };
+        let mut parts = field.split(':').map(|p| p.parse::<i32>());
+        let seconds = parts.next()?.ok()? * 3600 + parts.next().unwrap_or(Ok(0)).ok()? * 60 + parts.next().unwrap_or(Ok(0)).ok()?;
+        Some(sign * seconds)
+    }
+
+    fn offset_at(&self, s: i64) -> i32 {
+        let Some((dst, start, end)) = self.dst else {
+            return self.standard;
+        };
+        let year = DateTime::from_timestamp(s + self.standard as i64, 0).map_or(1970, |t| t.year());
+        // Changes happen at local time, standard before the start and daylight saving before the end
+        let (Some(start), Some(end)) = (start.in_year(year), end.in_year(year)) else {
+            return self.standard;
+        };
+        let (start, end) = (start - self.standard as i64, end - dst as i64);
+        let inside = if start < end { start <= s && s < end } else { s >= start || s < end };
+        if inside {
+            dst
+        } else {
+            self.standard
+        }
+    }
+}
+
+impl Change {
+    fn parse(s: &str) -> Option<Change> {
+        let (date, time) = s.split_once('/').unwrap_or((s, "2"));
+        let mut fields = date.strip_prefix('M')?.split('.').map(|f| f.parse::<u32>().ok());
+        let (month, week, weekday) = (fields.next()??, fields.next()??, fields.next()??);
+        let mut rest = time;
+        Some(Change { month, week, weekday, time: Rule::time(&mut rest)? as i64 })
+    }
+
+    /// Local time of the change in `year` as seconds since the epoch.
+    fn in_year(&self, year: i32) -> Option<i64> {
+        let weekday = Weekday::try_from(((self.weekday + 6) % 7) as u8).ok()?;
+        let day = match self.week {
+            5 => (1..=5).rev().find_map(|w| NaiveDate::from_weekday_of_month_opt(year, self.month, weekday, w))?,
+            w => NaiveDate::from_weekday_of_month_opt(year, self.month, weekday, w as u8)?,
+        };
+        Some(day.and_hms_opt(0, 0, 0)?.and_utc().timestamp() + self.time)
+    }
+}
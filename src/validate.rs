@@ -0,0 +1,92 @@
+//! Range checks for user input. Out-of-range coordinates do not fail in the
+//! formulas, they turn into NaN and print as 1970 or as zero day lengths, so
+//! they are rejected up front with a message saying what is expected.
+
+use std::error::Error;
+use std::fmt;
+
+#[cfg(feature = "chrono")]
+use chrono::{Datelike, NaiveDate};
+
+/// A value that parsed but lies outside the range the calculations support.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvalidInput(pub String);
+
+impl fmt::Display for InvalidInput {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for InvalidInput {}
+
+impl From<InvalidInput> for String {
+    fn from(e: InvalidInput) -> String {
+        e.0
+    }
+}
+
+/// Lowest and highest supported observer heights in metres: the shore of the
+/// Dead Sea, and the stratosphere well above any aircraft or balloon.
+pub const ELEVATION_RANGE: (f64, f64) = (-500.0, 100_000.0);
+
+/// Lowest and highest supported years.
+pub const YEAR_RANGE: (i32, i32) = (1, 9999);
+
+pub fn latitude(lat: f64) -> Result<f64, InvalidInput> {
+    if (-90.0..=90.0).contains(&lat) {
+        return Ok(lat);
+    }
+    let hint = if (-180.0..=180.0).contains(&lat) { " (were latitude and longitude swapped?)" } else { "" };
+    Err(InvalidInput(format!("latitude {} is out of range: expected -90 to 90 degrees, negative south of the equator{}", lat, hint)))
+}
+
+pub fn longitude(long: f64) -> Result<f64, InvalidInput> {
+    if (-180.0..=180.0).contains(&long) {
+        return Ok(long);
+    }
+    let hint = if (180.0..=360.0).contains(&long) { format!(" (for degrees east from 0 to 360, use {})", long - 360.0) } else { String::new() };
+    Err(InvalidInput(format!("longitude {} is out of range: expected -180 to 180 degrees, negative west of Greenwich{}", long, hint)))
+}
+
+/// Latitude and longitude in degrees, rejecting NaN and out-of-range values.
+pub fn location(lat: f64, long: f64) -> Result<(f64, f64), InvalidInput> {
+    Ok((latitude(lat)?, longitude(long)?))
+}
+
+/// Observer height in metres.
+pub fn elevation(elevation: f64) -> Result<f64, InvalidInput> {
+    let (low, high) = ELEVATION_RANGE;
+    if (low..=high).contains(&elevation) {
+        return Ok(elevation);
+    }
+    Err(InvalidInput(format!("elevation {} m is out of range: expected {} m to {} km above sea level", elevation, low, high / 1000.0)))
+}
+
+pub fn year(year: i32) -> Result<i32, InvalidInput> {
+    let (low, high) = YEAR_RANGE;
+    if (low..=high).contains(&year) {
+        return Ok(year);
+    }
+    Err(InvalidInput(format!("year {} is out of range: expected {} to {}", year, low, high)))
+}
+
+/// Parses a `YYYY-MM-DD` date in the supported years.
+#[cfg(feature = "chrono")]
+pub fn date(s: &str) -> Result<NaiveDate, InvalidInput> {
+    let date = NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").map_err(|e| {
+        InvalidInput(format!("invalid date `{}`: {} (expected YYYY-MM-DD, e.g. 2024-06-21)", s, e))
+    })?;
+    year(date.year())?;
+    Ok(date)
+}
+
+/// Parses a `YYYY-MM` month as its first day.
+#[cfg(feature = "chrono")]
+pub fn month(s: &str) -> Result<NaiveDate, InvalidInput> {
+    let date = NaiveDate::parse_from_str(&format!("{}-01", s.trim()), "%Y-%m-%d").map_err(|e| {
+        InvalidInput(format!("invalid month `{}`: {} (expected YYYY-MM, e.g. 2024-06)", s, e))
+    })?;
+    year(date.year())?;
+    Ok(date)
+}
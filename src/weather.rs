@@ -0,0 +1,85 @@
+//! Cloud cover forecasts from Open-Meteo, fetched with `curl`, to tell
+//! whether a sunrise or sunset will be seen. Forecasts reach about 16 days
+//! ahead; later days have no cover. Forecasts are cached for an hour.
+
+use std::error::Error;
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::fetch::HttpCache;
+use crate::json::Json;
+
+pub const OPEN_METEO: &str = "https://api.open-meteo.com/v1/forecast";
+
+/// Cloud cover in percent of the sky, hour by hour.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CloudCover {
+    pub hours: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl CloudCover {
+    /// The forecast for `lat`, `long` from the start of `first` to the end
+    /// of `last`, UTC days.
+    pub fn fetch(cache: &HttpCache, lat: f64, long: f64, first: NaiveDate, last: NaiveDate) -> Result<CloudCover, Box<dyn Error>> {
+        let url = format!(
+            "{}?latitude={:.4}&longitude={:.4}&hourly=cloud_cover&timezone=UTC&start_date={}&end_date={}",
+            OPEN_METEO, lat, long, first, last
+        );
+        let body = cache.get(&url, Duration::from_secs(3600)).map_err(|e| format!("Open-Meteo: {}", e))?;
+        Ok(CloudCover::parse(&body)?)
+    }
+
+    /// The `hourly` times and `cloud_cover` of an Open-Meteo response.
+    pub fn parse(s: &str) -> Result<CloudCover, String> {
+        let json = Json::parse(s)?;
+        let hourly = json.get("hourly").ok_or("Open-Meteo: no hourly forecast")?;
+        let (Some(Json::Array(times)), Some(Json::Array(covers))) = (hourly.get("time"), hourly.get("cloud_cover")) else {
+            return Err("Open-Meteo: no hourly cloud_cover".to_string());
+        };
+        let hours = times
+            .iter()
+            .zip(covers)
+            .filter_map(|(time, cover)| {
+                let time = NaiveDateTime::parse_from_str(time.as_str()?, "%Y-%m-%dT%H:%M").ok()?;
+                Some((time.and_utc(), cover.as_f64()?))
+            })
+            .collect();
+        Ok(CloudCover { hours })
+    }
+
+    /// Cover at `t`, interpolated between the hours around it.
+    pub fn at(&self, t: &DateTime<Utc>) -> Option<f64> {
+        let i = self.hours.iter().position(|(hour, _)| hour > t)?;
+        let (after, cover_after) = self.hours[i];
+        let (before, cover_before) = *self.hours.get(i.checked_sub(1)?)?;
+        let fraction = (*t - before).num_seconds() as f64 / (after - before).num_seconds() as f64;
+        Some(cover_before + (cover_after - cover_before) * fraction)
+    }
+}
+
+/// What the cover means for seeing the sun low on the horizon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outlook {
+    Visible,
+    Uncertain,
+    Obscured,
+}
+
+impl Outlook {
+    pub fn from_cover(percent: f64) -> Outlook {
+        match percent {
+            p if p < 40.0 => Outlook::Visible,
+            p if p < 75.0 => Outlook::Uncertain,
+            _ => Outlook::Obscured,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outlook::Visible => "likely visible",
+            Outlook::Uncertain => "possibly obscured",
+            Outlook::Obscured => "likely obscured",
+        }
+    }
+}
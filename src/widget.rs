@@ -0,0 +1,102 @@
+//! Compact renderings of the day for status bars and desktop widgets such as
+//! conky or lemonbar, selected by name with `--widget`.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, TimeDelta};
+
+use crate::duration::format_hm;
+use crate::i18n::Lang;
+use crate::phase::Phase;
+
+/// What a widget can show, in the display time zone.
+pub struct Snapshot {
+    pub now: DateTime<FixedOffset>,
+    pub phase: Phase,
+    /// Today's sunrise and sunset, `None` during polar day or night.
+    pub times: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)>,
+    /// Whether `times` are the stand-ins of [`crate::solar::PolarFallback`],
+    /// marked with a `~`.
+    pub fallback: bool,
+    pub day_length: TimeDelta,
+    pub night_length: TimeDelta,
+    /// The next sunrise or sunset after `now` and whether it is a sunrise.
+    pub next: Option<(DateTime<FixedOffset>, bool)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Widget {
+    /// `☀ 05:42–21:13 (15h 31m)`
+    Line,
+    /// `Sunrise 05:42  Sunset 21:13` over `Day 15h 31m  Night 8h 29m`
+    TwoLine,
+    /// `☀ 05:42–21:13, sunset in 3h 12m`
+    Countdown,
+    /// tmux `status-right` segment with colour codes.
+    Tmux,
+}
+
+impl Widget {
+    pub const ALL: [Widget; 4] = [Widget::Line, Widget::TwoLine, Widget::Countdown, Widget::Tmux];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Widget::Line => "line",
+            Widget::TwoLine => "two-line",
+            Widget::Countdown => "countdown",
+            Widget::Tmux => "tmux",
+        }
+    }
+
+    /// The widget with its words in `lang`.
+    pub fn render(&self, s: &Snapshot, lang: Lang) -> String {
+        let (rise, set) = s.times.unzip();
+        let mark = if s.fallback { "~" } else { "" };
+        let (rise, set) = (format!("{}{}", mark, clock(rise)), format!("{}{}", mark, clock(set)));
+        match self {
+            Widget::Line => format!("{} {}–{} ({})", symbol(&s.phase), rise, set, format_hm(s.day_length)),
+            Widget::TwoLine => {
+                let (day, night) = (format_hm(s.day_length), format_hm(s.night_length));
+                lang.format("widget-two-line", &[("rise", &rise), ("set", &set), ("day", &day), ("night", &night)])
+            }
+            Widget::Countdown => {
+                let next = match s.next {
+                    Some((t, rising)) => lang.format(if rising { "widget-sunrise-in" } else { "widget-sunset-in" }, &[("length", &format_hm(t - s.now))]),
+                    None => lang.text("widget-no-next").to_string(),
+                };
+                format!("{} {}–{}, {}", symbol(&s.phase), rise, set, next)
+            }
+            Widget::Tmux => {
+                let colour = match s.phase {
+                    Phase::Day => "yellow",
+                    Phase::Twilight { .. } => "colour208",
+                    Phase::Night => "colour75",
+                };
+                format!("#[fg={}]{} ↑{} ↓{}#[default]", colour, symbol(&s.phase), rise, set)
+            }
+        }
+    }
+}
+
+impl FromStr for Widget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Widget, String> {
+        Widget::ALL.into_iter().find(|w| w.name() == s).ok_or_else(|| {
+            let names: Vec<&str> = Widget::ALL.iter().map(Widget::name).collect();
+            format!("unknown widget `{}` (expected {})", s, names.join(", "))
+        })
+    }
+}
+
+fn clock(t: Option<DateTime<FixedOffset>>) -> String {
+    t.map_or("--:--".to_string(), |t| t.format("%H:%M").to_string())
+}
+
+fn symbol(phase: &Phase) -> char {
+    match phase {
+        Phase::Day => '☀',
+        Phase::Twilight { .. } => '◐',
+        Phase::Night => '☾',
+    }
+}
@@ -0,0 +1,192 @@
+//! Time windows bounded by solar events with offsets, such as
+//! `sunrise-30m to sunset+30m`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::angle::Coordinates;
+use crate::duration::parse_signed;
+use crate::format::FormatOptions;
+use crate::solar::{degrees::crossings_tz, jdn, sunrise_altitude, DaySolution};
+
+/// Drone operating windows of some jurisdictions: name, window and source.
+pub const DRONE_PRESETS: [(&str, &str, &str); 3] = [
+    ("faa", "dawn to dusk", "14 CFR 107.29, civil twilight with anti-collision lighting"),
+    ("faa-2016", "sunrise-30m to sunset+30m", "14 CFR 107.29 before April 2021"),
+    ("daylight", "sunrise to sunset", "daylight-only rules such as India's"),
+];
+
+/// The window of the drone preset `name`, or `name` parsed as a window.
+pub fn drone_window(name: &str) -> Result<Window, String> {
+    match DRONE_PRESETS.iter().find(|(preset, _, _)| preset.eq_ignore_ascii_case(name)) {
+        Some((_, window, _)) => window.parse(),
+        None => name.parse().map_err(|e| {
+            let names: Vec<&str> = DRONE_PRESETS.iter().map(|(preset, _, _)| *preset).collect();
+            format!("{} or a preset ({})", e, names.join(", "))
+        }),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    Sunrise,
+    Sunset,
+    /// Start of morning civil twilight.
+    Dawn,
+    /// End of evening civil twilight.
+    Dusk,
+}
+
+impl Anchor {
+    pub const ALL: [Anchor; 4] = [Anchor::Sunrise, Anchor::Sunset, Anchor::Dawn, Anchor::Dusk];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Anchor::Sunrise => "sunrise",
+            Anchor::Sunset => "sunset",
+            Anchor::Dawn => "dawn",
+            Anchor::Dusk => "dusk",
+        }
+    }
+
+    /// Altitude of the sun's centre at the event.
+    pub fn altitude(&self) -> f64 {
+        match self {
+            Anchor::Sunrise | Anchor::Sunset => sunrise_altitude(0.0),
+            Anchor::Dawn | Anchor::Dusk => -6.0,
+        }
+    }
+
+    /// Time of the event on the day `today`, `None` if the sun does not cross its altitude.
+    pub fn time(&self, lat: f64, long: f64, today: f64) -> Option<DateTime<Utc>> {
+        let (morning, evening) = crossings_tz(lat, long, today, self.altitude(), &Utc)?;
+        Some(if matches!(self, Anchor::Sunrise | Anchor::Dawn) { morning } else { evening })
+    }
+
+    /// Estimated uncertainty of [`Anchor::time`], see [`DaySolution::uncertainty`].
+    pub fn uncertainty(&self, lat: f64, long: f64, today: f64) -> Option<TimeDelta> {
+        DaySolution::from_degrees(lat, long, today).uncertainty(self.altitude())
+    }
+}
+
+impl fmt::Display for Anchor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An event and when it happens, displayed as e.g. `sunrise 05:42:33`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnchorTime {
+    pub anchor: Anchor,
+    pub time: DateTime<Utc>,
+}
+
+impl AnchorTime {
+    /// The event on the day `today`, `None` if the sun does not cross its altitude.
+    pub fn on(anchor: Anchor, at: &Coordinates, today: f64) -> Option<AnchorTime> {
+        anchor.time(at.lat.degrees().0, at.long.degrees().0, today).map(|time| AnchorTime { anchor, time })
+    }
+
+    /// The event with its time of day in `options.zone` to `options.precision`.
+    pub fn format_with(&self, options: &FormatOptions) -> String {
+        format!("{} {}", self.anchor, options.precision.time(&options.zone.convert(&self.time)))
+    }
+}
+
+impl fmt::Display for AnchorTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.format_with(&FormatOptions::default()))
+    }
+}
+
+/// Other names of the events, as in `civil_dawn+1h`.
+const ALIASES: [(&str, Anchor); 4] = [("civil_dawn", Anchor::Dawn), ("civil-dawn", Anchor::Dawn), ("civil_dusk", Anchor::Dusk), ("civil-dusk", Anchor::Dusk)];
+
+/// An event shifted by an offset, e.g. `sunset+30m`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bound {
+    pub anchor: Anchor,
+    pub offset: TimeDelta,
+}
+
+impl Bound {
+    /// Time of the shifted event on the day `today`, `None` if the sun does
+    /// not cross its altitude.
+    pub fn time(&self, lat: f64, long: f64, today: f64) -> Option<DateTime<Utc>> {
+        Some(self.anchor.time(lat, long, today)? + self.offset)
+    }
+}
+
+impl FromStr for Bound {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Bound, String> {
+        // Accept the typographic minus as well
+        let s = s.trim().replace('−', "-").to_lowercase();
+        let (name, anchor) = ALIASES
+            .into_iter()
+            .chain(Anchor::ALL.map(|a| (a.name(), a)))
+            .find(|(name, _)| s.starts_with(name))
+            .ok_or_else(|| {
+                let names: Vec<&str> = Anchor::ALL.iter().map(Anchor::name).collect();
+                format!("`{}` does not start with an event ({})", s, names.join(", "))
+            })?;
+        let offset = match s[name.len()..].trim() {
+            "" => TimeDelta::zero(),
+            rest if rest.starts_with(['+', '-']) => parse_signed(&rest.replace(' ', ""))?,
+            rest => return Err(format!("expected +OFFSET or -OFFSET after {}, found `{}`", name, rest)),
+        };
+        Ok(Bound { anchor, offset })
+    }
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let minutes = self.offset.num_minutes();
+        match minutes {
+            0 => write!(f, "{}", self.anchor.name()),
+            m => write!(f, "{}{:+}m", self.anchor.name(), m),
+        }
+    }
+}
+
+/// From one bound to another on the same day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Window {
+    pub start: Bound,
+    pub end: Bound,
+}
+
+impl Window {
+    /// Start and end of the window on the day `today`.
+    pub fn on(&self, lat: f64, long: f64, today: f64) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        Some((self.start.time(lat, long, today)?, self.end.time(lat, long, today)?))
+    }
+
+    /// Whether `t` falls inside the window of its own or an adjacent day.
+    pub fn contains(&self, lat: f64, long: f64, t: &DateTime<Utc>) -> bool {
+        let today = jdn(t.date_naive());
+        [today - 1.0, today, today + 1.0]
+            .into_iter()
+            .filter_map(|day| self.on(lat, long, day))
+            .any(|(start, end)| start <= *t && *t < end)
+    }
+}
+
+impl FromStr for Window {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Window, String> {
+        let (start, end) = s.split_once(" to ").ok_or_else(|| format!("invalid window `{}` (expected e.g. \"sunrise-30m to sunset+30m\")", s))?;
+        Ok(Window { start: start.parse()?, end: end.parse()? })
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} to {}", self.start, self.end)
+    }
+}